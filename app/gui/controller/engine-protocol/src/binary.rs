@@ -9,6 +9,8 @@ pub mod client;
 pub mod connection;
 pub mod message;
 pub mod serialization;
+pub mod session;
+pub mod transfer;
 pub mod uuid;
 
 pub use client::Client;
@@ -17,3 +19,6 @@ pub use client::MockAPI as MockClient;
 pub use client::Notification;
 pub use client::API;
 pub use connection::Connection;
+pub use session::ResilientClient;
+pub use transfer::FileReader;
+pub use transfer::FileWriter;
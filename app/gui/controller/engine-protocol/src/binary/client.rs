@@ -4,6 +4,7 @@
 use crate::prelude::*;
 
 use crate::binary::message::ErrorPayload;
+use crate::binary::message::FileSegment;
 use crate::binary::message::FromServerPayloadOwned;
 use crate::binary::message::MessageFromServerOwned;
 use crate::binary::message::MessageToServerRef;
@@ -55,6 +56,20 @@ pub type Event = crate::common::event::Event<Notification>;
 
 
 
+// =================
+// === FileChunk ===
+// =================
+
+/// Bytes read from a file segment, with their checksum computed by the Language Server.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FileChunk {
+    pub checksum: Sha3_224,
+    pub bytes:    Vec<u8>,
+}
+
+
+
 // ===========
 // === API ===
 // ===========
@@ -80,6 +95,13 @@ pub trait API {
         bytes: &[u8],
     ) -> StaticBoxFuture<FallibleResult<Sha3_224>>;
 
+    /// Reads the bytes of the given file segment. If the segment extends beyond the end of the
+    /// file, only the bytes up to the end are returned.
+    fn read_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<FileChunk>>;
+
+    /// Computes the checksum of the bytes of the given file segment.
+    fn checksum_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<Sha3_224>>;
+
     /// Asynchronous event stream with notification and errors.
     ///
     /// On a repeated call, previous stream is closed.
@@ -234,6 +256,35 @@ impl API for Client {
         })
     }
 
+    fn read_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<FileChunk>> {
+        let FileSegment { path, byte_offset, length } = segment;
+        info!(self.logger, "Reading {length} bytes from {path} at offset {byte_offset}.");
+        let payload = ToServerPayload::ReadBytes { segment };
+        self.make_request(payload, move |result| {
+            if let FromServerPayloadOwned::ReadBytesReply { checksum, bytes } = result {
+                Ok(FileChunk { checksum: checksum.into(), bytes })
+            } else {
+                Err(RpcError::MismatchedResponseType.into())
+            }
+        })
+    }
+
+    fn checksum_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<Sha3_224>> {
+        let FileSegment { path, byte_offset, length } = segment;
+        info!(
+            self.logger,
+            "Computing checksum of {length} bytes of {path} at offset {byte_offset}."
+        );
+        let payload = ToServerPayload::ChecksumBytes { segment };
+        self.make_request(payload, move |result| {
+            if let FromServerPayloadOwned::ChecksumBytesReply { checksum } = result {
+                Ok(checksum.into())
+            } else {
+                Err(RpcError::MismatchedResponseType.into())
+            }
+        })
+    }
+
     fn event_stream(&self) -> StaticBoxStream<Event> {
         self.handler.event_stream().boxed_local()
     }
@@ -249,6 +300,7 @@ impl API for Client {
 mod tests {
     use super::*;
 
+    use crate::binary::message::EnsoDigest;
     use crate::binary::message::MessageFromServer;
     use crate::binary::message::MessageToServerOwned;
     use crate::binary::message::ToServerPayloadOwned;
@@ -365,6 +417,38 @@ mod tests {
 
 
 
+    #[test]
+    fn test_read_bytes() {
+        let root_id = Uuid::new_v4();
+        let path = Path::new(root_id, &["Main.enso"]);
+        let segment = FileSegment { path, byte_offset: 2, length: 3 };
+        let bytes = Vec::from("llo".as_bytes());
+        let checksum = EnsoDigest { bytes: vec![1, 2, 3] };
+        let expected = FileChunk { checksum: checksum.clone().into(), bytes: bytes.clone() };
+        test_request(
+            |client| client.read_bytes(&segment),
+            expected,
+            ToServerPayloadOwned::ReadBytes { segment: segment.clone() },
+            FromServerPayloadOwned::ReadBytesReply { checksum, bytes },
+        );
+    }
+
+    #[test]
+    fn test_checksum_bytes() {
+        let root_id = Uuid::new_v4();
+        let path = Path::new(root_id, &["Main.enso"]);
+        let segment = FileSegment { path, byte_offset: 0, length: 5 };
+        let checksum = EnsoDigest { bytes: vec![1, 2, 3] };
+        test_request(
+            |client| client.checksum_bytes(&segment),
+            checksum.clone().into(),
+            ToServerPayloadOwned::ChecksumBytes { segment: segment.clone() },
+            FromServerPayloadOwned::ChecksumBytesReply { checksum },
+        );
+    }
+
+
+
     // =============================
     // === Testing Notifications ===
     // =============================
@@ -0,0 +1,159 @@
+//! Module with the [`ResilientClient`]: a binary protocol client which resumes the session after
+//! the connection has been lost and established again.
+
+use crate::prelude::*;
+
+use crate::binary::client::Event;
+use crate::binary::client::FileChunk;
+use crate::binary::client::API;
+use crate::binary::message::FileSegment;
+use crate::language_server::types::Path;
+use crate::types::Sha3_224;
+
+
+
+// =======================
+// === ResilientClient ===
+// =======================
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct ClientData {
+    #[derivative(Debug = "ignore")]
+    client:    Box<dyn API>,
+    /// The ID the session was initialized with.
+    client_id: Cell<Option<Uuid>>,
+}
+
+/// A binary protocol client wrapper which resumes the session after reconnecting.
+///
+/// The only session state of the binary protocol is its initialization: when the wrapped client's
+/// transport is opened again after being closed, the session is initialized again with the same
+/// client ID before the [`Event::Opened`] event is passed further. If it fails, the error is
+/// emitted as an [`Event::Error`] right after.
+///
+/// The session is restored only while the stream returned by [`API::event_stream`] is polled.
+#[derive(Clone, CloneRef, Debug)]
+pub struct ResilientClient {
+    data: Rc<ClientData>,
+}
+
+impl ResilientClient {
+    /// Wrap the given client. The client should not be used directly afterwards, as the session
+    /// initialization would not be recorded.
+    pub fn new(client: impl API + 'static) -> Self {
+        let client = Box::new(client);
+        Self { data: Rc::new(ClientData { client, client_id: default() }) }
+    }
+}
+
+impl API for ResilientClient {
+    fn init(&self, client_id: Uuid) -> StaticBoxFuture<FallibleResult> {
+        let data = Rc::downgrade(&self.data);
+        let init = self.data.client.init(client_id);
+        let tracked = init.map(move |result| {
+            if let (Ok(()), Some(data)) = (&result, data.upgrade()) {
+                data.client_id.set(Some(client_id));
+            }
+            result
+        });
+        tracked.boxed_local()
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> StaticBoxFuture<FallibleResult> {
+        self.data.client.write_file(path, contents)
+    }
+
+    fn read_file(&self, path: &Path) -> StaticBoxFuture<FallibleResult<Vec<u8>>> {
+        self.data.client.read_file(path)
+    }
+
+    fn write_bytes(
+        &self,
+        path: &Path,
+        byte_offset: u64,
+        overwrite: bool,
+        bytes: &[u8],
+    ) -> StaticBoxFuture<FallibleResult<Sha3_224>> {
+        self.data.client.write_bytes(path, byte_offset, overwrite, bytes)
+    }
+
+    fn read_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<FileChunk>> {
+        self.data.client.read_bytes(segment)
+    }
+
+    fn checksum_bytes(&self, segment: &FileSegment) -> StaticBoxFuture<FallibleResult<Sha3_224>> {
+        self.data.client.checksum_bytes(segment)
+    }
+
+    fn event_stream(&self) -> StaticBoxStream<Event> {
+        let weak_data = Rc::downgrade(&self.data);
+        let events = self.data.client.event_stream();
+        let events = events.then(move |event| {
+            let data = if let Event::Opened = event { weak_data.upgrade() } else { None };
+            let client_id = data.as_ref().and_then(|data| data.client_id.get());
+            async move {
+                let reinit = match (data, client_id) {
+                    (Some(data), Some(client_id)) => data.client.init(client_id).await.err(),
+                    _ => None,
+                };
+                let error = reinit.map(Event::Error);
+                futures::stream::iter(std::iter::once(event).chain(error))
+            }
+        });
+        events.flatten().boxed_local()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::binary::MockClient;
+
+    use futures::channel::mpsc::unbounded;
+    use json_rpc::error::RpcError;
+    use mockall::predicate::*;
+
+    fn ready<T: 'static>(t: impl Into<T>) -> StaticBoxFuture<T> {
+        futures::future::ready(t.into()).boxed_local()
+    }
+
+    #[test]
+    fn reinitialize_after_reconnecting() {
+        let client_id = Uuid::from_u128(159);
+        let (events_sender, events) = unbounded();
+        let mut mock = MockClient::new();
+        mock.expect_event_stream().return_once(|| events.boxed_local());
+        // The session is initialized, then resumed successfully, then resuming fails.
+        let init_count = Cell::new(0);
+        mock.expect_init().with(eq(client_id)).times(3).returning(move |_| {
+            init_count.set(init_count.get() + 1);
+            if init_count.get() < 3 {
+                ready(Ok(()))
+            } else {
+                ready(Err(RpcError::new_remote_error(0, "ErrorMessage").into()))
+            }
+        });
+        let client = ResilientClient::new(mock);
+        let mut events = client.event_stream();
+        client.init(client_id).expect_ok();
+
+        events_sender.unbounded_send(Event::Closed).unwrap();
+        assert!(matches!(events.expect_next(), Event::Closed));
+        events_sender.unbounded_send(Event::Opened).unwrap();
+        assert!(matches!(events.expect_next(), Event::Opened));
+        events.expect_pending();
+
+        events_sender.unbounded_send(Event::Opened).unwrap();
+        assert!(matches!(events.expect_next(), Event::Opened));
+        assert!(matches!(events.expect_next(), Event::Error(_)));
+        events.expect_pending();
+    }
+}
@@ -0,0 +1,393 @@
+//! Module with chunked file transfers over the binary protocol.
+//!
+//! Large files are read and written in chunks, each verified with the checksum computed by the
+//! Language Server. When a transfer is interrupted, e.g. by a lost connection, it can be resumed
+//! from the end of the longest prefix of the file which is known to be the same on both sides.
+
+use crate::prelude::*;
+
+use crate::binary::client::RpcError;
+use crate::binary::client::API;
+use crate::binary::message::ErrorPayload;
+use crate::binary::message::FileSegment;
+use crate::language_server::types::Path;
+use crate::types::Sha3_224;
+
+use sha3::Digest;
+
+
+
+// =============
+// === Error ===
+// =============
+
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Fail)]
+#[fail(
+    display = "Wrong checksum of the chunk transferred at offset {}: remote {}, local {}.",
+    offset, remote, local
+)]
+pub struct ChunkChecksumMismatch {
+    pub offset: u64,
+    pub remote: Sha3_224,
+    pub local:  Sha3_224,
+}
+
+/// Check if the error was returned by the Language Server because the requested file segment
+/// reaches beyond the end of the file.
+pub fn is_read_out_of_bounds(error: &failure::Error) -> bool {
+    let remote_error = match error.downcast_ref::<RpcError>() {
+        Some(RpcError::RemoteError(error)) => Some(error),
+        _ => None,
+    };
+    let payload = remote_error.and_then(|error| error.data.as_ref());
+    matches!(payload, Some(ErrorPayload::ReadOOB { .. }))
+}
+
+
+
+// ======================
+// === VerifiedPrefix ===
+// ======================
+
+/// The state of the file checksum computation at the end of some transferred chunk.
+#[derive(Clone, Debug)]
+struct Checkpoint {
+    length: u64,
+    hasher: sha3::Sha3_224,
+}
+
+impl Checkpoint {
+    fn checksum(&self) -> Sha3_224 {
+        self.hasher.clone().into()
+    }
+}
+
+/// The transferred part of the file, with checkpoints at the ends of all transferred chunks.
+#[derive(Clone, Debug)]
+struct VerifiedPrefix {
+    path:        Path,
+    /// Never empty: the first checkpoint is the beginning of the file.
+    checkpoints: Vec<Checkpoint>,
+}
+
+impl VerifiedPrefix {
+    fn new(path: Path) -> Self {
+        let beginning = Checkpoint { length: 0, hasher: default() };
+        Self { path, checkpoints: vec![beginning] }
+    }
+
+    fn last(&self) -> &Checkpoint {
+        self.checkpoints.last().expect("The beginning of the file is always a checkpoint.")
+    }
+
+    fn length(&self) -> u64 {
+        self.last().length
+    }
+
+    fn checksum(&self) -> Sha3_224 {
+        self.last().checksum()
+    }
+
+    fn extend(&mut self, bytes: &[u8]) {
+        let mut hasher = self.last().hasher.clone();
+        hasher.input(bytes);
+        let length = self.length() + bytes.len() as u64;
+        self.checkpoints.push(Checkpoint { length, hasher });
+    }
+
+    /// Drop the checkpoints past the longest prefix which has the same checksum in the remote
+    /// file, and return its length.
+    ///
+    /// If some prefix is the same, so are all the shorter ones, so the prefix is found with a
+    /// binary search. The whole prefix is checked first, as it is the most likely to be intact.
+    async fn resume(&mut self, client: &dyn API) -> FallibleResult<u64> {
+        let mut good = 0;
+        let mut bad = self.checkpoints.len();
+        if self.matches_remote(client, self.last()).await? {
+            good = bad - 1;
+        } else {
+            bad -= 1;
+        }
+        while bad - good > 1 {
+            let middle = (good + bad) / 2;
+            if self.matches_remote(client, &self.checkpoints[middle]).await? {
+                good = middle;
+            } else {
+                bad = middle;
+            }
+        }
+        self.checkpoints.truncate(good + 1);
+        Ok(self.length())
+    }
+
+    async fn matches_remote(
+        &self,
+        client: &dyn API,
+        checkpoint: &Checkpoint,
+    ) -> FallibleResult<bool> {
+        if checkpoint.length == 0 {
+            return Ok(true);
+        }
+        let path = self.path.clone();
+        let segment = FileSegment { path, byte_offset: 0, length: checkpoint.length };
+        match client.checksum_bytes(&segment).await {
+            Ok(remote) => Ok(remote == checkpoint.checksum()),
+            Err(error) if is_read_out_of_bounds(&error) => Ok(false),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+
+
+// ==================
+// === FileWriter ===
+// ==================
+
+/// Writes a file chunk by chunk, verifying the checksum of every written chunk.
+#[derive(Clone, Debug)]
+pub struct FileWriter {
+    prefix:    VerifiedPrefix,
+    overwrite: bool,
+}
+
+impl FileWriter {
+    /// Create a writer of the file at given path. The file is written from the beginning, so it
+    /// should not exist yet.
+    pub fn new(path: Path) -> Self {
+        Self { prefix: VerifiedPrefix::new(path), overwrite: false }
+    }
+
+    /// The path of the written file.
+    pub fn path(&self) -> &Path {
+        &self.prefix.path
+    }
+
+    /// The number of bytes written and verified so far.
+    pub fn bytes_written(&self) -> u64 {
+        self.prefix.length()
+    }
+
+    /// The checksum of all the bytes written so far.
+    pub fn checksum(&self) -> Sha3_224 {
+        self.prefix.checksum()
+    }
+
+    /// Append the next chunk to the file. When it fails, the writer should be resumed with
+    /// [`Self::resume`] before writing anything more.
+    pub async fn write_chunk(&mut self, client: &dyn API, bytes: &[u8]) -> FallibleResult {
+        let offset = self.bytes_written();
+        let remote = client.write_bytes(&self.prefix.path, offset, self.overwrite, bytes).await?;
+        let local = Sha3_224::new(bytes);
+        if remote == local {
+            self.prefix.extend(bytes);
+            Ok(())
+        } else {
+            Err(ChunkChecksumMismatch { offset, remote, local }.into())
+        }
+    }
+
+    /// Find the longest part of the remote file that matches the chunks written so far, and
+    /// continue writing from its end. Returns the number of bytes kept: the following
+    /// [`Self::write_chunk`] calls should pass the data starting from this offset.
+    pub async fn resume(&mut self, client: &dyn API) -> FallibleResult<u64> {
+        // The remote file may contain some bytes past the verified part, e.g. from a chunk
+        // written just before the connection was lost.
+        self.overwrite = true;
+        self.prefix.resume(client).await
+    }
+}
+
+
+
+// ==================
+// === FileReader ===
+// ==================
+
+/// Reads a file chunk by chunk, verifying the checksum of every read chunk.
+#[derive(Clone, Debug)]
+pub struct FileReader {
+    prefix:     VerifiedPrefix,
+    chunk_size: u64,
+}
+
+impl FileReader {
+    /// Create a reader of the file at given path, reading chunks of at most `chunk_size` bytes.
+    pub fn new(path: Path, chunk_size: u64) -> Self {
+        Self { prefix: VerifiedPrefix::new(path), chunk_size }
+    }
+
+    /// The path of the read file.
+    pub fn path(&self) -> &Path {
+        &self.prefix.path
+    }
+
+    /// The number of bytes read and verified so far.
+    pub fn bytes_read(&self) -> u64 {
+        self.prefix.length()
+    }
+
+    /// The checksum of all the bytes read so far.
+    pub fn checksum(&self) -> Sha3_224 {
+        self.prefix.checksum()
+    }
+
+    /// Read the next chunk of the file. Returns [`None`] once the end of the file is reached.
+    pub async fn read_chunk(&mut self, client: &dyn API) -> FallibleResult<Option<Vec<u8>>> {
+        let path = self.prefix.path.clone();
+        let byte_offset = self.bytes_read();
+        let segment = FileSegment { path, byte_offset, length: self.chunk_size };
+        let chunk = match client.read_bytes(&segment).await {
+            Ok(chunk) => chunk,
+            Err(error) if is_read_out_of_bounds(&error) => return Ok(None),
+            Err(error) => return Err(error),
+        };
+        let local = Sha3_224::new(&chunk.bytes);
+        if chunk.checksum != local {
+            let remote = chunk.checksum;
+            Err(ChunkChecksumMismatch { offset: byte_offset, remote, local }.into())
+        } else if chunk.bytes.is_empty() {
+            Ok(None)
+        } else {
+            self.prefix.extend(&chunk.bytes);
+            Ok(Some(chunk.bytes))
+        }
+    }
+
+    /// Find the longest part of the remote file that is still the same as the chunks read so
+    /// far, e.g. after reconnecting, and continue reading from its end. Returns the number of
+    /// bytes kept: the data read past this offset should be discarded.
+    pub async fn resume(&mut self, client: &dyn API) -> FallibleResult<u64> {
+        self.prefix.resume(client).await
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::binary::client::FileChunk;
+    use crate::binary::MockClient;
+
+    use json_rpc::messages::Error;
+    use std::cell::RefCell;
+
+    fn ready<T: 'static>(t: impl Into<T>) -> StaticBoxFuture<T> {
+        futures::future::ready(t.into()).boxed_local()
+    }
+
+    fn read_oob_error(file_length: u64) -> failure::Error {
+        let data = Some(ErrorPayload::ReadOOB { file_length });
+        let message = "Read out of bounds.".into();
+        RpcError::RemoteError(Error { code: 1004, message, data }).into()
+    }
+
+    /// A mock of the Language Server storing a single file.
+    fn mock_file_server(file: Rc<RefCell<Vec<u8>>>) -> MockClient {
+        let mut client = MockClient::new();
+        let segment_of = |file: &Rc<RefCell<Vec<u8>>>, segment: &FileSegment| {
+            let file = file.borrow();
+            let start = segment.byte_offset as usize;
+            let end = (start + segment.length as usize).min(file.len());
+            (start <= file.len()).then(|| file[start..end].to_vec())
+        };
+        let read_file = file.clone_ref();
+        client.expect_read_bytes().returning_st(move |segment| {
+            match segment_of(&read_file, segment) {
+                Some(bytes) => ready(Ok(FileChunk { checksum: Sha3_224::new(&bytes), bytes })),
+                None => ready(Err(read_oob_error(read_file.borrow().len() as u64))),
+            }
+        });
+        let checksum_file = file.clone_ref();
+        client.expect_checksum_bytes().returning_st(move |segment| {
+            let end = segment.byte_offset + segment.length;
+            match segment_of(&checksum_file, segment) {
+                Some(bytes) if end <= bytes.len() as u64 => ready(Ok(Sha3_224::new(&bytes))),
+                _ => ready(Err(read_oob_error(checksum_file.borrow().len() as u64))),
+            }
+        });
+        client.expect_write_bytes().returning_st(move |_, offset, overwrite, bytes| {
+            let mut file = file.borrow_mut();
+            let offset = offset as usize;
+            assert!(overwrite || offset == file.len(), "Writing past the end without overwrite.");
+            file.truncate(offset);
+            file.extend_from_slice(bytes);
+            ready(Ok(Sha3_224::new(bytes)))
+        });
+        client
+    }
+
+    #[test]
+    fn read_file_in_chunks() {
+        let file = Rc::new(RefCell::new(b"Hello, world!".to_vec()));
+        let client = mock_file_server(file);
+        let path = Path::new(default(), &["Main.enso"]);
+        let mut reader = FileReader::new(path, 5);
+        let mut chunks = vec![];
+        while let Some(chunk) = reader.read_chunk(&client).expect_ok() {
+            chunks.push(String::from_utf8(chunk).unwrap());
+        }
+        assert_eq!(chunks, vec!["Hello", ", wor", "ld!"]);
+        assert_eq!(reader.bytes_read(), 13);
+        assert_eq!(reader.checksum(), Sha3_224::new(b"Hello, world!"));
+    }
+
+    #[test]
+    fn reject_chunk_with_wrong_checksum() {
+        let mut client = MockClient::new();
+        client.expect_read_bytes().returning(|_| {
+            let checksum = Sha3_224::new(b"other");
+            ready(Ok(FileChunk { checksum, bytes: b"bytes".to_vec() }))
+        });
+        let path = Path::new(default(), &["Main.enso"]);
+        let mut reader = FileReader::new(path, 5);
+        let error = reader.read_chunk(&client).expect_err();
+        assert!(error.downcast_ref::<ChunkChecksumMismatch>().is_some());
+        assert_eq!(reader.bytes_read(), 0);
+    }
+
+    #[test]
+    fn resume_reading_changed_file() {
+        let file = Rc::new(RefCell::new(b"Hello, world!".to_vec()));
+        let client = mock_file_server(file.clone_ref());
+        let path = Path::new(default(), &["Main.enso"]);
+        let mut reader = FileReader::new(path, 2);
+        for _ in 0..5 {
+            reader.read_chunk(&client).expect_ok();
+        }
+        assert_eq!(reader.bytes_read(), 10);
+        assert_eq!(reader.resume(&client).expect_ok(), 10);
+
+        file.borrow_mut()[5] = b';';
+        assert_eq!(reader.resume(&client).expect_ok(), 4);
+        let rest = reader.read_chunk(&client).expect_ok();
+        assert_eq!(rest, Some(b"o;".to_vec()));
+    }
+
+    #[test]
+    fn resume_interrupted_writing() {
+        let file = Rc::new(RefCell::new(vec![]));
+        let client = mock_file_server(file.clone_ref());
+        let path = Path::new(default(), &["Main.enso"]);
+        let mut writer = FileWriter::new(path);
+        for chunk in ["Hel", "lo,", " wo"] {
+            writer.write_chunk(&client, chunk.as_bytes()).expect_ok();
+        }
+        // The remote file lost the end of the last chunk, and got some garbage instead.
+        file.borrow_mut().truncate(7);
+        file.borrow_mut().extend_from_slice(b"###");
+        assert_eq!(writer.resume(&client).expect_ok(), 6);
+        for chunk in [" wo", "rld!"] {
+            writer.write_chunk(&client, chunk.as_bytes()).expect_ok();
+        }
+        assert_eq!(*file.borrow(), b"Hello, world!".to_vec());
+        assert_eq!(writer.checksum(), Sha3_224::new(b"Hello, world!"));
+    }
+}
@@ -145,8 +145,13 @@ where
                         Disposition::Ignore => {}
                     }
                 }
-                TransportEvent::Opened => {}
-                TransportEvent::Closed => self.emit_event(Event::Closed),
+                TransportEvent::Opened => self.emit_event(Event::Opened),
+                TransportEvent::Closed => {
+                    // Dropping all ongoing calls will cancel their futures, as they will never
+                    // be replied.
+                    self.ongoing_calls.clear();
+                    self.emit_event(Event::Closed);
+                }
             }
         });
     }
@@ -313,5 +318,11 @@ mod tests {
         let event = events.expect_next();
         assert!(matches!(event, Event::Closed), "Event was: {:?}", event);
         events.expect_pending();
+
+        transport.mock_connection_reopened();
+        runner.expect_pending();
+        let event = events.expect_next();
+        assert!(matches!(event, Event::Opened), "Event was: {:?}", event);
+        events.expect_pending();
     }
 }
@@ -14,6 +14,7 @@
 pub mod connection;
 pub mod constants;
 pub mod response;
+pub mod session;
 
 
 
@@ -22,6 +23,7 @@ mod tests;
 pub mod types;
 
 pub use connection::Connection;
+pub use session::ResilientClient;
 pub use types::*;
 
 use crate::prelude::*;
@@ -116,9 +118,12 @@ trait API {
     fn apply_text_file_edit(&self, edit: FileEdit) -> ();
 
     /// Create a new execution context. Return capabilities executionContext/canModify and
-    /// executionContext/receivesUpdates containing freshly created ContextId
+    /// executionContext/receivesUpdates containing freshly created ContextId. If the `context_id`
+    /// is given, the context is created with that id; the call succeeds without changes if such a
+    /// context already exists.
     #[MethodInput=CreateExecutionContextInput, rpc_name="executionContext/create"]
-    fn create_execution_context(&self) -> response::CreateExecutionContext;
+    fn create_execution_context
+    (&self, context_id: Option<ContextId>) -> response::CreateExecutionContext;
 
     /// Destroy an execution context and free its resources.
     #[MethodInput=DestroyExecutionContextInput, rpc_name="executionContext/destroy"]
@@ -132,6 +137,12 @@ trait API {
     #[MethodInput=PopFromExecutionContextInput, rpc_name="executionContext/pop"]
     fn pop_from_execution_context(&self, context_id: ContextId) -> ();
 
+    /// Invalidate the cached values of the given expressions and recompute the execution context.
+    /// If no expressions are given, only the outdated values are recomputed.
+    #[MethodInput=RecomputeInput, rpc_name="executionContext/recompute"]
+    fn recompute
+    (&self, context_id: ContextId, invalidated_expressions: Option<InvalidatedExpressions>) -> ();
+
     /// Attach a visualisation, potentially preprocessed by some arbitrary Enso code, to a given
     /// node in the program.
     #[MethodInput=AttachVisualisationInput, rpc_name="executionContext/attachVisualisation"]
@@ -151,6 +162,15 @@ trait API {
     fn modify_visualisation
     (&self, visualisation_id: Uuid, visualisation_config: VisualisationConfiguration) -> ();
 
+    /// Evaluate the visualisation expression once, in the context of the given node. The result is
+    /// sent as a visualisation update, after which the visualisation is detached.
+    #[MethodInput=ExecuteExpressionInput, rpc_name="executionContext/executeExpression"]
+    fn execute_expression
+    ( &self
+    , visualisation_id     : Uuid
+    , expression_id        : ExpressionId
+    , visualisation_config : VisualisationConfiguration) -> ();
+
     /// Obtain the full suggestions database.
     #[MethodInput=GetSuggestionsDatabaseInput, rpc_name="search/getSuggestionsDatabase"]
     fn get_suggestions_database(&self) -> response::GetSuggestionDatabase;
@@ -174,6 +194,57 @@ trait API {
     /// Get the list of component groups available in runtime.
     #[MethodInput=GetComponentGroups, rpc_name="executionContext/getComponentGroups"]
     fn get_component_groups(&self, context_id: ContextId) -> response::GetComponentGroups;
+
+    /// Initialize the version control of the project under the given content root.
+    #[MethodInput=InitVcsInput, rpc_name="vcs/init"]
+    fn init_vcs(&self, root: Path) -> ();
+
+    /// Save the current state of the project, optionally naming the save point.
+    #[MethodInput=SaveVcsInput, rpc_name="vcs/save"]
+    fn save_vcs(&self, root: Path, name: Option<String>) -> response::SaveVcs;
+
+    /// Check whether the project has been changed since the last save.
+    #[MethodInput=VcsStatusInput, rpc_name="vcs/status"]
+    fn vcs_status(&self, root: Path) -> response::VcsStatus;
+
+    /// Restore the project to the given save point, or to the last one if none is given.
+    #[MethodInput=RestoreVcsInput, rpc_name="vcs/restore"]
+    fn restore_vcs(&self, root: Path, commit_id: Option<String>) -> response::RestoreVcs;
+
+    /// List the save points of the project, the most recent first.
+    #[MethodInput=ListVcsSavesInput, rpc_name="vcs/list"]
+    fn list_vcs_saves(&self, root: Path, limit: Option<usize>) -> response::ListVcsSaves;
+
+    /// List the libraries available in the local library repositories.
+    #[MethodInput=ListLocalLibrariesInput, rpc_name="library/listLocal"]
+    fn list_local_libraries(&self) -> response::ListLocalLibraries;
+
+    /// Create a new local library, which may then be edited and published.
+    #[MethodInput=CreateLibraryInput, rpc_name="library/create"]
+    fn create_library
+    ( &self
+    , namespace   : String
+    , name        : String
+    , authors     : Vec<Contact>
+    , maintainers : Vec<Contact>
+    , license     : String
+    ) -> ();
+
+    /// Publish the local library to the library repository accepting uploads at `upload_url`.
+    #[MethodInput=PublishLibraryInput, rpc_name="library/publish"]
+    fn publish_library
+    ( &self
+    , namespace                  : String
+    , name                       : String
+    , auth_token                 : String
+    , upload_url                 : String
+    , bump_version_after_publish : Option<bool>
+    ) -> ();
+
+    /// Install the library with its dependencies before it is imported, so the import does not
+    /// block the execution. The progress is reported with `task/*` notifications.
+    #[MethodInput=PreinstallLibraryInput, rpc_name="library/preinstall"]
+    fn preinstall_library(&self, namespace: String, name: String) -> ();
 }}
 
 
@@ -106,3 +106,42 @@ pub struct Completion {
 pub struct GetComponentGroups {
     pub component_groups: Vec<LibraryComponentGroup>,
 }
+
+/// Response of `save_vcs` method.
+pub type SaveVcs = SavepointInfo;
+
+/// Response of `vcs_status` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct VcsStatus {
+    /// Whether there are changes made since the last save.
+    pub dirty:     bool,
+    pub changed:   Vec<Path>,
+    pub last_save: SavepointInfo,
+}
+
+/// Response of `restore_vcs` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct RestoreVcs {
+    /// The files changed by the restoration.
+    pub changed: Vec<Path>,
+}
+
+/// Response of `list_vcs_saves` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ListVcsSaves {
+    pub saves: Vec<SavepointInfo>,
+}
+
+/// Response of `list_local_libraries` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct ListLocalLibraries {
+    pub local_libraries: Vec<LibraryEntry>,
+}
@@ -0,0 +1,778 @@
+//! Module with the [`ResilientClient`]: a Language Server client which resumes the session after
+//! the connection has been lost and established again.
+//!
+//! When the connection is dropped, the Language Server forgets everything that was bound to it:
+//! the protocol initialization, the acquired capabilities, the opened text files, the execution
+//! contexts and the attached visualisations. The [`ResilientClient`] records all of these while
+//! passing the calls to the wrapped client, and restores them once the transport is opened again.
+
+use crate::prelude::*;
+
+use crate::language_server::response;
+use crate::language_server::types::CapabilityRegistration;
+use crate::language_server::types::Contact;
+use crate::language_server::types::ContentRoot;
+use crate::language_server::types::ContextId;
+use crate::language_server::types::ExpressionId;
+use crate::language_server::types::FileEdit;
+use crate::language_server::types::FileSystemObject;
+use crate::language_server::types::InvalidatedExpressions;
+use crate::language_server::types::Path;
+use crate::language_server::types::Position;
+use crate::language_server::types::RegisterOptions;
+use crate::language_server::types::StackItem;
+use crate::language_server::types::SuggestionEntryType;
+use crate::language_server::types::VisualisationConfiguration;
+use crate::language_server::Event;
+use crate::language_server::API;
+use crate::types::Sha3_224;
+
+use futures::channel::mpsc::UnboundedSender;
+use futures::future::join_all;
+use json_rpc::api::Result;
+
+
+
+// ==================
+// === Resumption ===
+// ==================
+
+/// The outcome of resuming the session after the connection has been established again.
+#[derive(Debug, Default)]
+pub struct Resumption {
+    /// The content roots sent by the Language Server when initializing the protocol again.
+    pub content_roots:  Vec<ContentRoot>,
+    /// The opened files which were modified while the connection was lost: their current version
+    /// differs from the last one known to the client, so their content should be reloaded.
+    pub outdated_files: Vec<Path>,
+    /// Errors of the calls which failed to restore some part of the session.
+    pub errors:         Vec<failure::Error>,
+}
+
+
+
+// ====================
+// === SessionState ===
+// ====================
+
+/// A visualisation attached through the client.
+#[derive(Clone, Debug)]
+struct AttachedVisualisation {
+    expression_id: ExpressionId,
+    config:        VisualisationConfiguration,
+}
+
+/// The part of the Language Server session which is bound to the connection.
+#[derive(Clone, Debug, Default)]
+struct SessionState {
+    /// The ID the protocol connection was initialized with.
+    client_id:      Option<Uuid>,
+    /// The capabilities acquired either explicitly or when creating execution contexts.
+    capabilities:   Vec<CapabilityRegistration>,
+    /// The opened text files with their last version known to the client.
+    open_files:     HashMap<Path, Sha3_224>,
+    /// The created execution contexts with their stacks.
+    contexts:       HashMap<ContextId, Vec<StackItem>>,
+    /// The attached visualisations by their IDs.
+    visualisations: HashMap<Uuid, AttachedVisualisation>,
+}
+
+impl SessionState {
+    fn acquired(&mut self, capability: CapabilityRegistration) {
+        if !self.capabilities.contains(&capability) {
+            self.capabilities.push(capability);
+        }
+    }
+
+    fn destroyed_context(&mut self, context_id: ContextId) {
+        let options = RegisterOptions::ExecutionContextId { context_id };
+        self.capabilities.retain(|capability| capability.register_options != options);
+        self.contexts.remove(&context_id);
+        self.visualisations.retain(|_, vis| vis.config.execution_context_id != context_id);
+    }
+
+    /// Check whether the capability is granted by creating one of the execution contexts.
+    fn is_granted_by_context(&self, capability: &CapabilityRegistration) -> bool {
+        match capability.register_options {
+            RegisterOptions::ExecutionContextId { context_id } =>
+                self.contexts.contains_key(&context_id),
+            _ => false,
+        }
+    }
+}
+
+
+
+// =======================
+// === ResilientClient ===
+// =======================
+
+#[derive(Derivative)]
+#[derivative(Debug)]
+struct ClientData {
+    #[derivative(Debug = "ignore")]
+    client:      Box<dyn API>,
+    state:       RefCell<SessionState>,
+    resumptions: RefCell<Option<UnboundedSender<Resumption>>>,
+}
+
+impl ClientData {
+    /// Restore the session on the reopened connection: initialize the protocol, reopen the text
+    /// files, acquire the capabilities, recreate the execution contexts with their stacks and
+    /// attach the visualisations again.
+    ///
+    /// The order matters: the capabilities of the files can be acquired only for the opened files,
+    /// and the visualisations can be attached only in the existing contexts. The capabilities of
+    /// the execution contexts are granted when recreating them, so they are not acquired again.
+    async fn resume(&self) -> Resumption {
+        let state = self.state.borrow().clone();
+        let mut resumption = Resumption::default();
+        if let Some(client_id) = state.client_id {
+            match self.client.init_protocol_connection(&client_id).await {
+                Ok(response) => resumption.content_roots = response.content_roots,
+                Err(error) => {
+                    // Nothing else can be restored if the protocol is not initialized.
+                    resumption.errors.push(error.into());
+                    return resumption;
+                }
+            }
+        }
+
+        let files = state.open_files.iter().map(|(path, version)| {
+            self.client.open_text_file(path).map(move |result| (path, version, result))
+        });
+        for (path, version, result) in join_all(files).await {
+            match result {
+                Ok(opened) if opened.current_version != *version => {
+                    resumption.outdated_files.push(path.clone());
+                    let mut state = self.state.borrow_mut();
+                    if let Some(known_version) = state.open_files.get_mut(path) {
+                        *known_version = opened.current_version;
+                    }
+                }
+                Ok(_) => {}
+                Err(error) => resumption.errors.push(error.into()),
+            }
+        }
+
+        let capabilities = state.capabilities.iter().filter(|c| !state.is_granted_by_context(c));
+        let capabilities = capabilities.map(|capability| {
+            self.client.acquire_capability(&capability.method, &capability.register_options)
+        });
+        let acquired = join_all(capabilities).await;
+        resumption.errors.extend(acquired.into_iter().filter_map(|r| r.err()).map(Into::into));
+
+        let contexts = state.contexts.iter().map(|(id, stack)| self.recreate_context(*id, stack));
+        let recreated = join_all(contexts).await;
+        resumption.errors.extend(recreated.into_iter().filter_map(|r| r.err()).map(Into::into));
+
+        let visualisations = state
+            .visualisations
+            .iter()
+            .map(|(id, vis)| self.client.attach_visualisation(id, &vis.expression_id, &vis.config));
+        let attached = join_all(visualisations).await;
+        resumption.errors.extend(attached.into_iter().filter_map(|r| r.err()).map(Into::into));
+        resumption
+    }
+
+    /// Create the execution context with the given id again, and push the items of its stack.
+    async fn recreate_context(&self, context_id: ContextId, stack: &[StackItem]) -> Result<()> {
+        self.client.create_execution_context(&Some(context_id)).await?;
+        for item in stack {
+            self.client.push_to_execution_context(&context_id, item).await?;
+        }
+        Ok(())
+    }
+}
+
+/// A Language Server client wrapper which resumes the session after reconnecting.
+///
+/// All calls are passed to the wrapped client, while the session state they change is recorded.
+/// When the wrapped client's transport is opened again after being closed, the session is restored
+/// before the [`Event::Opened`] event is passed further, and the outcome is sent to the
+/// [`ResilientClient::resumptions`] stream. The requests which were ongoing when the connection
+/// has been lost fail with [`json_rpc::RpcError::LostConnection`]; they are not repeated.
+///
+/// The session is restored only while the stream returned by [`API::events`] is polled.
+#[derive(Clone, CloneRef, Debug)]
+pub struct ResilientClient {
+    data: Rc<ClientData>,
+}
+
+impl ResilientClient {
+    /// Wrap the given client. The client should not be used directly afterwards, as the session
+    /// changes would not be recorded.
+    pub fn new(client: impl API + 'static) -> Self {
+        let client = Box::new(client);
+        let state = default();
+        let resumptions = default();
+        Self { data: Rc::new(ClientData { client, state, resumptions }) }
+    }
+
+    /// Returns a stream of the outcomes of the session resumptions.
+    ///
+    /// On a repeated call, the previous stream is closed.
+    pub fn resumptions(&self) -> StaticBoxStream<Resumption> {
+        let (sender, receiver) = futures::channel::mpsc::unbounded();
+        *self.data.resumptions.borrow_mut() = Some(sender);
+        receiver.boxed_local()
+    }
+
+    /// Pass the result of the call further, recording the session changes of a successful one.
+    fn track<T: 'static>(
+        &self,
+        call: StaticBoxFuture<Result<T>>,
+        update: impl FnOnce(&mut SessionState, &T) + 'static,
+    ) -> StaticBoxFuture<Result<T>> {
+        let data = Rc::downgrade(&self.data);
+        let tracked = call.map(move |result| {
+            if let (Ok(value), Some(data)) = (&result, data.upgrade()) {
+                update(&mut data.state.borrow_mut(), value);
+            }
+            result
+        });
+        tracked.boxed_local()
+    }
+}
+
+impl API for ResilientClient {
+    fn init_protocol_connection<'a>(
+        &'a self,
+        client_id: &'a Uuid,
+    ) -> StaticBoxFuture<Result<response::InitProtocolConnection>> {
+        let id = *client_id;
+        let call = self.data.client.init_protocol_connection(client_id);
+        self.track(call, move |state, _| state.client_id = Some(id))
+    }
+
+    fn copy_file<'a>(&'a self, from: &'a Path, to: &'a Path) -> StaticBoxFuture<Result<()>> {
+        self.data.client.copy_file(from, to)
+    }
+
+    fn delete_file<'a>(&'a self, path: &'a Path) -> StaticBoxFuture<Result<()>> {
+        self.data.client.delete_file(path)
+    }
+
+    fn file_exists<'a>(&'a self, path: &'a Path) -> StaticBoxFuture<Result<response::FileExists>> {
+        self.data.client.file_exists(path)
+    }
+
+    fn file_list<'a>(&'a self, path: &'a Path) -> StaticBoxFuture<Result<response::FileList>> {
+        self.data.client.file_list(path)
+    }
+
+    fn move_file<'a>(&'a self, from: &'a Path, to: &'a Path) -> StaticBoxFuture<Result<()>> {
+        self.data.client.move_file(from, to)
+    }
+
+    fn read_file<'a>(&'a self, path: &'a Path) -> StaticBoxFuture<Result<response::Read>> {
+        self.data.client.read_file(path)
+    }
+
+    fn file_info<'a>(&'a self, path: &'a Path) -> StaticBoxFuture<Result<response::FileInfo>> {
+        self.data.client.file_info(path)
+    }
+
+    fn file_checksum<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> StaticBoxFuture<Result<response::FileChecksum>> {
+        self.data.client.file_checksum(path)
+    }
+
+    fn create_file<'a>(&'a self, object: &'a FileSystemObject) -> StaticBoxFuture<Result<()>> {
+        self.data.client.create_file(object)
+    }
+
+    fn write_file<'a>(
+        &'a self,
+        path: &'a Path,
+        contents: &'a String,
+    ) -> StaticBoxFuture<Result<()>> {
+        self.data.client.write_file(path, contents)
+    }
+
+    fn acquire_capability<'a>(
+        &'a self,
+        method: &'a String,
+        register_options: &'a RegisterOptions,
+    ) -> StaticBoxFuture<Result<()>> {
+        let method_owned = method.clone();
+        let register_options_owned = register_options.clone();
+        let call = self.data.client.acquire_capability(method, register_options);
+        self.track(call, move |state, _| {
+            let capability = CapabilityRegistration {
+                method:           method_owned,
+                register_options: register_options_owned,
+            };
+            state.acquired(capability)
+        })
+    }
+
+    fn open_text_file<'a>(
+        &'a self,
+        path: &'a Path,
+    ) -> StaticBoxFuture<Result<response::OpenTextFile>> {
+        let path_owned = path.clone();
+        let call = self.data.client.open_text_file(path);
+        self.track(call, move |state, opened| {
+            state.open_files.insert(path_owned, opened.current_version.clone());
+        })
+    }
+
+    fn close_text_file<'a>(&'a self, path: &'a Path) -> StaticBoxFuture<Result<()>> {
+        let path_owned = path.clone();
+        let call = self.data.client.close_text_file(path);
+        self.track(call, move |state, _| {
+            state.open_files.remove(&path_owned);
+        })
+    }
+
+    fn save_text_file<'a>(
+        &'a self,
+        path: &'a Path,
+        current_version: &'a Sha3_224,
+    ) -> StaticBoxFuture<Result<()>> {
+        self.data.client.save_text_file(path, current_version)
+    }
+
+    fn apply_text_file_edit<'a>(&'a self, edit: &'a FileEdit) -> StaticBoxFuture<Result<()>> {
+        let path = edit.path.clone();
+        let new_version = edit.new_version.clone();
+        let call = self.data.client.apply_text_file_edit(edit);
+        self.track(call, move |state, _| {
+            if let Some(version) = state.open_files.get_mut(&path) {
+                *version = new_version;
+            }
+        })
+    }
+
+    fn create_execution_context<'a>(
+        &'a self,
+        context_id: &'a Option<ContextId>,
+    ) -> StaticBoxFuture<Result<response::CreateExecutionContext>> {
+        let call = self.data.client.create_execution_context(context_id);
+        self.track(call, |state, created| {
+            state.contexts.entry(created.context_id).or_default();
+            state.acquired(created.can_modify.clone());
+            state.acquired(created.receives_updates.clone());
+        })
+    }
+
+    fn destroy_execution_context<'a>(
+        &'a self,
+        context_id: &'a ContextId,
+    ) -> StaticBoxFuture<Result<()>> {
+        let id = *context_id;
+        let call = self.data.client.destroy_execution_context(context_id);
+        self.track(call, move |state, _| state.destroyed_context(id))
+    }
+
+    fn push_to_execution_context<'a>(
+        &'a self,
+        context_id: &'a ContextId,
+        stack_item: &'a StackItem,
+    ) -> StaticBoxFuture<Result<()>> {
+        let id = *context_id;
+        let item = stack_item.clone();
+        let call = self.data.client.push_to_execution_context(context_id, stack_item);
+        self.track(call, move |state, _| {
+            if let Some(stack) = state.contexts.get_mut(&id) {
+                stack.push(item);
+            }
+        })
+    }
+
+    fn pop_from_execution_context<'a>(
+        &'a self,
+        context_id: &'a ContextId,
+    ) -> StaticBoxFuture<Result<()>> {
+        let id = *context_id;
+        let call = self.data.client.pop_from_execution_context(context_id);
+        self.track(call, move |state, _| {
+            if let Some(stack) = state.contexts.get_mut(&id) {
+                stack.pop();
+            }
+        })
+    }
+
+    fn recompute<'a>(
+        &'a self,
+        context_id: &'a ContextId,
+        invalidated_expressions: &'a Option<InvalidatedExpressions>,
+    ) -> StaticBoxFuture<Result<()>> {
+        self.data.client.recompute(context_id, invalidated_expressions)
+    }
+
+    fn attach_visualisation<'a>(
+        &'a self,
+        visualisation_id: &'a Uuid,
+        expression_id: &'a Uuid,
+        visualisation_config: &'a VisualisationConfiguration,
+    ) -> StaticBoxFuture<Result<()>> {
+        let id = *visualisation_id;
+        let visualisation = AttachedVisualisation {
+            expression_id: *expression_id,
+            config:        visualisation_config.clone(),
+        };
+        let call = self.data.client.attach_visualisation(
+            visualisation_id,
+            expression_id,
+            visualisation_config,
+        );
+        self.track(call, move |state, _| {
+            state.visualisations.insert(id, visualisation);
+        })
+    }
+
+    fn detach_visualisation<'a>(
+        &'a self,
+        context_id: &'a Uuid,
+        visualisation_id: &'a Uuid,
+        expression_id: &'a Uuid,
+    ) -> StaticBoxFuture<Result<()>> {
+        let id = *visualisation_id;
+        let call =
+            self.data.client.detach_visualisation(context_id, visualisation_id, expression_id);
+        self.track(call, move |state, _| {
+            state.visualisations.remove(&id);
+        })
+    }
+
+    fn modify_visualisation<'a>(
+        &'a self,
+        visualisation_id: &'a Uuid,
+        visualisation_config: &'a VisualisationConfiguration,
+    ) -> StaticBoxFuture<Result<()>> {
+        let id = *visualisation_id;
+        let config = visualisation_config.clone();
+        let call = self.data.client.modify_visualisation(visualisation_id, visualisation_config);
+        self.track(call, move |state, _| {
+            if let Some(visualisation) = state.visualisations.get_mut(&id) {
+                visualisation.config = config;
+            }
+        })
+    }
+
+    fn execute_expression<'a>(
+        &'a self,
+        visualisation_id: &'a Uuid,
+        expression_id: &'a ExpressionId,
+        visualisation_config: &'a VisualisationConfiguration,
+    ) -> StaticBoxFuture<Result<()>> {
+        let config = visualisation_config;
+        self.data.client.execute_expression(visualisation_id, expression_id, config)
+    }
+
+    fn get_suggestions_database(&self) -> StaticBoxFuture<Result<response::GetSuggestionDatabase>> {
+        self.data.client.get_suggestions_database()
+    }
+
+    fn get_suggestions_database_version(
+        &self,
+    ) -> StaticBoxFuture<Result<response::GetSuggestionDatabaseVersion>> {
+        self.data.client.get_suggestions_database_version()
+    }
+
+    fn completion<'a>(
+        &'a self,
+        file: &'a Path,
+        position: &'a Position,
+        self_type: &'a Option<String>,
+        return_type: &'a Option<String>,
+        tags: &'a Option<Vec<SuggestionEntryType>>,
+    ) -> StaticBoxFuture<Result<response::Completion>> {
+        self.data.client.completion(file, position, self_type, return_type, tags)
+    }
+
+    fn get_component_groups<'a>(
+        &'a self,
+        context_id: &'a ContextId,
+    ) -> StaticBoxFuture<Result<response::GetComponentGroups>> {
+        self.data.client.get_component_groups(context_id)
+    }
+
+    fn init_vcs<'a>(&'a self, root: &'a Path) -> StaticBoxFuture<Result<()>> {
+        self.data.client.init_vcs(root)
+    }
+
+    fn save_vcs<'a>(
+        &'a self,
+        root: &'a Path,
+        name: &'a Option<String>,
+    ) -> StaticBoxFuture<Result<response::SaveVcs>> {
+        self.data.client.save_vcs(root, name)
+    }
+
+    fn vcs_status<'a>(&'a self, root: &'a Path) -> StaticBoxFuture<Result<response::VcsStatus>> {
+        self.data.client.vcs_status(root)
+    }
+
+    fn restore_vcs<'a>(
+        &'a self,
+        root: &'a Path,
+        commit_id: &'a Option<String>,
+    ) -> StaticBoxFuture<Result<response::RestoreVcs>> {
+        self.data.client.restore_vcs(root, commit_id)
+    }
+
+    fn list_vcs_saves<'a>(
+        &'a self,
+        root: &'a Path,
+        limit: &'a Option<usize>,
+    ) -> StaticBoxFuture<Result<response::ListVcsSaves>> {
+        self.data.client.list_vcs_saves(root, limit)
+    }
+
+    fn list_local_libraries(&self) -> StaticBoxFuture<Result<response::ListLocalLibraries>> {
+        self.data.client.list_local_libraries()
+    }
+
+    fn create_library<'a>(
+        &'a self,
+        namespace: &'a String,
+        name: &'a String,
+        authors: &'a Vec<Contact>,
+        maintainers: &'a Vec<Contact>,
+        license: &'a String,
+    ) -> StaticBoxFuture<Result<()>> {
+        self.data.client.create_library(namespace, name, authors, maintainers, license)
+    }
+
+    fn publish_library<'a>(
+        &'a self,
+        namespace: &'a String,
+        name: &'a String,
+        auth_token: &'a String,
+        upload_url: &'a String,
+        bump_version_after_publish: &'a Option<bool>,
+    ) -> StaticBoxFuture<Result<()>> {
+        let bump_version = bump_version_after_publish;
+        self.data.client.publish_library(namespace, name, auth_token, upload_url, bump_version)
+    }
+
+    fn preinstall_library<'a>(
+        &'a self,
+        namespace: &'a String,
+        name: &'a String,
+    ) -> StaticBoxFuture<Result<()>> {
+        self.data.client.preinstall_library(namespace, name)
+    }
+
+    fn events(&self) -> StaticBoxStream<Event> {
+        let weak_data = Rc::downgrade(&self.data);
+        let events = self.data.client.events();
+        let events = events.then(move |event| {
+            let data = if let Event::Opened = event { weak_data.upgrade() } else { None };
+            async move {
+                if let Some(data) = data {
+                    let resumption = data.resume().await;
+                    if let Some(sender) = &*data.resumptions.borrow() {
+                        channel::emit(sender, resumption);
+                    }
+                }
+                event
+            }
+        });
+        events.boxed_local()
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::language_server::types::ExplicitCall;
+    use crate::language_server::types::MethodPointer;
+    use crate::language_server::Client;
+
+    use futures::task::LocalSpawnExt;
+    use json_rpc::messages::Message;
+    use json_rpc::messages::RequestMessage;
+    use json_rpc::test_util::transport::mock::MockTransport;
+    use serde_json::json;
+    use serde_json::Value;
+
+
+
+    // ===============
+    // === Fixture ===
+    // ===============
+
+    struct Fixture {
+        transport: MockTransport,
+        client:    ResilientClient,
+        events:    StaticBoxStream<Event>,
+        executor:  futures::executor::LocalPool,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let transport = MockTransport::new();
+            let inner = Client::new(transport.clone());
+            let executor = futures::executor::LocalPool::new();
+            executor.spawner().spawn_local(inner.runner()).unwrap();
+            let client = ResilientClient::new(inner);
+            let events = client.events();
+            Self { transport, client, events, executor }
+        }
+
+        /// Expect a request of the given method, reply to it with the given result and return its
+        /// parameters.
+        fn reply(&mut self, method: &str, result: Value) -> Value {
+            let request = self.transport.expect_json_message::<RequestMessage<Value>>();
+            assert_eq!(request.method, method);
+            self.transport.mock_peer_json_message(Message::new_success(request.id, result));
+            self.run();
+            request.params.clone()
+        }
+
+        fn run(&mut self) {
+            self.executor.run_until_stalled();
+            // Events are polled like they would be by the client's user.
+            while let std::task::Poll::Ready(Some(_)) = self.events.manual_poll_next() {}
+            self.executor.run_until_stalled();
+        }
+    }
+
+    fn main_path() -> Path {
+        Path { root_id: default(), segments: vec!["Main.enso".into()] }
+    }
+
+
+
+    // =============
+    // === Tests ===
+    // =============
+
+    #[test]
+    fn resume_session_after_reconnecting() {
+        let mut fixture = Fixture::new();
+        let client_id = Uuid::from_u128(1);
+        let context_id = Uuid::from_u128(2);
+        let visualisation_id = Uuid::from_u128(3);
+        let expression_id = Uuid::from_u128(4);
+        let path = main_path();
+        let mut resumptions = fixture.client.resumptions();
+
+        let mut init = fixture.client.init_protocol_connection(&client_id);
+        fixture.reply("session/initProtocolConnection", json!({"contentRoots": []}));
+        init.expect_ok();
+
+        let mut open = fixture.client.open_text_file(&path);
+        let opened = json!({"writeCapability":null, "content":"main = 1", "currentVersion":"v1"});
+        fixture.reply("text/openFile", opened);
+        open.expect_ok();
+        let can_edit = "text/canEdit".to_owned();
+        let file_options = RegisterOptions::Path { path: path.clone() };
+        let mut acquire = fixture.client.acquire_capability(&can_edit, &file_options);
+        fixture.reply("capability/acquire", json!(null));
+        acquire.expect_ok();
+
+        let mut create = fixture.client.create_execution_context(&None);
+        let capability =
+            |method: &str| json!({"method": method, "registerOptions": {"contextId": context_id}});
+        let created = json!({
+            "contextId"       : context_id,
+            "canModify"       : capability("executionContext/canModify"),
+            "receivesUpdates" : capability("executionContext/receivesUpdates"),
+        });
+        fixture.reply("executionContext/create", created.clone());
+        create.expect_ok();
+
+        let module = "Test.Main".to_owned();
+        let expression = MethodPointer {
+            module:          module.clone(),
+            defined_on_type: module,
+            name:            "visualise".to_owned(),
+        };
+        let stack_item = StackItem::ExplicitCall(ExplicitCall {
+            method_pointer:                   expression.clone(),
+            this_argument_expression:         None,
+            positional_arguments_expressions: vec![],
+        });
+        let mut push = fixture.client.push_to_execution_context(&context_id, &stack_item);
+        fixture.reply("executionContext/push", json!(null));
+        push.expect_ok();
+        let config = VisualisationConfiguration {
+            execution_context_id: context_id,
+            expression,
+            positional_arguments_expressions: vec![],
+        };
+        let mut attach =
+            fixture.client.attach_visualisation(&visualisation_id, &expression_id, &config);
+        fixture.reply("executionContext/attachVisualisation", json!(null));
+        attach.expect_ok();
+
+        // The connection is lost in the middle of a request.
+        fixture.transport.mock_drop_after(1);
+        let mut lost = fixture.client.file_exists(&path);
+        fixture.transport.expect_text_message();
+        fixture.run();
+        lost.expect_err();
+
+        fixture.transport.mock_connection_reopened();
+        fixture.run();
+        // The files are reopened before acquiring their capabilities, and the contexts are
+        // recreated before attaching their visualisations.
+        let params = fixture.reply("session/initProtocolConnection", json!({"contentRoots": []}));
+        assert_eq!(params, json!({ "clientId": client_id }));
+        let opened = json!({"writeCapability":null, "content":"main = 2", "currentVersion":"v2"});
+        let params = fixture.reply("text/openFile", opened);
+        assert_eq!(params["path"], serde_json::to_value(&path).unwrap());
+        // The capabilities of the context are granted when recreating it.
+        let params = fixture.reply("capability/acquire", json!(null));
+        assert_eq!(params["method"], json!(can_edit));
+        let params = fixture.reply("executionContext/create", created);
+        assert_eq!(params, json!({ "contextId": context_id }));
+        let params = fixture.reply("executionContext/push", json!(null));
+        assert_eq!(params["contextId"], json!(context_id));
+        assert_eq!(params["stackItem"], serde_json::to_value(&stack_item).unwrap());
+        let params = fixture.reply("executionContext/attachVisualisation", json!(null));
+        assert_eq!(params["visualisationId"], json!(visualisation_id));
+
+        let resumption = resumptions.expect_next();
+        assert_eq!(resumption.outdated_files, vec![path]);
+        assert!(resumption.errors.is_empty());
+        assert!(fixture.transport.with_mut_data(|data| data.sent_text_msgs.is_empty()));
+    }
+
+    #[test]
+    fn forget_closed_and_destroyed_resources() {
+        let mut fixture = Fixture::new();
+        let client_id = Uuid::from_u128(1);
+        let context_id = Uuid::from_u128(2);
+        let path = main_path();
+
+        let mut init = fixture.client.init_protocol_connection(&client_id);
+        fixture.reply("session/initProtocolConnection", json!({"contentRoots": []}));
+        init.expect_ok();
+        let mut open = fixture.client.open_text_file(&path);
+        let opened = json!({"writeCapability":null, "content":"main = 1", "currentVersion":"v1"});
+        fixture.reply("text/openFile", opened);
+        open.expect_ok();
+        let mut close = fixture.client.close_text_file(&path);
+        fixture.reply("text/closeFile", json!(null));
+        close.expect_ok();
+        let method = "executionContext/canModify".to_owned();
+        let options = RegisterOptions::ExecutionContextId { context_id };
+        let mut acquire = fixture.client.acquire_capability(&method, &options);
+        fixture.reply("capability/acquire", json!(null));
+        acquire.expect_ok();
+        let mut destroy = fixture.client.destroy_execution_context(&context_id);
+        fixture.reply("executionContext/destroy", json!(null));
+        destroy.expect_ok();
+
+        fixture.transport.mock_connection_closed();
+        fixture.transport.mock_connection_reopened();
+        fixture.run();
+        fixture.reply("session/initProtocolConnection", json!({"contentRoots": []}));
+        assert!(fixture.transport.with_mut_data(|data| data.sent_text_msgs.is_empty()));
+    }
+}
@@ -372,9 +372,9 @@ fn test_execution_context() {
     let create_execution_context_response =
         response::CreateExecutionContext { context_id, can_modify, receives_updates };
     test_request(
-        |client| client.create_execution_context(),
+        |client| client.create_execution_context(&None),
         "executionContext/create",
-        json!({}),
+        json!({ "contextId": null }),
         json!({
             "contextId" : "00000000-0000-0000-0000-000000000000",
             "canModify" : {
@@ -422,6 +422,28 @@ fn test_execution_context() {
         unit_json.clone(),
         (),
     );
+    let all = Some(InvalidatedExpressions::All);
+    test_request(
+        |client| client.recompute(&context_id, &all),
+        "executionContext/recompute",
+        json!({
+            "contextId"              : "00000000-0000-0000-0000-000000000000",
+            "invalidatedExpressions" : "all"
+        }),
+        unit_json.clone(),
+        (),
+    );
+    let invalidated = Some(InvalidatedExpressions::Expressions(vec![expression_id]));
+    test_request(
+        |client| client.recompute(&context_id, &invalidated),
+        "executionContext/recompute",
+        json!({
+            "contextId"              : "00000000-0000-0000-0000-000000000000",
+            "invalidatedExpressions" : ["00000000-0000-0000-0000-000000000000"]
+        }),
+        unit_json.clone(),
+        (),
+    );
     let visualisation_id = uuid::Uuid::default();
     let expression_id = uuid::Uuid::default();
     let visualization_function = "foo";
@@ -458,6 +480,27 @@ fn test_execution_context() {
         unit_json.clone(),
         (),
     );
+    test_request(
+        |client| {
+            client.execute_expression(&visualisation_id, &expression_id, &visualisation_config)
+        },
+        "executionContext/executeExpression",
+        json!({
+            "visualisationId"     : "00000000-0000-0000-0000-000000000000",
+            "expressionId"        : "00000000-0000-0000-0000-000000000000",
+            "visualisationConfig" : {
+                "executionContextId"  : "00000000-0000-0000-0000-000000000000",
+                "expression"          : {
+                    "module"        : "[Foo.Bar.Baz]",
+                    "definedOnType" : "[Foo.Bar.Baz]",
+                    "name"          : "foo"
+                },
+                "positionalArgumentsExpressions" : ["1", "2", "3"]
+            }
+        }),
+        unit_json.clone(),
+        (),
+    );
     test_request(
         |client| client.detach_visualisation(&context_id, &visualisation_id, &expression_id),
         "executionContext/detachVisualisation",
@@ -602,3 +645,193 @@ fn test_execution_context() {
         (),
     );
 }
+
+#[test]
+fn test_vcs_requests() {
+    let root_id = uuid::Uuid::parse_str("00000000-0000-0000-0000-000000000000");
+    let root_id = root_id.expect("Couldn't parse uuid.");
+    let root = Path { root_id, segments: vec![] };
+    let main = Path { root_id, segments: vec!["Main.enso".into()] };
+    let root_json = json!({"rootId":"00000000-0000-0000-0000-000000000000","segments":[]});
+    let main_json =
+        json!({"rootId":"00000000-0000-0000-0000-000000000000","segments":["Main.enso"]});
+    let savepoint = SavepointInfo { commit_id: "abc".into(), message: "First".into() };
+    let savepoint_json = json!({"commitId":"abc","message":"First"});
+
+    test_request(
+        |client| client.init_vcs(&root),
+        "vcs/init",
+        json!({ "root": root_json }),
+        json!(null),
+        (),
+    );
+    test_request(
+        |client| client.save_vcs(&root, &Some("First".into())),
+        "vcs/save",
+        json!({"root":root_json,"name":"First"}),
+        savepoint_json.clone(),
+        savepoint.clone(),
+    );
+    let status = response::VcsStatus {
+        dirty:     true,
+        changed:   vec![main.clone()],
+        last_save: savepoint,
+    };
+    test_request(
+        |client| client.vcs_status(&root),
+        "vcs/status",
+        json!({ "root": root_json }),
+        json!({"dirty":true,"changed":[main_json],"lastSave":savepoint_json}),
+        status,
+    );
+    test_request(
+        |client| client.restore_vcs(&root, &None),
+        "vcs/restore",
+        json!({"root":root_json,"commitId":null}),
+        json!({ "changed": [main_json] }),
+        response::RestoreVcs { changed: vec![main] },
+    );
+    test_request(
+        |client| client.list_vcs_saves(&root, &Some(1)),
+        "vcs/list",
+        json!({"root":root_json,"limit":1}),
+        json!({"saves":[]}),
+        response::ListVcsSaves { saves: vec![] },
+    );
+}
+
+#[test]
+fn test_library_requests() {
+    let namespace = "local".to_owned();
+    let name = "Lib".to_owned();
+    let local = LibraryEntry {
+        namespace: namespace.clone(),
+        name:      name.clone(),
+        version:   LibraryVersion::LocalLibraryVersion,
+        is_cached: true,
+    };
+    let published = LibraryEntry {
+        namespace: "Standard".into(),
+        name:      "Base".into(),
+        version:   LibraryVersion::PublishedLibraryVersion {
+            version:        "0.1.0".into(),
+            repository_url: "https://libraries.example.com".into(),
+        },
+        is_cached: false,
+    };
+    test_request(
+        |client| client.list_local_libraries(),
+        "library/listLocal",
+        json!({}),
+        json!({"localLibraries":[
+            {
+                "namespace" : "local",
+                "name"      : "Lib",
+                "version"   : {"type":"LocalLibraryVersion"},
+                "isCached"  : true
+            },
+            {
+                "namespace" : "Standard",
+                "name"      : "Base",
+                "version"   : {
+                    "type"          : "PublishedLibraryVersion",
+                    "version"       : "0.1.0",
+                    "repositoryUrl" : "https://libraries.example.com"
+                },
+                "isCached"  : false
+            }
+        ]}),
+        response::ListLocalLibraries { local_libraries: vec![local, published] },
+    );
+    let author = Contact { name: Some("Author".into()), email: None };
+    let license = "MIT".to_owned();
+    test_request(
+        |client| client.create_library(&namespace, &name, &vec![author], &vec![], &license),
+        "library/create",
+        json!({
+            "namespace"   : "local",
+            "name"        : "Lib",
+            "authors"     : [{"name":"Author","email":null}],
+            "maintainers" : [],
+            "license"     : "MIT"
+        }),
+        json!(null),
+        (),
+    );
+    let token = "token".to_owned();
+    let upload_url = "https://libraries.example.com/upload".to_owned();
+    test_request(
+        |client| client.publish_library(&namespace, &name, &token, &upload_url, &Some(true)),
+        "library/publish",
+        json!({
+            "namespace"               : "local",
+            "name"                    : "Lib",
+            "authToken"               : "token",
+            "uploadUrl"               : "https://libraries.example.com/upload",
+            "bumpVersionAfterPublish" : true
+        }),
+        json!(null),
+        (),
+    );
+    test_request(
+        |client| client.preinstall_library(&namespace, &name),
+        "library/preinstall",
+        json!({"namespace":"local","name":"Lib"}),
+        json!(null),
+        (),
+    );
+}
+
+#[test]
+fn test_task_notifications() {
+    let task_id = Uuid::parse_str("b36dea0b-b75a-40cf-aaad-5fcdf29a0573").unwrap();
+    let started = json!({
+        "jsonrpc" : "2.0",
+        "method"  : "task/started",
+        "params"  : {
+            "taskId"           : task_id,
+            "relatedOperation" : "library/preinstall",
+            "unit"             : "Bytes",
+            "total"            : 1024
+        }
+    });
+    let progress = json!({
+        "jsonrpc" : "2.0",
+        "method"  : "task/progress-update",
+        "params"  : {"taskId":task_id,"message":"Downloading","done":512}
+    });
+    let finished = json!({
+        "jsonrpc" : "2.0",
+        "method"  : "task/finished",
+        "params"  : {"taskId":task_id,"message":null,"success":true}
+    });
+
+    let mut fixture = setup_language_server();
+    let mut stream = fixture.client.events();
+    for notification in [started, progress, finished] {
+        fixture.transport.mock_peer_json_message(notification);
+    }
+    fixture.executor.run_until_stalled();
+
+    let expected = [
+        Notification::TaskStarted(TaskStarted {
+            task_id,
+            related_operation: "library/preinstall".into(),
+            unit: ProgressUnit::Bytes,
+            total: Some(1024),
+        }),
+        Notification::TaskProgressUpdate(TaskProgressUpdate {
+            task_id,
+            message: Some("Downloading".into()),
+            done: 512,
+        }),
+        Notification::TaskFinished(TaskFinished { task_id, message: None, success: true }),
+    ];
+    for expected in expected {
+        match stream.expect_next() {
+            Event::Notification(notification) => assert_eq!(notification, expected),
+            event => panic!("Expected a notification, got {:?}", event),
+        }
+    }
+    stream.expect_pending();
+}
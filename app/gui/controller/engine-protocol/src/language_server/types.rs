@@ -157,6 +157,19 @@ pub enum Notification {
     /// visualisation.
     #[serde(rename = "executionContext/visualisationEvaluationFailed")]
     VisualisationEvaluationFailed(VisualisationEvaluationFailed),
+
+    /// Sent from server to the client to inform that a long running operation, like a library
+    /// installation, has started.
+    #[serde(rename = "task/started")]
+    TaskStarted(TaskStarted),
+
+    /// Sent from server to the client to inform about the progress of a long running operation.
+    #[serde(rename = "task/progress-update")]
+    TaskProgressUpdate(TaskProgressUpdate),
+
+    /// Sent from server to the client to inform that a long running operation has finished.
+    #[serde(rename = "task/finished")]
+    TaskFinished(TaskFinished),
 }
 
 /// Sent from the server to the client to inform about a failure during execution of an execution
@@ -181,6 +194,48 @@ pub struct VisualisationEvaluationFailed {
     pub diagnostic:       Option<Diagnostic>,
 }
 
+/// Sent from server to the client to inform that a long running operation has started.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[allow(missing_docs)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskStarted {
+    pub task_id:           Uuid,
+    /// The name of the method which started the operation, e.g. `library/preinstall`.
+    pub related_operation: String,
+    pub unit:              ProgressUnit,
+    /// The total amount of work in `unit`s, if known.
+    pub total:             Option<u64>,
+}
+
+/// Sent from server to the client to inform about the progress of a long running operation.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[allow(missing_docs)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskProgressUpdate {
+    pub task_id: Uuid,
+    pub message: Option<String>,
+    /// The amount of work done so far in the units given by [`TaskStarted::unit`].
+    pub done:    u64,
+}
+
+/// Sent from server to the client to inform that a long running operation has finished.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[allow(missing_docs)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskFinished {
+    pub task_id: Uuid,
+    pub message: Option<String>,
+    pub success: bool,
+}
+
+/// The unit in which the progress of a long running operation is reported.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[allow(missing_docs)]
+pub enum ProgressUnit {
+    Bytes,
+    Other,
+}
+
 
 
 // =======================
@@ -589,6 +644,48 @@ impl TextEdit {
         TextEdit { range: source_text_range.into(), text: target_text }
     }
 
+    /// Compute the minimal edits transforming the `source` string into the `target` one, as found
+    /// by [`enso_text::diff`]. Unlike [`Self::from_prefix_postfix_differences`], every group of
+    /// changed lines gets its own edit.
+    ///
+    /// All ranges refer to positions in the `source` string. The edits are ordered from the end of
+    /// the string, so applying them one after another gives the `target` string.
+    ///
+    /// Example:
+    /// ```
+    /// # use engine_protocol::language_server::{TextEdit, Position, TextRange};
+    /// let source = "1\n2\n3\n4\n";
+    /// let target = "1\n20\n3\n";
+    /// let edits = TextEdit::from_differences(source, target);
+    /// let removed_range = TextRange {
+    ///     start: Position { line: 3, character: 0 },
+    ///     end:   Position { line: 4, character: 0 },
+    /// };
+    /// let changed_range = TextRange {
+    ///     start: Position { line: 1, character: 1 },
+    ///     end:   Position { line: 1, character: 1 },
+    /// };
+    /// let removal = TextEdit { range: removed_range, text: "".to_string() };
+    /// let change = TextEdit { range: changed_range, text: "0".to_string() };
+    /// assert_eq!(edits, vec![removal, change]);
+    /// ```
+    pub fn from_differences(
+        source: impl Into<enso_text::Text>,
+        target: impl Into<enso_text::Text>,
+    ) -> Vec<TextEdit> {
+        use enso_text::Range;
+
+        let source = source.into();
+        let target = target.into();
+        let changes = source.diff(&target).into_iter().rev();
+        let edits = changes.map(|change| {
+            let start = source.location_of_byte_offset_snapped(change.range.start);
+            let end = source.location_of_byte_offset_snapped(change.range.end);
+            TextEdit { range: Range::new(start, end).into(), text: change.text }
+        });
+        edits.collect()
+    }
+
     /// Return the edit moved by the given number of lines.
     ///
     /// Example:
@@ -657,6 +754,53 @@ pub struct VisualisationConfiguration {
     pub positional_arguments_expressions: Vec<String>,
 }
 
+/// The expressions whose cached values are invalidated when recomputing an execution context.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum InvalidatedExpressions {
+    /// Invalidate the values of all the expressions.
+    All,
+    /// Invalidate the values of the given expressions.
+    Expressions(Vec<ExpressionId>),
+}
+
+impl InvalidatedExpressions {
+    /// The keyword used by the protocol to represent [`InvalidatedExpressions::All`].
+    const ALL: &'static str = "all";
+}
+
+impl Serialize for InvalidatedExpressions {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::All => serializer.serialize_str(Self::ALL),
+            Self::Expressions(ids) => ids.serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for InvalidatedExpressions {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Repr {
+            Keyword(String),
+            Expressions(Vec<ExpressionId>),
+        }
+        match Repr::deserialize(deserializer)? {
+            Repr::Keyword(keyword) if keyword == Self::ALL => Ok(Self::All),
+            Repr::Keyword(keyword) => {
+                let unexpected = serde::de::Unexpected::Str(&keyword);
+                Err(serde::de::Error::invalid_value(unexpected, &"\"all\" or expression IDs"))
+            }
+            Repr::Expressions(ids) => Ok(Self::Expressions(ids)),
+        }
+    }
+}
+
 /// Used to enter deeper in the execution context stack. In general, all consequent stack items
 /// should be `LocalCall`s.
 #[derive(Hash, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -1121,6 +1265,59 @@ pub struct LibraryComponentGroup {
 }
 
 
+// ===========
+// === VCS ===
+// ===========
+
+/// A save point of the project, created with the `vcs/save` method.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct SavepointInfo {
+    pub commit_id: String,
+    pub message:   String,
+}
+
+
+
+// ===============
+// === Library ===
+// ===============
+
+/// A library available in the local library repositories.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct LibraryEntry {
+    pub namespace: String,
+    pub name:      String,
+    pub version:   LibraryVersion,
+    /// Whether the library is available in one of the local caches.
+    pub is_cached: bool,
+}
+
+/// The version of a library.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[allow(missing_docs)]
+pub enum LibraryVersion {
+    /// A library which is being edited locally and has no version yet.
+    LocalLibraryVersion,
+    /// A library downloaded from the given repository.
+    #[serde(rename_all = "camelCase")]
+    PublishedLibraryVersion { version: String, repository_url: String },
+}
+
+/// The contact information of a library author or maintainer.
+#[derive(Hash, Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+#[allow(missing_docs)]
+pub struct Contact {
+    pub name:  Option<String>,
+    pub email: Option<String>,
+}
+
+
 // ======================
 // === Test Utilities ===
 // ======================
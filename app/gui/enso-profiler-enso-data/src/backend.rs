@@ -1,5 +1,10 @@
 //! Defines a metadata type for representing backend messages.
 
+use crate::Metadata;
+
+use enso_profiler_data as data;
+use enso_profiler_data::export::chrome_trace;
+
 
 
 // ===============
@@ -28,3 +33,41 @@ pub enum Direction {
     /// From other process to logging process.
     Response,
 }
+
+
+
+// ====================
+// === Chrome trace ===
+// ====================
+
+/// Category of the trace events of backend messages.
+const TRACE_CATEGORY: &str = "backend";
+
+/// Add the backend messages logged in the profile to the trace, as async events spanning from
+/// each request to its response. The messages without a request id are not paired; they are
+/// present in the trace only as the metadata instant events.
+pub fn add_to_trace(
+    trace: &mut chrome_trace::Trace,
+    process: chrome_trace::Process,
+    profile: &data::Profile<Metadata>,
+) {
+    let mut messages = profile
+        .metadata()
+        .filter_map(|metadata| match &metadata.data {
+            Metadata::BackendMessage(message) => Some((metadata.time, message)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    messages.sort_by_key(|(time, _)| *time);
+    for (time, message) in messages {
+        if let Some(request_id) = &message.request_id {
+            let phase = match message.direction {
+                Direction::Request => chrome_trace::Phase::AsyncBegin,
+                Direction::Response => chrome_trace::Phase::AsyncEnd,
+            };
+            let name = &message.endpoint;
+            let event = process.async_event(phase, name, TRACE_CATEGORY, request_id, time);
+            trace.push(event);
+        }
+    }
+}
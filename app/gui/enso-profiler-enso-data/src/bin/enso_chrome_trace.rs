@@ -0,0 +1,57 @@
+//! Tool that generates Chrome Trace Event files from profiles containing Enso application data.
+//!
+//! # Usage
+//!
+//! The tool reads a multi-process profile from stdin, and writes a trace to stdout. In addition to
+//! the intervals and metadata of each process (see [`enso_profiler_data::export::chrome_trace`]),
+//! the trace contains the backend messages between the Language Server and the Engine, shown as
+//! async events spanning from each request to its response.
+//!
+//! For example, with the profile prepared as described in the `message_beanpoles` tool:
+//!
+//! ```console
+//! profiler/enso_data $ cargo run --bin enso_chrome_trace < ~/fullprofile.json > ~/trace.json
+//! ```
+
+// === Features ===
+#![feature(test)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler_data as data;
+use enso_profiler_data::export::chrome_trace;
+use enso_profiler_enso_data as enso_data;
+use enso_profiler_enso_data::backend;
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() {
+    use std::io::Read;
+
+    let mut profile = String::new();
+    std::io::stdin().read_to_string(&mut profile).unwrap();
+    let mut trace = chrome_trace::Trace::default();
+    for profile in data::parse_multiprocess_profile::<enso_data::Metadata>(&profile) {
+        let profile = match profile {
+            Ok(profile) => profile,
+            Err(data::Error::RecoverableFormatError { with_missing_data, .. }) => with_missing_data,
+            Err(e) => panic!("{}", e),
+        };
+        let process = trace.add_profile(&profile);
+        backend::add_to_trace(&mut trace, process, &profile);
+    }
+    serde_json::to_writer(std::io::stdout(), &trace).unwrap();
+}
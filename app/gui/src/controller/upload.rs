@@ -17,7 +17,6 @@ use engine_protocol::language_server::FileSystemObject;
 use engine_protocol::language_server::Path;
 use engine_protocol::types::Sha3_224;
 use json_rpc::error::RpcError;
-use sha3::Digest;
 
 
 
@@ -82,7 +81,7 @@ pub struct FileUploadProcess<DataProvider> {
     file:            FileToUpload<DataProvider>,
     remote_path:     Path,
     bytes_uploaded:  u64,
-    checksum:        sha3::Sha3_224,
+    writer:          binary::FileWriter,
 }
 
 /// The information if the uploading is finished or not, returned from
@@ -105,16 +104,8 @@ impl<DP: DataProvider> FileUploadProcess<DP> {
     ) -> Self {
         let logger = Logger::new_sub(parent, "FileUploadProcess");
         let bytes_uploaded = 0;
-        let checksum = sha3::Sha3_224::new();
-        Self {
-            logger,
-            bin_connection,
-            json_connection,
-            file,
-            remote_path,
-            bytes_uploaded,
-            checksum,
-        }
+        let writer = binary::FileWriter::new(remote_path.clone());
+        Self { logger, bin_connection, json_connection, file, remote_path, bytes_uploaded, writer }
     }
 
     /// Upload next chunk. Returns information if all data has been uploaded.
@@ -132,9 +123,7 @@ impl<DP: DataProvider> FileUploadProcess<DP> {
                     "Received chunk of {self.file.name} of size {data.len()} \
                     uploading to {self.remote_path:?}: {data:?}"
                 );
-                let offset = self.bytes_uploaded;
-                self.bin_connection.write_bytes(&self.remote_path, offset, false, &data).await?;
-                self.checksum.input(&data);
+                self.write_chunk(&data).await?;
                 self.bytes_uploaded += data.len() as u64;
                 Ok(UploadingState::NotFinished)
             }
@@ -159,9 +148,34 @@ impl<DP: DataProvider> FileUploadProcess<DP> {
         }
     }
 
+    /// Write the chunk to the remote file. If it fails, e.g. because the connection was lost and
+    /// restored in the meantime, the upload is resumed and the chunk is written once again.
+    ///
+    /// The previous chunks are not kept, so the upload fails if any of them did not reach the
+    /// remote file intact.
+    async fn write_chunk(&mut self, data: &[u8]) -> FallibleResult {
+        let client = &**self.bin_connection;
+        let offset = self.writer.bytes_written();
+        match self.writer.write_chunk(client, data).await {
+            Ok(()) => Ok(()),
+            Err(err) => {
+                warning!(
+                    self.logger,
+                    "Failed to upload a chunk of {self.file.name} at offset {offset}: {err}. \
+                    Resuming the upload."
+                );
+                if self.writer.resume(client).await? == offset {
+                    self.writer.write_chunk(client, data).await
+                } else {
+                    Err(err)
+                }
+            }
+        }
+    }
+
     async fn check_checksum(&mut self) -> FallibleResult {
         let remote = self.json_connection.file_checksum(&self.remote_path).await?.checksum;
-        let local = Into::<Sha3_224>::into(std::mem::take(&mut self.checksum));
+        let local = self.writer.checksum();
         if remote != local {
             Err(ChecksumMismatch { remote, local }.into())
         } else {
@@ -389,6 +403,7 @@ mod test {
     use crate::executor::test_utils::TestWithLocalPoolExecutor;
     use crate::test::mock;
 
+    use engine_protocol::binary::message::FileSegment;
     use engine_protocol::language_server::response;
     use engine_protocol::language_server::FileAttributes;
     use engine_protocol::types::UTCDateTime;
@@ -511,8 +526,17 @@ mod test {
         fn new(logger: impl AnyLogger, data: TestData) -> Self {
             let mut binary_cli = binary::MockClient::new();
             let json_cli = language_server::MockClient::default();
-            json_cli.require_all_calls();
             data.setup_uploading_expectations(&json_cli, &mut binary_cli);
+            Self::new_with_clients(logger, data, binary_cli, json_cli)
+        }
+
+        fn new_with_clients(
+            logger: impl AnyLogger,
+            data: TestData,
+            binary_cli: binary::MockClient,
+            json_cli: language_server::MockClient,
+        ) -> Self {
+            json_cli.require_all_calls();
             let (file, provider_sink) = data.file_to_upload_async();
             let bin_con = Rc::new(binary::Connection::new_mock(binary_cli));
             let json_con = Rc::new(language_server::Connection::new_mock(json_cli));
@@ -567,6 +591,57 @@ mod test {
     }
 
 
+    #[test]
+    fn corrupted_chunk_should_be_uploaded_again() {
+        let logger = Logger::new("test::corrupted_chunk_should_be_uploaded_again");
+        let data = TestData::new(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        let mut binary_cli = binary::MockClient::new();
+        let json_cli = language_server::MockClient::default();
+        let mut seq = Sequence::new();
+        let path = data.path.clone();
+        let expect_write = |binary_cli: &mut binary::MockClient,
+                            seq: &mut Sequence,
+                            chunk: &[u8],
+                            offset: u64,
+                            overwrite: bool,
+                            reply: &[u8]| {
+            let path = path.clone();
+            let chunk = chunk.to_vec();
+            let reply = Sha3_224::new(reply);
+            binary_cli
+                .expect_write_bytes()
+                .withf(move |p, off, ow, ch| {
+                    *p == path && ch == chunk && *off == offset && *ow == overwrite
+                })
+                .times(1)
+                .in_sequence(seq)
+                .returning(move |_, _, _, _| future::ready(Ok(reply.clone())).boxed_local());
+        };
+        // The second chunk is corrupted, and written again after checking the first one.
+        expect_write(&mut binary_cli, &mut seq, &[1, 2, 3], 0, false, &[1, 2, 3]);
+        expect_write(&mut binary_cli, &mut seq, &[4, 5, 6], 3, false, &[4, 5]);
+        let uploaded_prefix =
+            FileSegment { path: path.clone(), byte_offset: 0, length: 3 };
+        binary_cli
+            .expect_checksum_bytes()
+            .withf(move |segment| *segment == uploaded_prefix)
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| future::ready(Ok(Sha3_224::new(&[1, 2, 3]))).boxed_local());
+        expect_write(&mut binary_cli, &mut seq, &[4, 5, 6], 3, true, &[4, 5, 6]);
+        let checksum = data.checksum.clone();
+        json_cli.expect.file_checksum(move |p| {
+            assert_eq!(*p, path);
+            Ok(response::FileChecksum { checksum })
+        });
+        let mut test = UploadingFixture::new_with_clients(logger, data, binary_cli, json_cli);
+
+        assert_eq!(test.next_chunk_result().unwrap(), UploadingState::NotFinished);
+        assert_eq!(test.next_chunk_result().unwrap(), UploadingState::NotFinished);
+        assert_eq!(test.next_chunk_result().unwrap(), UploadingState::Finished);
+    }
+
+
     // === NodeFromDroppedFileHandler Tests ===
 
     #[wasm_bindgen_test]
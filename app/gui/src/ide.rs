@@ -29,6 +29,9 @@ pub use initializer::Initializer;
 pub const BACKEND_DISCONNECTED_MESSAGE: &str =
     "Connection to the backend has been lost. Please try restarting IDE.";
 
+/// Text that shows up in the statusbar when a lost backend connection has been established again.
+pub const BACKEND_RECONNECTED_MESSAGE: &str = "Connection to the backend has been restored.";
+
 const ALIVE_LOG_INTERVAL_SEC: u64 = 60;
 
 
@@ -71,7 +71,7 @@ impl ExecutionContext {
         let logger = Logger::new_sub(&parent, "ExecutionContext");
         async move {
             info!(logger, "Creating.");
-            let id = language_server.client.create_execution_context().await?.context_id;
+            let id = language_server.client.create_execution_context(&None).await?.context_id;
             let logger = Logger::new_sub(&parent, iformat! {"ExecutionContext {id}"});
             let model = model::execution_context::Plain::new(&logger, root_definition);
             info!(logger, "Created. Id: {id}.");
@@ -378,7 +378,7 @@ pub mod test {
         fn mock_create_destroy_calls(data: &MockData, ls: &mut language_server::MockClient) {
             let id = data.context_id;
             let result = Self::expected_creation_response(data);
-            expect_call!(ls.create_execution_context()    => Ok(result));
+            expect_call!(ls.create_execution_context(context_id=None) => Ok(result));
             expect_call!(ls.destroy_execution_context(id) => Ok(()));
         }
 
@@ -366,40 +366,36 @@ impl Module {
         self.notify_language_server(ls_content, &new_file, edits)
     }
 
-    fn edit_for_snipped(start: &Location, source: Text, target: Text) -> Option<TextEdit> {
+    fn edits_for_snipped(start: &Location, source: Text, target: Text) -> Vec<TextEdit> {
         // This is an implicit assumption that always seems to be true. Otherwise finding the
         // correct location for the final edit would be more complex.
         debug_assert_eq!(start.column, 0.column());
 
-        let edit = TextEdit::from_prefix_postfix_differences(&source, &target);
-        (edit.range.start != edit.range.end || !edit.text.is_empty())
-            .as_some_from(|| edit.move_by_lines(start.line.as_usize()))
+        let edits = TextEdit::from_differences(source, target).into_iter();
+        edits.map(|edit| edit.move_by_lines(start.line.as_usize())).collect()
     }
 
-    fn edit_for_code(ls_content: &ParsedContentSummary, new_file: &SourceFile) -> Option<TextEdit> {
-        Self::edit_for_snipped(
+    fn edits_for_code(ls_content: &ParsedContentSummary, new_file: &SourceFile) -> Vec<TextEdit> {
+        Self::edits_for_snipped(
             &ls_content.code.start,
             ls_content.code_slice(),
             new_file.code_slice().into(),
         )
     }
 
-    fn edit_for_metadata(
+    fn edits_for_metadata(
         ls_content: &ParsedContentSummary,
         new_file: &SourceFile,
-    ) -> Option<TextEdit> {
-        Self::edit_for_snipped(
+    ) -> Vec<TextEdit> {
+        Self::edits_for_snipped(
             &ls_content.metadata.start,
             ls_content.metadata_slice(),
             new_file.metadata_slice().into(),
         )
     }
 
-    fn edit_for_idmap(
-        ls_content: &ParsedContentSummary,
-        new_file: &SourceFile,
-    ) -> Option<TextEdit> {
-        Self::edit_for_snipped(
+    fn edits_for_idmap(ls_content: &ParsedContentSummary, new_file: &SourceFile) -> Vec<TextEdit> {
+        Self::edits_for_snipped(
             &ls_content.id_map.start,
             ls_content.id_map_slice(),
             new_file.id_map_slice().into(),
@@ -409,8 +405,9 @@ impl Module {
     /// Send update to Language Server with the changed file content. Returns the new content
     /// summary of Language Server state.
     ///
-    /// Note that a heuristic is used to determine the changed file content. The indicated change
-    /// might not be the minimal diff, but will contain all changes.
+    /// Every section of the file is compared line by line, and only the differing fragments are
+    /// sent. The edits are applied by the Language Server one after another, so the edits of each
+    /// section are ordered from its end, keeping the positions of the following edits valid.
     #[profile(Debug)]
     fn partial_invalidation(
         &self,
@@ -420,9 +417,9 @@ impl Module {
         debug!(self.logger, "Handling partial invalidation: {ls_content:?}.");
         let edits = vec![
             //id_map and metadata go first, because code change may alter their position.
-            Self::edit_for_idmap(ls_content, &new_file),
-            Self::edit_for_metadata(ls_content, &new_file),
-            Self::edit_for_code(ls_content, &new_file),
+            Self::edits_for_idmap(ls_content, &new_file),
+            Self::edits_for_metadata(ls_content, &new_file),
+            Self::edits_for_code(ls_content, &new_file),
         ]
         .into_iter()
         .flatten()
@@ -582,14 +579,14 @@ pub mod test {
                 if let [edit_idmap, edit_code] = edit.edits.as_slice() {
                     let code_so_far = this.current_ls_content.get();
                     let file_so_far = SourceFile::new((&code_so_far).into());
-                    // TODO [mwu]
-                    //  Currently this assumes that the whole idmap is replaced at each edit.
-                    //  This code should be adjusted, if partial metadata updates are implemented.
+                    // The idmap takes a single line, so its edit is limited to that line.
                     let idmap_range =
                         file_so_far.id_map.map(|x| code_so_far.location_of_byte_offset_snapped(x));
                     let idmap_range = TextRange::from(idmap_range);
-                    assert_eq!(edit_idmap.range, idmap_range);
-                    assert!(SourceFile::looks_like_idmap(&edit_idmap.text));
+                    assert_eq!(edit_idmap.range.start.line, idmap_range.start.line);
+                    assert_eq!(edit_idmap.range.end.line, idmap_range.end.line);
+                    let edited = SourceFile::new((&apply_edit(&code_so_far, edit_idmap)).into());
+                    assert!(SourceFile::looks_like_idmap(edited.id_map_slice()));
                     f(edit_code)
                 } else {
                     // This test assumes that expected single file edit consists from two text
@@ -673,9 +670,9 @@ pub mod test {
                 // Replacing `Test` with `Test 2`
                 edit_handler.expect_some_edit(client, |edits| {
                     let edit_code = &edits.edits[1];
-                    assert_eq!(edit_code.text, "Test 2");
+                    assert_eq!(edit_code.text, " 2");
                     assert_eq!(edit_code.range, TextRange {
-                        start: Position { line: 1, character: 13 },
+                        start: Position { line: 1, character: 17 },
                         end:   Position { line: 1, character: 17 },
                     });
                     Ok(())
@@ -712,9 +709,9 @@ pub mod test {
                 edit_handler.expect_full_invalidation(client);
                 // Applying code update.
                 edit_handler.expect_edit_with_metadata(client, |edit| {
-                    assert_eq!(edit.text, "Test 2");
+                    assert_eq!(edit.text, " 2");
                     assert_eq!(edit.range, TextRange {
-                        start: Position { line: 1, character: 13 },
+                        start: Position { line: 1, character: 17 },
                         end:   Position { line: 1, character: 17 },
                     });
                     Err(RpcError::LostConnection)
@@ -736,18 +733,46 @@ pub mod test {
     fn handle_insertion_edits_bug180558676() {
         let source = Text::from("from Standard.Base import all\n\nmain =\n    operator1 = 0.up_to 100 . to_vector . map .noise\n    operator1.sort\n");
         let target = Text::from("from Standard.Base import all\nimport Standard.Visualization\n\nmain =\n    operator1 = 0.up_to 100 . to_vector . map .noise\n    operator1.sort\n");
-        let edit = Module::edit_for_snipped(
+        let edits = Module::edits_for_snipped(
             &Location { line: 0.into(), column: 0.into() },
             source,
             target,
         );
-        let expected = Some(TextEdit {
+        let expected = vec![TextEdit {
             range: TextRange {
                 start: Position { line: 1, character: 0 },
                 end:   Position { line: 1, character: 0 },
             },
             text:  "import Standard.Visualization\n".to_string(),
-        });
-        assert_eq!(edit, expected);
+        }];
+        assert_eq!(edits, expected);
+    }
+
+    #[test]
+    fn minimal_edits_for_distant_changes() {
+        let lines = (0..50).map(|i| format!("    x{i} = {i}\n")).collect_vec();
+        let source = format!("from Standard.Base import all\n\nmain =\n{}", lines.concat());
+        let target = source.replace("import all", "import all\nimport Standard.Table");
+        let target = target.replace("x49 = 49", "x49 = x48 + 1");
+        let start = Location { line: 3.into(), column: 0.into() };
+        let edits =
+            Module::edits_for_snipped(&start, source.as_str().into(), target.clone().into());
+        let texts = edits.iter().map(|edit| edit.text.as_str()).collect_vec();
+        assert_eq!(texts, vec!["x48 + 1", "import Standard.Table\n"]);
+
+        let ls_content = Text::from(format!("one\ntwo\nthree\n{source}"));
+        let edit_size = |edit: &TextEdit| {
+            let start = ls_content.byte_offset_of_location_snapped(edit.range.start.into());
+            let end = ls_content.byte_offset_of_location_snapped(edit.range.end.into());
+            (end - start).as_usize() + edit.text.len()
+        };
+        let prefix_postfix_edit = TextEdit::from_prefix_postfix_differences(&source, &target);
+        let prefix_postfix_size = edit_size(&prefix_postfix_edit.move_by_lines(3));
+        let total_size: usize = edits.iter().map(edit_size).sum();
+        assert!(total_size * 10 < prefix_postfix_size);
+
+        let expected = format!("one\ntwo\nthree\n{target}");
+        let result = edits.iter().fold(ls_content.clone(), |text, edit| apply_edit(&text, edit));
+        assert_eq!(result.to_string(), expected);
     }
 }
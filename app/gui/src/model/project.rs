@@ -177,6 +177,8 @@ pub type Synchronized = synchronized::Project;
 pub enum Notification {
     /// One of the backend connections has been lost.
     ConnectionLost(BackendConnection),
+    /// One of the backend connections has been established again after being lost.
+    ConnectionRestored(BackendConnection),
 }
 
 /// Denotes one of backend connections used by a project.
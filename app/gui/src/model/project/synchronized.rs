@@ -328,6 +328,19 @@ impl Project {
         let client_binary = binary::Client::new(&parent, binary_ws);
         crate::executor::global::spawn(client_json.runner());
         crate::executor::global::spawn(client_binary.runner());
+        let client_json = language_server::ResilientClient::new(client_json);
+        let client_binary = binary::ResilientClient::new(client_binary);
+        let logger = Logger::new_sub(&parent, "language-server-session");
+        let resumptions = client_json.resumptions();
+        crate::executor::global::spawn(resumptions.for_each(move |resumption| {
+            for error in &resumption.errors {
+                error!(logger, "Failed to restore the Language Server session: {error}");
+            }
+            for path in &resumption.outdated_files {
+                warning!(logger, "File {path} has been modified while the connection was lost.");
+            }
+            futures::future::ready(())
+        }));
         let connection_json =
             language_server::Connection::new(client_json, client_id).await.map_err(&wrap)?;
         let connection_binary =
@@ -403,14 +416,17 @@ impl Project {
                         );
                     }
                 }
+                Event::Opened => {
+                    info!(logger, "Binary connection with the Language Server restored.");
+                    let which = model::project::BackendConnection::LanguageServerBinary;
+                    let notification = model::project::Notification::ConnectionRestored(which);
+                    publisher.notify(notification);
+                }
                 Event::Closed => {
                     error!(logger, "Lost binary connection with the Language Server!");
                     let which = model::project::BackendConnection::LanguageServerBinary;
                     let notification = model::project::Notification::ConnectionLost(which);
                     publisher.notify(notification);
-                    // TODO [wmu]
-                    //   The connection should be reestablished, see
-                    //   https://github.com/enso-org/ide/issues/145
                 }
                 Event::Error(error) => {
                     error!(logger, "Error emitted by the binary data connection: {error}.");
@@ -516,14 +532,24 @@ impl Project {
                         {update.expression_id}. Error: {update.message}"
                     );
                 }
+                Event::Notification(Notification::TaskStarted(_)) => {}
+                Event::Notification(Notification::TaskProgressUpdate(_)) => {}
+                Event::Notification(Notification::TaskFinished(task)) =>
+                    if !task.success {
+                        let message = task.message.unwrap_or_default();
+                        error!(logger, "Task {task.task_id} failed. Error: {message}");
+                    },
+                Event::Opened => {
+                    info!(logger, "JSON-RPC connection with the Language Server restored.");
+                    let which = model::project::BackendConnection::LanguageServerJson;
+                    let notification = model::project::Notification::ConnectionRestored(which);
+                    publisher.notify(notification);
+                }
                 Event::Closed => {
                     error!(logger, "Lost JSON-RPC connection with the Language Server!");
                     let which = model::project::BackendConnection::LanguageServerJson;
                     let notification = model::project::Notification::ConnectionLost(which);
                     publisher.notify(notification);
-                    // TODO [wmu]
-                    //  The connection should be reestablished,
-                    //  see https://github.com/enso-org/ide/issues/145
                 }
                 Event::Error(error) => {
                     error!(logger, "Error emitted by the JSON-RPC data connection: {error}.");
@@ -268,6 +268,8 @@ impl Project {
             let message = match notification {
                 model::project::Notification::ConnectionLost(_) =>
                     crate::BACKEND_DISCONNECTED_MESSAGE,
+                model::project::Notification::ConnectionRestored(_) =>
+                    crate::BACKEND_RECONNECTED_MESSAGE,
             };
             let message = view::status_bar::event::Label::from(message);
             model.status_bar.add_event(message);
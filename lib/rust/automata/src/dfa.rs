@@ -9,6 +9,8 @@ use crate::nfa::Nfa;
 use crate::state;
 use crate::symbol::Symbol;
 
+use std::collections::BTreeSet;
+
 
 
 // =============
@@ -88,6 +90,152 @@ impl Dfa {
 }
 
 
+// === Minimization ===
+
+impl Dfa {
+    /// Build the minimal DFA recognizing the same language, using the
+    /// [Hopcroft's algorithm](https://en.wikipedia.org/wiki/DFA_minimization#Hopcroft's_algorithm).
+    ///
+    /// Only the states having the same [`Dfa::sources`] are merged, so that any data attached to
+    /// the NFA states stays available. See [`Dfa::minimize_by`] to merge more states.
+    pub fn minimize(&self) -> Dfa {
+        self.minimize_by(|sources| sources.to_vec())
+    }
+
+    /// Build the minimal DFA recognizing the same language, using the
+    /// [Hopcroft's algorithm](https://en.wikipedia.org/wiki/DFA_minimization#Hopcroft's_algorithm).
+    ///
+    /// Only the states whose [`Dfa::sources`] have the same key are merged. For example, the key
+    /// may be the set of the NFA states having callbacks or names, which are then preserved. The
+    /// sources of a merged state are the union of the sources of the original states.
+    ///
+    /// The states equivalent to the invalid state are removed, except for the start state, which is
+    /// then left without links. The states are numbered in the order of their lowest ids in this
+    /// DFA, so the start state stays [`Dfa::START_STATE`]. The asymptotic complexity is
+    /// `O(s * n * log(n))` for `n` states and `s` alphabet segments.
+    pub fn minimize_by<K: Ord>(&self, key: impl Fn(&[nfa::State]) -> K) -> Dfa {
+        if self.links.rows == 0 {
+            return self.clone();
+        }
+        // The invalid state is represented by the extra state `self.links.rows`.
+        let invalid = self.links.rows;
+        let mut keys = self.sources.iter().map(|sources| key(sources)).collect_vec();
+        keys.push(key(&[]));
+        let mut blocks = self.equivalence_classes(&keys);
+        // The states equivalent to the invalid state are dead: all their links are removed.
+        let dead_block = blocks.iter().position(|block| block.contains(&invalid));
+        let dead = dead_block.map(|ix| blocks.swap_remove(ix)).unwrap_or_default();
+        if dead.contains(&Self::START_STATE.id()) {
+            // The start state is kept, but all its links lead to the invalid state.
+            blocks.push(vec![Self::START_STATE.id()]);
+        }
+        blocks.sort_by_key(|block| block.iter().min().copied());
+        let mut new_state = vec![State::INVALID; invalid + 1];
+        for (ix, block) in blocks.iter().enumerate() {
+            for &state in block {
+                new_state[state] = State::new(ix);
+            }
+        }
+
+        let mut links = Matrix::new(blocks.len(), self.links.columns);
+        let mut sources = Vec::with_capacity(blocks.len());
+        for (ix, block) in blocks.iter().enumerate() {
+            let representative = block[0];
+            for column in 0..self.links.columns {
+                let target = self.links[(representative, column)];
+                if !target.is_invalid() {
+                    links[(ix, column)] = new_state[target.id()];
+                }
+            }
+            let block_sources = block.iter().filter_map(|&state| self.sources.get(state));
+            let block_sources: BTreeSet<_> = block_sources.flatten().copied().collect();
+            sources.push(block_sources.into_iter().collect());
+        }
+        Dfa { alphabet: self.alphabet.clone(), links, sources }
+    }
+
+    /// Partition the states, including the invalid state represented as `self.links.rows`, into
+    /// the classes of equivalent states. Only the states with equal keys can be equivalent.
+    fn equivalence_classes<K: Ord>(&self, keys: &[K]) -> Vec<Vec<usize>> {
+        let invalid = self.links.rows;
+        let state_count = invalid + 1;
+        let columns = self.links.columns;
+        let target = |state: usize, column: usize| match self.links[(state, column)] {
+            target if target.is_invalid() => invalid,
+            target => target.id(),
+        };
+        // For each symbol and state, the states transitioning to that state on that symbol.
+        let mut sources_of = vec![vec![vec![]; state_count]; columns];
+        for state in 0..invalid {
+            for (column, sources) in sources_of.iter_mut().enumerate() {
+                sources[target(state, column)].push(state);
+            }
+        }
+        for sources in &mut sources_of {
+            sources[invalid].push(invalid);
+        }
+
+        let mut initial = BTreeMap::<&K, Vec<usize>>::new();
+        for (state, key) in keys.iter().enumerate() {
+            initial.entry(key).or_default().push(state);
+        }
+        let mut blocks = initial.into_values().collect_vec();
+        let mut block_of = vec![0; state_count];
+        for (ix, block) in blocks.iter().enumerate() {
+            for &state in block {
+                block_of[state] = ix;
+            }
+        }
+
+        let mut pending = (0..blocks.len()).cartesian_product(0..columns).collect_vec();
+        let mut is_pending: HashSet<(usize, usize)> = pending.iter().copied().collect();
+        let mut is_splitter_source = vec![false; state_count];
+        while let Some((splitter, column)) = pending.pop() {
+            is_pending.remove(&(splitter, column));
+            let splitter_sources = blocks[splitter]
+                .iter()
+                .flat_map(|&state| sources_of[column][state].iter().copied())
+                .collect_vec();
+            let mut touched = BTreeSet::new();
+            for &state in &splitter_sources {
+                is_splitter_source[state] = true;
+                touched.insert(block_of[state]);
+            }
+            for block in touched {
+                let (inside, outside): (Vec<_>, Vec<_>) =
+                    blocks[block].iter().copied().partition(|&state| is_splitter_source[state]);
+                if outside.is_empty() {
+                    continue;
+                }
+                // The smaller part becomes the new block.
+                let (kept, moved) = if inside.len() < outside.len() {
+                    (outside, inside)
+                } else {
+                    (inside, outside)
+                };
+                let new_block = blocks.len();
+                for &state in &moved {
+                    block_of[state] = new_block;
+                }
+                blocks[block] = kept;
+                blocks.push(moved);
+                for column in 0..columns {
+                    // A pending block must be split by both parts; otherwise, splitting by the
+                    // smaller part is enough.
+                    if is_pending.insert((new_block, column)) {
+                        pending.push((new_block, column));
+                    }
+                }
+            }
+            for &state in &splitter_sources {
+                is_splitter_source[state] = false;
+            }
+        }
+        blocks
+    }
+}
+
+
 // === Trait Impls ===
 
 impl From<Vec<Vec<usize>>> for Matrix<State> {
@@ -171,8 +319,12 @@ impl From<&Nfa> for Dfa {
 pub mod tests {
     extern crate test;
     use super::*;
+    use crate::matcher::Matcher;
     use crate::nfa;
     use crate::nfa::tests::NfaTest;
+    use crate::pattern::Pattern;
+    use rand::prelude::*;
+    use rand_chacha::ChaCha8Rng;
     use test::Bencher;
 
 
@@ -207,6 +359,54 @@ pub mod tests {
         State::new(ix)
     }
 
+    fn run(dfa: &Dfa, input: &str) -> State {
+        input.chars().fold(Dfa::START_STATE, |state, char| dfa.next_state(state, &char.into()))
+    }
+
+    /// Check whether the NFA accepts the input, by simulating all its possible runs.
+    fn nfa_accepts(nfa: &Nfa, end: nfa::State, input: &str) -> bool {
+        let closure = |mut states: Vec<nfa::State>| {
+            let mut reachable = BTreeSet::new();
+            while let Some(state) = states.pop() {
+                if reachable.insert(state) {
+                    states.extend(nfa[state].epsilon_links());
+                }
+            }
+            reachable
+        };
+        let mut states = closure(vec![nfa.start]);
+        for char in input.chars() {
+            let symbol = Symbol::from(char);
+            let links = states.iter().flat_map(|&state| nfa[state].links());
+            let matching = links.filter(|link| link.symbols.contains(&symbol));
+            states = closure(matching.map(|link| link.target).collect());
+        }
+        states.contains(&end)
+    }
+
+    fn random_pattern(rng: &mut impl Rng, depth: usize) -> Pattern {
+        if depth == 0 || rng.gen_bool(0.3) {
+            match rng.gen_range(0..4) {
+                0 => Pattern::range('a'..='b'),
+                1 => Pattern::always(),
+                2 => Pattern::never(),
+                _ => Pattern::char(*['a', 'b', 'c'].choose(rng).unwrap()),
+            }
+        } else {
+            let pattern = random_pattern(rng, depth - 1);
+            match rng.gen_range(0..3) {
+                0 => pattern >> random_pattern(rng, depth - 1),
+                1 => pattern | random_pattern(rng, depth - 1),
+                _ => pattern.many(),
+            }
+        }
+    }
+
+    fn random_input(rng: &mut impl Rng) -> String {
+        let len = rng.gen_range(0..=6);
+        (0..len).map(|_| *['a', 'b', 'c', 'd'].choose(rng).unwrap()).collect()
+    }
+
 
     // === The Tests ===
 
@@ -321,6 +521,104 @@ pub mod tests {
         assert_eq!(get_name(&nfa, &dfa, make_state(4)), Some(&String::from("rule_2")));
     }
 
+    #[test]
+    fn dfa_minimize_keeps_sources() {
+        for nfa in [
+            nfa::tests::pattern_range(),
+            nfa::tests::pattern_or(),
+            nfa::tests::pattern_seq(),
+            nfa::tests::pattern_many(),
+            nfa::tests::pattern_always(),
+            nfa::tests::pattern_never(),
+            nfa::tests::simple_rules(),
+            nfa::tests::complex_rules(),
+            nfa::tests::named_rules(),
+        ] {
+            let dfa = Dfa::from(&nfa.nfa);
+            // Different sources distinguish all the states of these automata.
+            assert_eq!(dfa.minimize(), dfa);
+        }
+    }
+
+    #[test]
+    fn dfa_minimize_named_rules() {
+        let nfa = nfa::tests::named_rules();
+        let dfa = Dfa::from(&nfa.nfa);
+        let named = |sources: &[nfa::State]| {
+            sources.iter().filter(|&&source| nfa.name(source).is_some()).copied().collect_vec()
+        };
+        let minimized = dfa.minimize_by(named);
+        // The states after the first and the following letters of a word are merged.
+        assert_eq!(minimized.links.rows, 3);
+        assert_eq!(minimized.sources.len(), 3);
+        for input in ["", "a", "aa", "aaa", "b", "bbb", "ab", "c"] {
+            let state = run(&dfa, input);
+            let minimized_state = run(&minimized, input);
+            assert_eq!(state.is_invalid(), minimized_state.is_invalid());
+            if !state.is_invalid() {
+                let name = get_name(&nfa, &dfa, state);
+                assert_eq!(get_name(&nfa, &minimized, minimized_state), name);
+            }
+        }
+    }
+
+    #[test]
+    fn dfa_minimize_empty_language() {
+        let nfa = nfa::tests::pattern_never();
+        let dfa = Dfa::from(&nfa.nfa);
+        let minimized = dfa.minimize_by(|sources| sources.contains(&nfa.end_state_id));
+        assert_eq!(minimized.links, Matrix::new(1, dfa.links.columns));
+    }
+
+    #[test]
+    fn minimized_dfa_accepts_nfa_language() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for _ in 0..500 {
+            let pattern = random_pattern(&mut rng, 4);
+            let nfa = NfaTest::make(vec![pattern.clone()]);
+            let end = nfa.end_state_id;
+            let accepting = |sources: &[nfa::State]| sources.contains(&end);
+            let dfa = Dfa::from(&nfa.nfa);
+            let minimized = dfa.minimize_by(accepting);
+            assert!(minimized.links.rows <= dfa.links.rows);
+            assert_eq!(minimized.minimize_by(accepting).links.rows, minimized.links.rows);
+            let dfa_matcher = Matcher::with_targets(&dfa, &[end]);
+            let minimized_matcher = Matcher::with_targets(&minimized, &[end]);
+            for _ in 0..20 {
+                let input = random_input(&mut rng);
+                let expected = nfa_accepts(&nfa.nfa, end, &input);
+                assert_eq!(dfa_matcher.accepts_str(&input), expected, "{pattern:?} {input:?}");
+                assert_eq!(
+                    minimized_matcher.accepts_str(&input),
+                    expected,
+                    "{pattern:?} {input:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn minimized_dfa_with_dead_start_state() {
+        let mut rng = ChaCha8Rng::seed_from_u64(1);
+        for _ in 0..200 {
+            // No input is accepted, but the DFA may have many states, all of them dead.
+            let pattern = random_pattern(&mut rng, 4) >> Pattern::never();
+            let nfa = NfaTest::make(vec![pattern.clone()]);
+            let end = nfa.end_state_id;
+            let accepting = |sources: &[nfa::State]| sources.contains(&end);
+            let dfa = Dfa::from(&nfa.nfa);
+            let minimized = dfa.minimize_by(accepting);
+            assert_eq!(minimized.links, Matrix::new(1, dfa.links.columns), "{pattern:?}");
+            assert_eq!(minimized.sources, vec![dfa.sources[Dfa::START_STATE.id()].clone()]);
+            let matcher = Matcher::with_targets(&minimized, &[end]);
+            for _ in 0..20 {
+                let input = random_input(&mut rng);
+                assert!(!matcher.accepts_str(&input), "{pattern:?} {input:?}");
+                assert!(run(&minimized, &input).is_invalid() || input.is_empty());
+            }
+        }
+    }
+
     // === The Benchmarks ===
 
     #[bench]
@@ -362,4 +660,10 @@ pub mod tests {
     fn bench_to_dfa_complex_rules(bencher: &mut Bencher) {
         bencher.iter(|| Dfa::from(&nfa::tests::complex_rules().nfa))
     }
+
+    #[bench]
+    fn bench_minimize_complex_rules(bencher: &mut Bencher) {
+        let dfa = Dfa::from(&nfa::tests::complex_rules().nfa);
+        bencher.iter(|| dfa.minimize())
+    }
 }
@@ -23,6 +23,7 @@
 pub mod alphabet;
 pub mod data;
 pub mod dfa;
+pub mod matcher;
 pub mod nfa;
 pub mod pattern;
 pub mod state;
@@ -30,6 +31,7 @@ pub mod symbol;
 
 pub use dfa::Dfa;
 pub use enso_prelude as prelude;
+pub use matcher::Matcher;
 pub use nfa::Nfa;
 pub use pattern::*;
 pub use symbol::*;
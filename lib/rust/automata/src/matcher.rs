@@ -0,0 +1,232 @@
+//! A driver running a [`Dfa`] over the input, finding the matching parts of it.
+
+use crate::prelude::*;
+
+use crate::dfa;
+use crate::dfa::Dfa;
+use crate::nfa;
+use crate::symbol::Symbol;
+
+
+
+// ===============
+// === Matcher ===
+// ===============
+
+/// Runs a [`Dfa`] over sequences of symbols or over strings.
+///
+/// The DFA does not define which of its states are accepting, as this depends on the NFA states
+/// that the user attached meaning to. The accepting states are therefore given as a predicate over
+/// the [`Dfa::sources`] of each state, evaluated once, when the matcher is created.
+#[derive(Clone, Debug)]
+pub struct Matcher<'a> {
+    dfa:       &'a Dfa,
+    accepting: Vec<bool>,
+}
+
+impl<'a> Matcher<'a> {
+    /// Constructor. A DFA state is accepting if `is_accepting` returns true for its sources.
+    pub fn new(dfa: &'a Dfa, is_accepting: impl Fn(&[nfa::State]) -> bool) -> Self {
+        let accepting = dfa.sources.iter().map(|sources| is_accepting(sources)).collect();
+        Self { dfa, accepting }
+    }
+
+    /// Constructor. A DFA state is accepting if it was constructed from any of the `targets`.
+    pub fn with_targets(dfa: &'a Dfa, targets: &[nfa::State]) -> Self {
+        Self::new(dfa, |sources| sources.iter().any(|source| targets.contains(source)))
+    }
+
+    /// Check whether the state is accepting.
+    pub fn is_accepting(&self, state: dfa::State) -> bool {
+        !state.is_invalid() && self.accepting.get(state.id()).copied().unwrap_or_default()
+    }
+
+    /// Check whether the whole input is accepted.
+    pub fn accepts(&self, input: impl IntoIterator<Item = Symbol>) -> bool {
+        let mut state = Dfa::START_STATE;
+        for symbol in input {
+            state = self.dfa.next_state(state, &symbol);
+            if state.is_invalid() {
+                return false;
+            }
+        }
+        self.is_accepting(state)
+    }
+
+    /// Check whether the whole string is accepted.
+    pub fn accepts_str(&self, input: &str) -> bool {
+        self.accepts(input.chars().map(symbol_of_char))
+    }
+
+    /// Find the longest accepted prefix of the input. The offsets of the match are the indices of
+    /// the symbols. The input is read only as far as the DFA can advance.
+    pub fn longest_match(&self, input: impl IntoIterator<Item = Symbol>) -> Option<Match> {
+        let symbols = input.into_iter().enumerate().map(|(ix, symbol)| (ix, ix + 1, symbol));
+        self.longest_match_at(0, symbols).map(|(found, _)| found)
+    }
+
+    /// Find the longest accepted prefix of the string. The offsets of the match are in bytes.
+    pub fn longest_match_str(&self, input: &str) -> Option<Match> {
+        self.longest_match_at(0, char_offsets(input)).map(|(found, _)| found)
+    }
+
+    /// Find all the non-overlapping matches in the input, in order. At each position, the longest
+    /// match is taken; if there is none, the search continues from the next symbol. Empty matches
+    /// are not reported. The offsets of the matches are the indices of the symbols.
+    pub fn find_all(&self, input: impl IntoIterator<Item = Symbol>) -> Vec<Match> {
+        let symbols = input.into_iter().enumerate().map(|(ix, symbol)| (ix, ix + 1, symbol));
+        self.find_all_in(&symbols.collect_vec())
+    }
+
+    /// Find all the non-overlapping matches in the string, in order, like [`Self::find_all`]. The
+    /// offsets of the matches are in bytes.
+    pub fn find_all_str(&self, input: &str) -> Vec<Match> {
+        self.find_all_in(&char_offsets(input).collect_vec())
+    }
+
+    /// Find all the matches in the symbols, given with their start and end offsets.
+    fn find_all_in(&self, symbols: &[(usize, usize, Symbol)]) -> Vec<Match> {
+        let mut matches = vec![];
+        let mut ix = 0;
+        while let Some((start, _, _)) = symbols.get(ix) {
+            let found = self.longest_match_at(*start, symbols[ix..].iter().cloned());
+            match found.filter(|(_, length)| *length > 0) {
+                Some((found, length)) => {
+                    matches.push(found);
+                    ix += length;
+                }
+                None => ix += 1,
+            }
+        }
+        matches
+    }
+
+    /// Find the longest accepted prefix of the symbols, given with their start and end offsets.
+    /// Returns the match and its length in symbols.
+    fn longest_match_at(
+        &self,
+        start: usize,
+        input: impl IntoIterator<Item = (usize, usize, Symbol)>,
+    ) -> Option<(Match, usize)> {
+        let mut state = Dfa::START_STATE;
+        let mut longest = self.is_accepting(state).as_some((Match { start, end: start, state }, 0));
+        for (length, (_, end, symbol)) in input.into_iter().enumerate() {
+            state = self.dfa.next_state(state, &symbol);
+            if state.is_invalid() {
+                break;
+            }
+            if self.is_accepting(state) {
+                longest = Some((Match { start, end, state }, length + 1));
+            }
+        }
+        longest
+    }
+}
+
+
+// === Match ===
+
+/// A part of the input accepted by the DFA.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Match {
+    /// The offset of the first symbol of the match.
+    pub start: usize,
+    /// The offset past the last symbol of the match.
+    pub end:   usize,
+    /// The accepting state the DFA was in after reading the match. Its [`Dfa::sources`] identify
+    /// the matched rules.
+    pub state: dfa::State,
+}
+
+
+
+// ===============
+// === Symbols ===
+// ===============
+
+/// The symbol of the character, as used by [`crate::Pattern::char`].
+fn symbol_of_char(char: char) -> Symbol {
+    Symbol::new(char as u64)
+}
+
+/// The symbols of the string characters, with their start and end byte offsets.
+fn char_offsets(input: &str) -> impl Iterator<Item = (usize, usize, Symbol)> + '_ {
+    input.char_indices().map(|(ix, char)| (ix, ix + char.len_utf8(), symbol_of_char(char)))
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nfa::tests::NfaTest;
+    use crate::pattern::Pattern;
+
+    fn keywords() -> NfaTest {
+        let short = Pattern::all_of("ab");
+        let long = Pattern::all_of("abcd");
+        NfaTest::make(vec![short | long])
+    }
+
+    fn words_and_numbers() -> NfaTest {
+        let word = Pattern::range('a'..='z').many1();
+        let number = Pattern::range('0'..='9').many1();
+        NfaTest::make(vec![word, number])
+    }
+
+    #[test]
+    fn accepts() {
+        let nfa = keywords();
+        let dfa = Dfa::from(&nfa.nfa);
+        let matcher = Matcher::with_targets(&dfa, &[nfa.end_state_id]);
+        assert!(matcher.accepts_str("ab"));
+        assert!(matcher.accepts_str("abcd"));
+        assert!(!matcher.accepts_str("abc"));
+        assert!(!matcher.accepts_str(""));
+        assert!(matcher.accepts("ab".chars().map(Symbol::from)));
+    }
+
+    #[test]
+    fn longest_match() {
+        let nfa = keywords();
+        let dfa = Dfa::from(&nfa.nfa);
+        let matcher = Matcher::with_targets(&dfa, &[nfa.end_state_id]);
+        let range = |found: Option<Match>| found.map(|found| found.start..found.end);
+        assert_eq!(range(matcher.longest_match_str("abcde")), Some(0..4));
+        assert_eq!(range(matcher.longest_match_str("abce")), Some(0..2));
+        assert_eq!(range(matcher.longest_match_str("xab")), None);
+        let symbols = "abcd".chars().map(Symbol::from);
+        assert_eq!(range(matcher.longest_match(symbols)), Some(0..4));
+    }
+
+    #[test]
+    fn find_all() {
+        let nfa = words_and_numbers();
+        let dfa = Dfa::from(&nfa.nfa);
+        let matcher = Matcher::with_targets(&dfa, &[nfa.end_state_id]);
+        let word = nfa.pattern_state_ids[0];
+        let number = nfa.pattern_state_ids[1];
+        let describe = |found: &Match| {
+            let sources = &dfa.sources[found.state.id()];
+            let rule = if sources.contains(&word) {
+                "word"
+            } else if sources.contains(&number) {
+                "number"
+            } else {
+                "other"
+            };
+            (found.start..found.end, rule)
+        };
+        // The offsets in strings are in bytes; `ä` takes two bytes.
+        let matches = matcher.find_all_str("foo, bär 42");
+        let expected = vec![(0..3, "word"), (5..6, "word"), (8..9, "word"), (10..12, "number")];
+        assert_eq!(matches.iter().map(describe).collect_vec(), expected);
+        let matches = matcher.find_all("foo, bär 42".chars().map(Symbol::from));
+        let expected = vec![(0..3, "word"), (5..6, "word"), (7..8, "word"), (9..11, "number")];
+        assert_eq!(matches.iter().map(describe).collect_vec(), expected);
+    }
+}
@@ -10,6 +10,13 @@ use std::ops::RangeInclusive;
 use std::ops::Shr;
 
 
+// ==============
+// === Export ===
+// ==============
+
+pub mod syntax;
+
+
 
 // =============
 // == Pattern ==
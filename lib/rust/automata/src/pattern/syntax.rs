@@ -0,0 +1,874 @@
+//! Conversion between [`Pattern`]s and a practical subset of the regular expression syntax of the
+//! [`regex`](https://docs.rs/regex) crate.
+//!
+//! The supported syntax consists of:
+//! - literal characters and the escapes `\n`, `\t`, `\r`, `\f`, `\v`, `\a`, `\xHH`, `\x{H...}`,
+//!   `\uHHHH`, `\u{H...}`, and escaped meta characters, like `\*`;
+//! - the character classes `[a-z_]` and `[^a-z]`, the any-character-but-newline class `.`, the Perl
+//!   classes `\d`, `\w`, `\s` and their negations, and the Unicode classes, like `\pL`, `\p{Lu}`,
+//!   `\p{Greek}` and `\P{L}`;
+//! - the repetitions `*`, `+`, `?`, `{m}`, `{m,}` and `{m,n}`;
+//! - alternation `|` and grouping `(...)` or `(?:...)`.
+//!
+//! The classes have the same meaning as in the `regex` crate, with Unicode support enabled. The
+//! escapes `\x{...}` and `\u{...}` can also denote symbols outside of the Unicode range, like
+//! [`Symbol::eof`]. Anchors, assertions, flags, lazy repetitions and backreferences are not
+//! supported, as they do not fit the finite automata.
+//!
+//! The expressions can come from the users, so the parser limits the nesting depth of the groups
+//! and repetitions, the counts of the repetitions, and the size of the repeated patterns, instead
+//! of exhausting the stack or the memory.
+
+use crate::prelude::*;
+
+use crate::pattern::Pattern;
+use crate::symbol::Symbol;
+use crate::symbol::SymbolIndex;
+
+use regex_syntax::hir;
+use std::iter;
+use std::mem;
+use std::str::FromStr;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The characters having a special meaning, which can be escaped with a backslash.
+const META_CHARACTERS: &str = "\\.+*?()|[]{}^$#&-~";
+
+/// The ranges of the Unicode scalar values, which the negated classes are complemented within.
+const UNICODE: [(SymbolIndex, SymbolIndex); 2] = [(0, 0xD7FF), (0xE000, 0x10FFFF)];
+
+/// The class of the [`Pattern::Never`], which matches no character.
+const EMPTY_CLASS: &str = "[^\\x00-\\x{10FFFF}]";
+
+/// The maximum nesting depth of the groups and of the patterns built by the repetition operators.
+/// The same as the default `nest_limit` of the `regex-syntax` crate.
+pub const NEST_LIMIT: usize = 250;
+
+/// The maximum count of a counted repetition, like `{m,n}`.
+pub const REPETITION_LIMIT: usize = 1000;
+
+/// The maximum number of the nodes of a repeated pattern.
+pub const SIZE_LIMIT: usize = 100_000;
+
+
+
+// =============
+// === Error ===
+// =============
+
+/// An error of parsing a regular expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Error {
+    /// The byte offset of the erroneous part of the expression.
+    pub position: usize,
+    /// The kind of the error.
+    pub kind:     ErrorKind,
+}
+
+/// The kind of an [`Error`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorKind {
+    /// The group opened at the position is not closed.
+    UnclosedGroup,
+    /// The group closed at the position was not opened.
+    UnopenedGroup,
+    /// The class opened at the position is not closed.
+    UnclosedClass,
+    /// The range of the class starts after its end.
+    InvalidRange,
+    /// The repetition operator does not follow an expression.
+    NothingToRepeat,
+    /// The counted repetition is malformed, its minimum exceeds its maximum, its count exceeds the
+    /// [`REPETITION_LIMIT`], or the repeated pattern exceeds the [`SIZE_LIMIT`].
+    InvalidRepetition,
+    /// The escape sequence is malformed or unknown.
+    InvalidEscape,
+    /// The Unicode class name is unknown.
+    UnknownClass(String),
+    /// The syntax is valid for the `regex` crate, but it is not supported. This includes nesting
+    /// deeper than the [`NEST_LIMIT`].
+    Unsupported(&'static str),
+}
+
+impl Error {
+    fn new(position: usize, kind: ErrorKind) -> Self {
+        Self { position, kind }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.kind {
+            ErrorKind::UnclosedGroup => write!(f, "Unclosed group")?,
+            ErrorKind::UnopenedGroup => write!(f, "Unopened group")?,
+            ErrorKind::UnclosedClass => write!(f, "Unclosed character class")?,
+            ErrorKind::InvalidRange => write!(f, "Invalid character class range")?,
+            ErrorKind::NothingToRepeat => write!(f, "Repetition operator without expression")?,
+            ErrorKind::InvalidRepetition => write!(f, "Invalid counted repetition")?,
+            ErrorKind::InvalidEscape => write!(f, "Invalid escape sequence")?,
+            ErrorKind::UnknownClass(name) => write!(f, "Unknown Unicode class `{}`", name)?,
+            ErrorKind::Unsupported(feature) => write!(f, "Unsupported {}", feature)?,
+        }
+        write!(f, " at position {}.", self.position)
+    }
+}
+
+impl std::error::Error for Error {}
+
+
+
+// ===============
+// === Parsing ===
+// ===============
+
+/// Parse the regular expression into a pattern matching the same strings.
+pub fn parse(expression: &str) -> Result<Pattern, Error> {
+    let mut parser = Parser { input: expression, position: 0, depth: 0 };
+    let pattern = parser.parse_alternation()?;
+    match parser.peek() {
+        None => Ok(pattern),
+        Some(_) => Err(Error::new(parser.position, ErrorKind::UnopenedGroup)),
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = Error;
+    fn from_str(expression: &str) -> Result<Self, Self::Err> {
+        parse(expression)
+    }
+}
+
+/// A single character or a class of characters.
+#[derive(Clone, Debug)]
+enum Item {
+    Symbol(SymbolIndex),
+    Class(Vec<(SymbolIndex, SymbolIndex)>),
+}
+
+impl From<Item> for Pattern {
+    fn from(item: Item) -> Self {
+        match item {
+            Item::Symbol(symbol) => symbols(symbol, symbol),
+            Item::Class(class) => {
+                let ranges = normalize(class).into_iter();
+                let mut alternatives = ranges.map(|(start, end)| symbols(start, end)).collect_vec();
+                match alternatives.len() {
+                    0 => Pattern::Never,
+                    1 => alternatives.pop().unwrap(),
+                    _ => Pattern::Or(alternatives),
+                }
+            }
+        }
+    }
+}
+
+/// A recursive descent parser of the regular expressions.
+#[derive(Debug)]
+struct Parser<'a> {
+    input:    &'a str,
+    /// The byte offset of the next character.
+    position: usize,
+    /// The number of the groups enclosing the next character.
+    depth:    usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<char> {
+        self.input[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.position += char.len_utf8();
+        Some(char)
+    }
+
+    /// Consume the next character, if it is the expected one.
+    fn eat(&mut self, expected: char) -> bool {
+        let matches = self.peek() == Some(expected);
+        if matches {
+            self.next();
+        }
+        matches
+    }
+
+    fn parse_alternation(&mut self) -> Result<Pattern, Error> {
+        let mut alternatives = vec![self.parse_sequence()?];
+        while self.eat('|') {
+            alternatives.push(self.parse_sequence()?);
+        }
+        Ok(alternatives.into_iter().reduce(|lhs, rhs| lhs | rhs).unwrap_or(Pattern::Never))
+    }
+
+    fn parse_sequence(&mut self) -> Result<Pattern, Error> {
+        let mut items = vec![];
+        while let Some(char) = self.peek().filter(|&char| char != '|' && char != ')') {
+            let start = self.position;
+            self.next();
+            let atom = self.parse_atom(start, char)?;
+            items.push(self.parse_repetitions(atom)?);
+        }
+        Ok(sequence(items))
+    }
+
+    /// Parse the atom starting with the character at `start`, which is already consumed.
+    fn parse_atom(&mut self, start: usize, char: char) -> Result<Pattern, Error> {
+        match char {
+            '(' => self.parse_group(start),
+            '[' => self.parse_class(start).map(|class| Item::Class(class).into()),
+            '.' => Ok(Item::Class(perl_class(".")).into()),
+            '\\' => self.parse_escape(start).map(Into::into),
+            '*' | '+' | '?' | '{' => Err(Error::new(start, ErrorKind::NothingToRepeat)),
+            '^' | '$' => Err(Error::new(start, ErrorKind::Unsupported("anchor"))),
+            char => Ok(Item::Symbol(char as SymbolIndex).into()),
+        }
+    }
+
+    fn parse_group(&mut self, start: usize) -> Result<Pattern, Error> {
+        if self.eat('?') && !self.eat(':') {
+            return Err(Error::new(start, ErrorKind::Unsupported("group flags")));
+        }
+        if self.depth >= NEST_LIMIT {
+            return Err(Error::new(start, ErrorKind::Unsupported("nesting depth")));
+        }
+        self.depth += 1;
+        let pattern = self.parse_alternation();
+        self.depth -= 1;
+        let pattern = pattern?;
+        match self.eat(')') {
+            true => Ok(pattern),
+            false => Err(Error::new(start, ErrorKind::UnclosedGroup)),
+        }
+    }
+
+    fn parse_repetitions(&mut self, mut pattern: Pattern) -> Result<Pattern, Error> {
+        loop {
+            let start = self.position;
+            pattern = match self.peek() {
+                Some('*') => pattern.many(),
+                Some('+') => pattern.many1(),
+                Some('?') => pattern.opt(),
+                Some('{') => {
+                    let (min, max) = self.parse_counted_repetition(start)?;
+                    let count = max.unwrap_or(min).max(1);
+                    if size(&pattern).saturating_mul(count) > SIZE_LIMIT {
+                        return Err(Error::new(start, ErrorKind::InvalidRepetition));
+                    }
+                    repeat(&pattern, min, max)
+                }
+                _ => return Ok(pattern),
+            };
+            if size(&pattern) > SIZE_LIMIT {
+                return Err(Error::new(start, ErrorKind::InvalidRepetition));
+            }
+            if depth(&pattern) > NEST_LIMIT {
+                return Err(Error::new(start, ErrorKind::Unsupported("nesting depth")));
+            }
+            // Consume the operator, or the closing brace of the counted repetition.
+            self.next();
+            if self.peek() == Some('?') {
+                return Err(Error::new(self.position, ErrorKind::Unsupported("lazy repetition")));
+            }
+        }
+    }
+
+    /// Parse the `{m}`, `{m,}` or `{m,n}` repetition, except for the closing brace. Returns the
+    /// minimum and the optional maximum count.
+    fn parse_counted_repetition(&mut self, start: usize) -> Result<(usize, Option<usize>), Error> {
+        let invalid = || Error::new(start, ErrorKind::InvalidRepetition);
+        self.next();
+        let min = self.parse_decimal().ok_or_else(invalid)?;
+        let max = match self.eat(',') {
+            true if self.peek() == Some('}') => None,
+            true => Some(self.parse_decimal().ok_or_else(invalid)?),
+            false => Some(min),
+        };
+        let is_too_large = max.unwrap_or(min) > REPETITION_LIMIT;
+        if self.peek() != Some('}') || max.map_or(false, |max| max < min) || is_too_large {
+            return Err(invalid());
+        }
+        Ok((min, max))
+    }
+
+    fn parse_decimal(&mut self) -> Option<usize> {
+        let digits = self.input[self.position..].chars().take_while(char::is_ascii_digit).count();
+        let number = self.input[self.position..self.position + digits].parse().ok()?;
+        self.position += digits;
+        Some(number)
+    }
+
+    /// Parse the class contents, after the opening bracket at `start`.
+    fn parse_class(&mut self, start: usize) -> Result<Vec<(SymbolIndex, SymbolIndex)>, Error> {
+        let unclosed = || Error::new(start, ErrorKind::UnclosedClass);
+        let negated = self.eat('^');
+        let mut ranges = vec![];
+        let mut is_first = true;
+        loop {
+            let position = self.position;
+            let item = match self.next().ok_or_else(unclosed)? {
+                ']' if !is_first => break,
+                '[' => return Err(Error::new(position, ErrorKind::Unsupported("nested class"))),
+                '\\' => self.parse_escape(position)?,
+                char => Item::Symbol(char as SymbolIndex),
+            };
+            is_first = false;
+            let low = match item {
+                Item::Symbol(low) => low,
+                Item::Class(class) => {
+                    ranges.extend(class);
+                    continue;
+                }
+            };
+            let mut lookahead = self.input[self.position..].chars();
+            if lookahead.next() != Some('-') || matches!(lookahead.next(), None | Some(']')) {
+                ranges.push((low, low));
+                continue;
+            }
+            self.next();
+            let end_position = self.position;
+            let high = match self.next().ok_or_else(unclosed)? {
+                '\\' => match self.parse_escape(end_position)? {
+                    Item::Symbol(high) => high,
+                    Item::Class(_) => return Err(Error::new(position, ErrorKind::InvalidRange)),
+                },
+                '[' =>
+                    return Err(Error::new(end_position, ErrorKind::Unsupported("nested class"))),
+                char => char as SymbolIndex,
+            };
+            if high < low {
+                return Err(Error::new(position, ErrorKind::InvalidRange));
+            }
+            ranges.push((low, high));
+        }
+        Ok(if negated { complement(ranges) } else { ranges })
+    }
+
+    /// Parse the escape sequence, after the backslash at `start`.
+    fn parse_escape(&mut self, start: usize) -> Result<Item, Error> {
+        let invalid = || Error::new(start, ErrorKind::InvalidEscape);
+        let symbol = |char: char| Ok(Item::Symbol(char as SymbolIndex));
+        match self.next().ok_or_else(invalid)? {
+            'n' => symbol('\n'),
+            't' => symbol('\t'),
+            'r' => symbol('\r'),
+            'f' => symbol('\x0C'),
+            'v' => symbol('\x0B'),
+            'a' => symbol('\x07'),
+            'x' => self.parse_code(2).map(Item::Symbol).ok_or_else(invalid),
+            'u' => self.parse_code(4).map(Item::Symbol).ok_or_else(invalid),
+            class @ ('d' | 'D' | 'w' | 'W' | 's' | 'S') =>
+                Ok(Item::Class(perl_class(&format!("\\{}", class)))),
+            class @ ('p' | 'P') => {
+                let name = match self.eat('{') {
+                    true => {
+                        let length = self.input[self.position..].find('}').ok_or_else(invalid)?;
+                        let name = &self.input[self.position..self.position + length];
+                        self.position += length + 1;
+                        name.to_owned()
+                    }
+                    false => self.next().ok_or_else(invalid)?.to_string(),
+                };
+                let class = unicode_class(&format!("\\{}{{{}}}", class, name));
+                class
+                    .map(Item::Class)
+                    .ok_or_else(|| Error::new(start, ErrorKind::UnknownClass(name)))
+            }
+            'b' | 'B' | 'A' | 'z' => Err(Error::new(start, ErrorKind::Unsupported("assertion"))),
+            '0'..='9' => Err(Error::new(start, ErrorKind::Unsupported("backreference"))),
+            char if META_CHARACTERS.contains(char) => symbol(char),
+            _ => Err(invalid()),
+        }
+    }
+
+    /// Parse the hexadecimal code of a symbol, either in braces or of the given number of digits.
+    fn parse_code(&mut self, digits: usize) -> Option<SymbolIndex> {
+        let rest = &self.input[self.position..];
+        let (code, length) = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let code = &braced[..braced.find('}')?];
+                (code, code.len() + 2)
+            }
+            None => (rest.get(..digits)?, digits),
+        };
+        if code.is_empty() || !code.chars().all(|char| char.is_ascii_hexdigit()) {
+            return None;
+        }
+        let code = SymbolIndex::from_str_radix(code, 16).ok()?;
+        self.position += length;
+        Some(code)
+    }
+}
+
+
+// === Pattern Construction ===
+
+/// The pattern matching the symbols in the range.
+fn symbols(start: SymbolIndex, end: SymbolIndex) -> Pattern {
+    match (char_of(start), char_of(end)) {
+        (Some(start), Some(end)) => Pattern::range(start..=end),
+        _ => Pattern::symbols(Symbol::from(start)..=Symbol::from(end)),
+    }
+}
+
+/// The pattern matching the patterns one after another.
+fn sequence(items: Vec<Pattern>) -> Pattern {
+    items.into_iter().reduce(|lhs, rhs| lhs >> rhs).unwrap_or(Pattern::Always)
+}
+
+/// The pattern matching from `min` to `max` repetitions of the pattern, or at least `min`
+/// repetitions if there is no `max`. The optional repetitions are a flat sequence, like `a?a?`,
+/// instead of the equivalent nested one, like `(?:aa?)?`, so the depth of the result does not
+/// depend on the counts.
+fn repeat(pattern: &Pattern, min: usize, max: Option<usize>) -> Pattern {
+    let required = iter::repeat(pattern.clone()).take(min);
+    let optional = match max {
+        None => iter::repeat(pattern.many()).take(1),
+        Some(max) => iter::repeat(pattern.opt()).take(max - min),
+    };
+    sequence(required.chain(optional).collect())
+}
+
+/// The number of the nested nodes of the pattern, not counting its leaves.
+fn depth(pattern: &Pattern) -> usize {
+    match pattern {
+        Pattern::Range(_) | Pattern::Always | Pattern::Never => 0,
+        Pattern::Many(body) => 1 + depth(body),
+        Pattern::Seq(items) | Pattern::Or(items) => 1 + items.iter().map(depth).max().unwrap_or(0),
+    }
+}
+
+/// The number of the nodes of the pattern.
+fn size(pattern: &Pattern) -> usize {
+    match pattern {
+        Pattern::Range(_) | Pattern::Always | Pattern::Never => 1,
+        Pattern::Many(body) => 1 + size(body),
+        Pattern::Seq(items) | Pattern::Or(items) => 1 + items.iter().map(size).sum::<usize>(),
+    }
+}
+
+/// Sort the ranges, merging the overlapping and the adjacent ones.
+fn normalize(mut ranges: Vec<(SymbolIndex, SymbolIndex)>) -> Vec<(SymbolIndex, SymbolIndex)> {
+    ranges.sort_unstable();
+    let mut normalized: Vec<(SymbolIndex, SymbolIndex)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match normalized.last_mut() {
+            Some(last) if start <= last.1.saturating_add(1) => last.1 = last.1.max(end),
+            _ => normalized.push((start, end)),
+        }
+    }
+    normalized
+}
+
+/// The Unicode scalar values not in the ranges.
+fn complement(ranges: Vec<(SymbolIndex, SymbolIndex)>) -> Vec<(SymbolIndex, SymbolIndex)> {
+    let ranges = normalize(ranges);
+    let mut complement = vec![];
+    for (universe_start, universe_end) in UNICODE {
+        let mut next = universe_start;
+        for &(start, end) in &ranges {
+            if end < next || start > universe_end {
+                continue;
+            }
+            if start > next {
+                complement.push((next, start - 1));
+            }
+            next = end.saturating_add(1);
+        }
+        if next <= universe_end {
+            complement.push((next, universe_end));
+        }
+    }
+    complement
+}
+
+/// The ranges of a predefined class of the `regex` crate, like `\d` or `.`.
+fn perl_class(expression: &str) -> Vec<(SymbolIndex, SymbolIndex)> {
+    unicode_class(expression).expect("The predefined classes are known to the `regex` crate.")
+}
+
+/// The ranges of the Unicode class given in the `regex` crate syntax.
+fn unicode_class(expression: &str) -> Option<Vec<(SymbolIndex, SymbolIndex)>> {
+    let hir = regex_syntax::Parser::new().parse(expression).ok()?;
+    match hir.into_kind() {
+        hir::HirKind::Class(hir::Class::Unicode(class)) => Some(
+            class
+                .iter()
+                .map(|range| (range.start() as SymbolIndex, range.end() as SymbolIndex))
+                .collect(),
+        ),
+        _ => None,
+    }
+}
+
+fn char_of(symbol: SymbolIndex) -> Option<char> {
+    u32::try_from(symbol).ok().and_then(char::from_u32)
+}
+
+
+
+// ================
+// === Printing ===
+// ================
+
+impl Display for Pattern {
+    /// Print the pattern as a regular expression, which [`parse`] converts to an equivalent
+    /// pattern.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Node::from(self).write(f, Precedence::Alternation)
+    }
+}
+
+/// The binding strength of the regular expression operators.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Precedence {
+    Alternation,
+    Concatenation,
+    Repetition,
+    Atom,
+}
+
+/// A simplified pattern, mapping directly to the regular expression syntax.
+#[derive(Clone, Debug)]
+enum Node {
+    Class(Vec<(SymbolIndex, SymbolIndex)>),
+    Empty,
+    Concatenation(Vec<Node>),
+    Alternation(Vec<Node>),
+    Many(Box<Node>),
+    Optional(Box<Node>),
+}
+
+impl From<&Pattern> for Node {
+    fn from(pattern: &Pattern) -> Self {
+        match pattern {
+            Pattern::Range(range) => Node::Class(vec![(range.start().index, range.end().index)]),
+            Pattern::Never => Node::Class(vec![]),
+            Pattern::Always => Node::Empty,
+            Pattern::Many(body) => Node::Many(Box::new(body.as_ref().into())),
+            Pattern::Seq(items) => {
+                let mut nodes = vec![];
+                for node in items.iter().map(Node::from) {
+                    match node {
+                        Node::Empty => {}
+                        Node::Concatenation(inner) => nodes.extend(inner),
+                        node => nodes.push(node),
+                    }
+                }
+                match nodes.len() {
+                    0 => Node::Empty,
+                    1 => nodes.pop().unwrap(),
+                    _ => Node::Concatenation(nodes),
+                }
+            }
+            Pattern::Or(items) => {
+                let mut nodes = vec![];
+                let mut is_optional = false;
+                for node in items.iter().map(Node::from) {
+                    match node {
+                        Node::Class(class) if class.is_empty() => {}
+                        Node::Empty => is_optional = true,
+                        Node::Alternation(inner) => nodes.extend(inner),
+                        Node::Optional(body) => {
+                            is_optional = true;
+                            nodes.push(*body);
+                        }
+                        node => nodes.push(node),
+                    }
+                }
+                let node = if nodes.iter().all(|node| matches!(node, Node::Class(_))) {
+                    let classes = nodes.into_iter().map(|node| match node {
+                        Node::Class(class) => class,
+                        _ => default(),
+                    });
+                    Node::Class(classes.flatten().collect())
+                } else if nodes.len() == 1 {
+                    mem::replace(&mut nodes[0], Node::Empty)
+                } else {
+                    Node::Alternation(nodes)
+                };
+                match node {
+                    Node::Class(class) if class.is_empty() && is_optional => Node::Empty,
+                    node if is_optional => Node::Optional(Box::new(node)),
+                    node => node,
+                }
+            }
+        }
+    }
+}
+
+impl Node {
+    fn precedence(&self) -> Precedence {
+        match self {
+            Node::Class(_) => Precedence::Atom,
+            Node::Empty | Node::Concatenation(_) => Precedence::Concatenation,
+            Node::Alternation(_) => Precedence::Alternation,
+            Node::Many(_) | Node::Optional(_) => Precedence::Repetition,
+        }
+    }
+
+    /// Write the node, grouping it if it binds weaker than `min`.
+    fn write(&self, f: &mut fmt::Formatter, min: Precedence) -> fmt::Result {
+        if self.precedence() < min {
+            write!(f, "(?:")?;
+            self.write(f, Precedence::Alternation)?;
+            return write!(f, ")");
+        }
+        match self {
+            Node::Class(class) => write_class(f, class),
+            Node::Empty => Ok(()),
+            Node::Concatenation(nodes) =>
+                nodes.iter().try_for_each(|node| node.write(f, Precedence::Repetition)),
+            Node::Alternation(nodes) => {
+                for (index, node) in nodes.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "|")?;
+                    }
+                    node.write(f, Precedence::Concatenation)?;
+                }
+                Ok(())
+            }
+            Node::Many(body) => {
+                body.write(f, Precedence::Atom)?;
+                write!(f, "*")
+            }
+            Node::Optional(body) => {
+                body.write(f, Precedence::Atom)?;
+                write!(f, "?")
+            }
+        }
+    }
+}
+
+fn write_class(f: &mut fmt::Formatter, class: &[(SymbolIndex, SymbolIndex)]) -> fmt::Result {
+    match class {
+        [] => write!(f, "{}", EMPTY_CLASS),
+        [(start, end)] if start == end => write_symbol(f, *start),
+        _ => {
+            write!(f, "[")?;
+            for &(start, end) in class {
+                write_symbol(f, start)?;
+                if end != start {
+                    write!(f, "-")?;
+                    write_symbol(f, end)?;
+                }
+            }
+            write!(f, "]")
+        }
+    }
+}
+
+fn write_symbol(f: &mut fmt::Formatter, symbol: SymbolIndex) -> fmt::Result {
+    match char_of(symbol) {
+        Some(char) if META_CHARACTERS.contains(char) => write!(f, "\\{}", char),
+        Some('\n') => write!(f, "\\n"),
+        Some('\t') => write!(f, "\\t"),
+        Some('\r') => write!(f, "\\r"),
+        Some(char) if char == ' ' || !(char.is_control() || char.is_whitespace()) =>
+            write!(f, "{}", char),
+        _ => write!(f, "\\x{{{:X}}}", symbol),
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::prelude::*;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::BTreeSet;
+
+    /// Expressions supported by both this module and the `regex` crate.
+    const EXPRESSIONS: &[&str] = &[
+        "",
+        "a",
+        "abc",
+        "a|b|",
+        "(?:ab|c)*d",
+        "a+b?c*",
+        "(a|bc){2}",
+        "a{1,3}b{2,}",
+        "[a-c_]+",
+        "[^a-c]",
+        "[]a]",
+        "[-a]|[a-]",
+        "\\.\\*\\\\|\\[\\]",
+        "\\x41\\u{62}\\n",
+        ".",
+        "\\d+(?:\\.\\d+)?",
+        "\\w+\\s\\W",
+        "[\\d_]\\D",
+        "\\p{Lu}\\pL*",
+        "\\P{L}",
+        "\\p{Greek}+",
+        "é|中文",
+    ];
+
+    /// Characters used in the random inputs.
+    const INPUT_CHARACTERS: &[char] = &[
+        'a', 'b', 'c', 'd', 'A', 'Z', '_', '-', ']', '.', '*', '\\', '[', '0', '7', ' ', '\n', 'é',
+        'Σ', '中', '文', '٣',
+    ];
+
+    /// The offsets in the input where the matches of the pattern starting at `start` end. The
+    /// pattern is interpreted directly, as the automata of the large Unicode classes take long to
+    /// build.
+    fn match_ends(pattern: &Pattern, input: &[Symbol], start: usize) -> BTreeSet<usize> {
+        match pattern {
+            Pattern::Range(range) => input
+                .get(start)
+                .filter(|symbol| range.contains(symbol))
+                .map(|_| start + 1)
+                .into_iter()
+                .collect(),
+            Pattern::Always => iter::once(start).collect(),
+            Pattern::Never => default(),
+            Pattern::Seq(items) => items.iter().fold(iter::once(start).collect(), |ends, item| {
+                ends.into_iter().flat_map(|end| match_ends(item, input, end)).collect()
+            }),
+            Pattern::Or(items) =>
+                items.iter().flat_map(|item| match_ends(item, input, start)).collect(),
+            Pattern::Many(body) => {
+                let mut ends: BTreeSet<_> = iter::once(start).collect();
+                let mut pending = vec![start];
+                while let Some(end) = pending.pop() {
+                    for next in match_ends(body, input, end) {
+                        if ends.insert(next) {
+                            pending.push(next);
+                        }
+                    }
+                }
+                ends
+            }
+        }
+    }
+
+    fn pattern_accepts(pattern: &Pattern, inputs: &[String]) -> Vec<bool> {
+        let accepts = |input: &String| {
+            let symbols = input.chars().map(Symbol::from).collect_vec();
+            match_ends(pattern, &symbols, 0).contains(&symbols.len())
+        };
+        inputs.iter().map(accepts).collect()
+    }
+
+    fn regex_accepts(expression: &str, inputs: &[String]) -> Vec<bool> {
+        let regex = regex::Regex::new(&format!("^(?:{})$", expression)).unwrap();
+        inputs.iter().map(|input| regex.is_match(input)).collect()
+    }
+
+    fn random_inputs(rng: &mut impl Rng) -> Vec<String> {
+        let mut random_input = || {
+            let length = rng.gen_range(0..=5);
+            (0..length).map(|_| *INPUT_CHARACTERS.choose(rng).unwrap()).collect::<String>()
+        };
+        (0..300).map(|_| random_input()).collect()
+    }
+
+    #[test]
+    fn parse_structure() {
+        let a = Pattern::char('a');
+        let b = Pattern::char('b');
+        assert_eq!(parse("ab|b*").unwrap(), (&a >> &b) | b.many());
+        assert_eq!(parse("(?:a|b)?").unwrap(), (&a | &b).opt());
+        assert_eq!(parse("[a-c]").unwrap(), Pattern::range('a'..='c'));
+        assert_eq!(parse("[ca-b]").unwrap(), Pattern::range('a'..='c'));
+        assert_eq!(parse("a{2}").unwrap(), &a >> &a);
+        assert_eq!(parse("").unwrap(), Pattern::Always);
+        assert_eq!(parse("\\x{FFFFFFFFFFFFFFFF}").unwrap(), Pattern::eof());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let error = |expression: &str| parse(expression).unwrap_err();
+        assert_eq!(error("a(bc"), Error::new(1, ErrorKind::UnclosedGroup));
+        assert_eq!(error("ab)c"), Error::new(2, ErrorKind::UnopenedGroup));
+        assert_eq!(error("a[bc"), Error::new(1, ErrorKind::UnclosedClass));
+        assert_eq!(error("[ab-a]"), Error::new(2, ErrorKind::InvalidRange));
+        assert_eq!(error("a|*"), Error::new(2, ErrorKind::NothingToRepeat));
+        assert_eq!(error("ab{3,1}"), Error::new(2, ErrorKind::InvalidRepetition));
+        assert_eq!(error("a{x}"), Error::new(1, ErrorKind::InvalidRepetition));
+        assert_eq!(error("é\\q"), Error::new(2, ErrorKind::InvalidEscape));
+        assert_eq!(error("\\x{zz}"), Error::new(0, ErrorKind::InvalidEscape));
+        let unknown_class = ErrorKind::UnknownClass("Foo".into());
+        assert_eq!(error("a\\p{Foo}"), Error::new(1, unknown_class));
+        assert_eq!(error("^a"), Error::new(0, ErrorKind::Unsupported("anchor")));
+        assert_eq!(error("a*?"), Error::new(2, ErrorKind::Unsupported("lazy repetition")));
+        assert_eq!(error("(?i)a"), Error::new(0, ErrorKind::Unsupported("group flags")));
+        assert_eq!(error("(a)\\1"), Error::new(3, ErrorKind::Unsupported("backreference")));
+        let message = error("a(bc").to_string();
+        assert_eq!(message, "Unclosed group at position 1.");
+    }
+
+    #[test]
+    fn repetition_limits() {
+        let error = |expression: &str| parse(expression).unwrap_err();
+        let invalid = |position| Error::new(position, ErrorKind::InvalidRepetition);
+        assert!(parse(&format!("a{{{}}}", REPETITION_LIMIT)).is_ok());
+        assert!(parse(&format!("a{{0,{}}}", REPETITION_LIMIT)).is_ok());
+        assert_eq!(error("a{0,100000}"), invalid(1));
+        assert_eq!(error("ba{1000000}"), invalid(2));
+        assert_eq!(error("a{1001,}"), invalid(1));
+        assert_eq!(error("(?:a{1000}){1000}"), invalid(11));
+        assert_eq!(error(&format!("(?:a{{1000}}){{2}}{}", "+".repeat(7))), invalid(19));
+        let pattern = parse(&format!("a{{0,{}}}", REPETITION_LIMIT)).unwrap();
+        assert_eq!(pattern.to_string(), "a?".repeat(REPETITION_LIMIT));
+    }
+
+    #[test]
+    fn nesting_limits() {
+        let error = |expression: &str| parse(expression).unwrap_err();
+        let too_deep = |position| Error::new(position, ErrorKind::Unsupported("nesting depth"));
+        let nested = |depth: usize| format!("{}a{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(NEST_LIMIT)).is_ok());
+        assert_eq!(error(&nested(NEST_LIMIT + 1)), too_deep(NEST_LIMIT));
+        assert_eq!(error(&nested(100_000)), too_deep(NEST_LIMIT));
+        assert!(parse(&format!("a{}", "*".repeat(NEST_LIMIT))).is_ok());
+        assert_eq!(error(&format!("a{}", "*".repeat(100_000))), too_deep(NEST_LIMIT + 1));
+        let repeated_groups =
+            |depth: usize| format!("{}a{}", "(?:".repeat(depth), ")*".repeat(depth));
+        assert!(parse(&repeated_groups(NEST_LIMIT)).is_ok());
+        assert_eq!(error(&repeated_groups(NEST_LIMIT + 1)), too_deep(3 * NEST_LIMIT));
+        let expression = format!("{}{}", repeated_groups(200), "*".repeat(51));
+        assert_eq!(error(&expression), too_deep(expression.len() - 1));
+    }
+
+    #[test]
+    fn print_patterns() {
+        let a = Pattern::char('a');
+        let b = Pattern::char('b');
+        assert_eq!((&a >> &b | b.many()).to_string(), "ab|b*");
+        assert_eq!((&a >> &b).many().to_string(), "(?:ab)*");
+        assert_eq!((&a | &b).opt().to_string(), "[ab]?");
+        assert_eq!(Pattern::any_of("a.-").to_string(), "[a\\.\\-]");
+        assert_eq!(Pattern::all_of("a.b").to_string(), "a\\.b");
+        assert_eq!(Pattern::range('a'..='z').many1().to_string(), "[a-z][a-z]*");
+        assert_eq!(Pattern::char('\u{1}').to_string(), "\\x{1}");
+        assert_eq!(Pattern::never().to_string(), EMPTY_CLASS);
+        assert_eq!(Pattern::always().many().to_string(), "(?:)*");
+        assert_eq!(Pattern::eof().to_string(), "\\x{FFFFFFFFFFFFFFFF}");
+    }
+
+    #[test]
+    fn same_language_as_regex_crate() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        for &expression in EXPRESSIONS {
+            let inputs = random_inputs(&mut rng);
+            let expected = regex_accepts(expression, &inputs);
+            let pattern = parse(expression).unwrap();
+            assert_eq!(pattern_accepts(&pattern, &inputs), expected, "{:?}", expression);
+            let printed = pattern.to_string();
+            assert_eq!(regex_accepts(&printed, &inputs), expected, "{:?}", printed);
+            let reparsed = parse(&printed).unwrap();
+            assert_eq!(pattern_accepts(&reparsed, &inputs), expected, "{:?}", printed);
+        }
+    }
+}
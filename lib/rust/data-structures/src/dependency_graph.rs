@@ -34,6 +34,33 @@ impl<Edge> Node<Edge> {
 
 
 
+// =============
+// === Cycle ===
+// =============
+
+/// A cycle of dependencies. Each node has to be sorted before the next one, and the last node has
+/// to be sorted before the first one.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub struct Cycle<T> {
+    pub nodes: Vec<T>,
+}
+
+impl<T: Debug> Display for Cycle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Dependency cycle: ")?;
+        for node in &self.nodes {
+            write!(f, "{node:?} -> ")?;
+        }
+        match self.nodes.first() {
+            Some(first) => write!(f, "{first:?}."),
+            None => write!(f, "empty."),
+        }
+    }
+}
+
+
+
 // =======================
 // === DependencyGraph ===
 // =======================
@@ -42,7 +69,10 @@ impl<Edge> Node<Edge> {
 ///
 /// The primary use case of this graph is topological sorting of dependencies. Please note that this
 /// graph implementation is not DAG, it can contain cycles. In case a cycle occurs it will be
-/// automatically broken on the lowest node id.
+/// automatically broken on the lowest node id. Use [`DependencyGraph::try_topo_sort`] or
+/// [`DependencyGraph::find_cycle`] to detect the cycles instead, and the
+/// [`IncrementalDependencyGraph`] to keep the topological order up to date without re-sorting the
+/// whole graph after each change.
 #[derive(Clone)]
 #[derive(Derivative)]
 #[derivative(Default(bound = "T:Eq+Hash+Ord"))]
@@ -201,6 +231,58 @@ impl<T: Clone + Eq + Hash + Ord> DependencyGraph<T> {
         }
         sorted
     }
+
+    /// Sorts the provided indexes in topological order based on the rules recorded in the graph,
+    /// just like [`topo_sort`]. In case the dependencies between the provided indexes are cyclic,
+    /// returns one of the cycles instead.
+    pub fn try_topo_sort(&self, keys: &[T]) -> Result<Vec<T>, Cycle<T>> {
+        let sorted_keys = keys.iter().cloned().sorted().collect_vec();
+        let graph = self.clone().unchecked_kept_only(sorted_keys.iter().cloned());
+        match graph.find_cycle() {
+            Some(cycle) => Err(cycle),
+            None => Ok(graph.into_unchecked_topo_sort(sorted_keys)),
+        }
+    }
+
+    /// Find a cycle of dependencies in the graph, if any. The graph is searched in depth-first
+    /// order, starting from the lowest node id.
+    pub fn find_cycle(&self) -> Option<Cycle<T>> {
+        let mut finished = BTreeSet::<&T>::new();
+        for root in self.nodes.keys() {
+            if finished.contains(root) {
+                continue;
+            }
+            // The currently visited path, with the index of the next outgoing edge of each node.
+            let mut path = vec![(root, 0)];
+            let mut on_path = BTreeSet::<&T>::new();
+            on_path.insert(root);
+            while let Some((node, ix)) = path.last_mut() {
+                let node = *node;
+                let out = self.nodes.get(node).map(|t| &t.out[..]).unwrap_or_default();
+                match out.get(*ix) {
+                    Some(next) => {
+                        *ix += 1;
+                        if on_path.contains(next) {
+                            let start = path.iter().position(|(t, _)| *t == next);
+                            let cycle = path[start.unwrap_or_default()..].iter();
+                            let nodes = cycle.map(|(t, _)| (*t).clone()).collect();
+                            return Some(Cycle { nodes });
+                        }
+                        if !finished.contains(next) {
+                            path.push((next, 0));
+                            on_path.insert(next);
+                        }
+                    }
+                    None => {
+                        on_path.remove(node);
+                        finished.insert(node);
+                        path.pop();
+                    }
+                }
+            }
+        }
+        None
+    }
 }
 
 
@@ -228,6 +310,150 @@ impl<T: Ord> Extend<(T, Node<T>)> for DependencyGraph<T> {
 
 
 
+// ==================================
+// === IncrementalDependencyGraph ===
+// ==================================
+
+/// Dependency graph maintaining the topological order of its nodes across insertions and removals
+/// of dependencies, instead of sorting the whole graph on every query.
+///
+/// The order is maintained with the Pearce-Kelly algorithm. When an inserted dependency contradicts
+/// the current order, only the nodes positioned between its ends and reachable from them are
+/// reordered. Unlike [`DependencyGraph`], this graph is always a DAG. Dependencies which would
+/// create a cycle are rejected. Follow the link to learn more:
+/// https://www.doc.ic.ac.uk/~phjk/Publications/DynamicTopoSortAlg-JEA-07.pdf.
+#[derive(Clone)]
+#[derive(Derivative)]
+#[derivative(Default(bound = "T:Eq+Hash+Ord"))]
+#[derivative(Debug(bound = "T:Debug+Eq+Hash"))]
+pub struct IncrementalDependencyGraph<T> {
+    graph:         DependencyGraph<T>,
+    position:      BTreeMap<T, usize>,
+    next_position: usize,
+}
+
+impl<T: Clone + Eq + Hash + Ord> IncrementalDependencyGraph<T> {
+    /// Constructor.
+    pub fn new() -> Self {
+        default()
+    }
+
+    /// The graph of the dependencies.
+    pub fn graph(&self) -> &DependencyGraph<T> {
+        &self.graph
+    }
+
+    /// Insert a new dependency to the graph. Returns [`true`] if the insertion was successful
+    /// (the dependency was not present already), or [`false`] otherwise. In case the dependency
+    /// would create a cycle, it is not inserted and the cycle is returned instead.
+    pub fn insert_dependency(&mut self, first: T, second: T) -> Result<bool, Cycle<T>> {
+        if first == second {
+            return Err(Cycle { nodes: vec![first] });
+        }
+        let lower = self.position_or_insert(&second);
+        let upper = self.position_or_insert(&first);
+        if lower < upper {
+            let forward = self.reachable_forward(&second, &first, upper)?;
+            let backward = self.reachable_backward(&first, lower);
+            self.reorder(backward, forward);
+        }
+        Ok(self.graph.insert_dependency(first, second))
+    }
+
+    /// Remove a dependency from the graph. Returns [`true`] if the dependency was found, or
+    /// [`false`] otherwise. Removing a dependency never invalidates the order, so no reordering
+    /// is performed.
+    pub fn remove_dependency(&mut self, first: T, second: T) -> bool {
+        let found = self.graph.remove_dependency(first.clone(), second.clone());
+        for key in [first, second] {
+            if !self.graph.nodes.contains_key(&key) {
+                self.position.remove(&key);
+            }
+        }
+        found
+    }
+
+    /// Sorts the provided indexes in topological order based on the rules recorded in the graph.
+    /// The indexes without any dependencies are placed after all the other ones, in ascending
+    /// order.
+    pub fn topo_sort(&self, keys: &[T]) -> Vec<T> {
+        let (mut sorted, unordered): (Vec<T>, Vec<T>) =
+            keys.iter().cloned().partition(|key| self.position.contains_key(key));
+        sorted.sort_by_cached_key(|key| self.position[key]);
+        sorted.extend(unordered.into_iter().sorted());
+        sorted
+    }
+
+    /// Get the position of the node in the order, placing it after all other nodes if it was not
+    /// present in the graph.
+    fn position_or_insert(&mut self, key: &T) -> usize {
+        let next_position = &mut self.next_position;
+        *self.position.entry(key.clone()).or_insert_with(|| {
+            *next_position += 1;
+            *next_position - 1
+        })
+    }
+
+    /// Collect the nodes reachable from `start` which are positioned before `upper`. Fails with
+    /// the cycle in case the `target` is reachable, assuming that the dependency from `target` to
+    /// `start` is being inserted.
+    fn reachable_forward(&self, start: &T, target: &T, upper: usize) -> Result<Vec<T>, Cycle<T>> {
+        let mut parents = BTreeMap::<&T, Option<&T>>::new();
+        let mut stack = vec![start];
+        parents.insert(start, None);
+        while let Some(node) = stack.pop() {
+            for next in self.graph.nodes.get(node).iter().flat_map(|t| &t.out) {
+                if next == target {
+                    let mut nodes = vec![node.clone()];
+                    let mut current = node;
+                    while let Some(Some(parent)) = parents.get(current) {
+                        nodes.push((*parent).clone());
+                        current = parent;
+                    }
+                    nodes.push(target.clone());
+                    nodes.reverse();
+                    return Err(Cycle { nodes });
+                }
+                if !parents.contains_key(next) && self.position[next] < upper {
+                    parents.insert(next, Some(node));
+                    stack.push(next);
+                }
+            }
+        }
+        Ok(parents.into_keys().cloned().collect())
+    }
+
+    /// Collect the nodes from which `start` is reachable, which are positioned after `lower`.
+    fn reachable_backward(&self, start: &T, lower: usize) -> Vec<T> {
+        let mut visited = BTreeSet::<&T>::new();
+        let mut stack = vec![start];
+        visited.insert(start);
+        while let Some(node) = stack.pop() {
+            for next in self.graph.nodes.get(node).iter().flat_map(|t| &t.ins) {
+                if !visited.contains(next) && self.position[next] > lower {
+                    visited.insert(next);
+                    stack.push(next);
+                }
+            }
+        }
+        visited.into_iter().cloned().collect()
+    }
+
+    /// Place the `backward` nodes before the `forward` nodes, reusing their positions. The
+    /// relative order within both groups is preserved.
+    fn reorder(&mut self, mut backward: Vec<T>, mut forward: Vec<T>) {
+        backward.sort_by_key(|key| self.position[key]);
+        forward.sort_by_key(|key| self.position[key]);
+        let nodes = backward.into_iter().chain(forward);
+        let positions = nodes.clone().map(|key| self.position[&key]).sorted().collect_vec();
+        for (key, position) in nodes.zip(positions) {
+            self.position.insert(key, position);
+        }
+    }
+}
+
+
+
 // ==============
 // === Macros ===
 // ==============
@@ -289,6 +515,10 @@ macro_rules! assert_valid_sort {
 mod tests {
     use super::*;
 
+    use rand::Rng;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
     #[test]
     fn test_identity() {
         assert_valid_sort! {
@@ -331,6 +561,82 @@ mod tests {
             [0,1,2] for {0->0,0->1,0->2,1->0,1->1,1->2,2->0,2->1,2->2}
         }
     }
+
+    #[test]
+    fn test_find_cycle() {
+        let cycle = |nodes: &[usize]| Some(Cycle { nodes: nodes.to_vec() });
+        assert_eq!(dependency_graph!(0->1, 1->2).find_cycle(), None);
+        assert_eq!(dependency_graph!(0->1, 0->2, 1->2).find_cycle(), None);
+        assert_eq!(dependency_graph!(1->1).find_cycle(), cycle(&[1]));
+        assert_eq!(dependency_graph!(0->1, 1->2, 2->0).find_cycle(), cycle(&[0, 1, 2]));
+        assert_eq!(dependency_graph!(0->1, 1->2, 2->3, 3->1).find_cycle(), cycle(&[1, 2, 3]));
+    }
+
+    #[test]
+    fn test_try_topo_sort() {
+        let graph = dependency_graph!(2->1, 3->4, 4->3);
+        assert_eq!(graph.try_topo_sort(&[0, 1, 2]), Ok(vec![0, 2, 1]));
+        assert_eq!(graph.try_topo_sort(&[0, 1, 2, 3]), Ok(vec![0, 2, 1, 3]));
+        assert_eq!(graph.try_topo_sort(&[3, 4]), Err(Cycle { nodes: vec![3, 4] }));
+    }
+
+    #[test]
+    fn test_incremental_order() {
+        let mut graph = IncrementalDependencyGraph::new();
+        assert_eq!(graph.insert_dependency(1, 2), Ok(true));
+        assert_eq!(graph.insert_dependency(1, 2), Ok(false));
+        assert_eq!(graph.topo_sort(&[0, 1, 2]), vec![1, 2, 0]);
+        assert_eq!(graph.insert_dependency(2, 0), Ok(true));
+        assert_eq!(graph.insert_dependency(3, 1), Ok(true));
+        assert_eq!(graph.topo_sort(&[0, 1, 2, 3]), vec![3, 1, 2, 0]);
+        assert_eq!(graph.insert_dependency(0, 3), Err(Cycle { nodes: vec![0, 3, 1, 2] }));
+        assert_eq!(graph.insert_dependency(0, 0), Err(Cycle { nodes: vec![0] }));
+        assert!(graph.remove_dependency(3, 1));
+        assert!(!graph.remove_dependency(3, 1));
+        assert_eq!(graph.insert_dependency(0, 3), Ok(true));
+        assert_eq!(graph.topo_sort(&[0, 1, 2, 3]), vec![1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn test_incremental_order_random() {
+        let mut rng = ChaCha8Rng::seed_from_u64(0);
+        let keys = (0..20).collect_vec();
+        let mut graph = DependencyGraph::new();
+        let mut incremental = IncrementalDependencyGraph::new();
+        for _ in 0..2000 {
+            let first = rng.gen_range(0..keys.len());
+            let second = rng.gen_range(0..keys.len());
+            if rng.gen_bool(0.3) {
+                let found = graph.remove_dependency(first, second);
+                assert_eq!(incremental.remove_dependency(first, second), found);
+            } else {
+                let mut extended = graph.clone();
+                let inserted = extended.insert_dependency(first, second);
+                match incremental.insert_dependency(first, second) {
+                    Ok(result) => {
+                        assert_eq!(result, inserted);
+                        assert_eq!(extended.find_cycle(), None);
+                        graph = extended;
+                    }
+                    Err(cycle) => {
+                        assert!(extended.find_cycle().is_some());
+                        let edges = cycle.nodes.iter().zip(cycle.nodes.iter().cycle().skip(1));
+                        for (node, next) in edges {
+                            let out = graph.nodes.get(node).map(|t| &t.out[..]).unwrap_or_default();
+                            assert!(out.contains(next) || (*node, *next) == (first, second));
+                        }
+                    }
+                }
+            }
+            let sorted = incremental.topo_sort(&keys);
+            let position = |key: &usize| sorted.iter().position(|t| t == key);
+            for (key, node) in &graph {
+                for next in &node.out {
+                    assert!(position(key) < position(next));
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -371,4 +677,109 @@ mod benches {
         }
         b.iter(move || assert_eq!(graph.topo_sort(&out), out));
     }
+
+    /// The shape orderings declared with `shapes_order_dependencies!` by the GUI components: the
+    /// node, its action bar, the breadcrumbs, the visualization container, the project list, and
+    /// the window control buttons. All of them are registered in the default scene layer.
+    const SHAPES_ORDER_DEPENDENCIES: &[(&str, &str)] = &[
+        ("node::edge::back::corner", "node::backdrop"),
+        ("node::edge::back::line", "node::backdrop"),
+        ("node::edge::back::corner", "node::error_shape"),
+        ("node::edge::back::line", "node::error_shape"),
+        ("node::error_shape", "node::backdrop"),
+        ("node::backdrop", "node::output::port::single_port"),
+        ("node::backdrop", "node::output::port::multi_port"),
+        ("node::output::port::single_port", "node::background"),
+        ("node::output::port::multi_port", "node::background"),
+        ("node::background", "node::drag_area"),
+        ("node::drag_area", "node::edge::front::corner"),
+        ("node::drag_area", "node::edge::front::line"),
+        ("node::edge::front::corner", "node::input::port::hover"),
+        ("node::edge::front::line", "node::input::port::hover"),
+        ("node::input::port::hover", "node::input::port::viz"),
+        ("action_bar::hover_area", "action_bar::icon::freeze"),
+        ("action_bar::hover_area", "action_bar::icon::visibility"),
+        ("action_bar::hover_area", "action_bar::icon::skip"),
+        ("breadcrumb::background", "breadcrumb::icon"),
+        ("breadcrumb::background", "breadcrumb::separator"),
+        ("breadcrumbs::background", "breadcrumb::background"),
+        ("breadcrumbs::background", "project_name::background"),
+        ("visualization::background", "visualization::overlay"),
+        ("project_list::background", "list_view::selection"),
+        ("list_view::background", "project_list::background"),
+        ("window_control_buttons::shape", "close::shape"),
+        ("window_control_buttons::shape", "fullscreen::shape"),
+    ];
+
+    /// The number of elements of the layer without any ordering constraints, like the symbols of
+    /// the text areas and the shape systems not mentioned in [`SHAPES_ORDER_DEPENDENCIES`].
+    const UNORDERED_ELEMENTS: usize = 100;
+
+    type Dependency = (usize, usize);
+
+    /// A workload modeled on the depth-ordering of the default scene layer (see `LayerModel` in
+    /// `ensogl_core::display::scene::layer`). The shape orderings are registered once, when the
+    /// components are created. The layer is then sorted again whenever an element is added or
+    /// removed, e.g. when a shape system is instantiated for the first time.
+    ///
+    /// Returns the elements, the dependencies, and the elements removed and added again between
+    /// the sorts.
+    fn layer_workload() -> (Vec<usize>, Vec<Dependency>, Vec<usize>) {
+        let mut ids = HashMap::<&str, usize>::new();
+        let mut id = |name| {
+            let next_id = ids.len();
+            *ids.entry(name).or_insert(next_id)
+        };
+        let dependencies =
+            SHAPES_ORDER_DEPENDENCIES.iter().map(|(below, above)| (id(below), id(above))).collect();
+        let elements = (0..ids.len() + UNORDERED_ELEMENTS).collect_vec();
+        let changes = elements.iter().copied().step_by(10).collect();
+        (elements, dependencies, changes)
+    }
+
+    /// The elements of the layer, with the given one removed.
+    fn without(elements: &[usize], removed: usize) -> Vec<usize> {
+        elements.iter().copied().filter(|&element| element != removed).collect()
+    }
+
+    /// # Results (ms)
+    ///
+    ///   elements | time(ms) |
+    ///   129      | 0.37     |
+    #[bench]
+    fn bench_layer_workload(b: &mut Bencher) {
+        let (elements, dependencies, changes) = layer_workload();
+        let mut graph = DependencyGraph::new();
+        for (first, second) in dependencies {
+            graph.insert_dependency(first, second);
+        }
+        // Like the layer, sort a copy of the graph after every change of the elements.
+        b.iter(move || {
+            for &element in &changes {
+                test::black_box(
+                    graph.clone().into_unchecked_topo_sort(without(&elements, element)),
+                );
+                test::black_box(graph.clone().into_unchecked_topo_sort(elements.clone()));
+            }
+        });
+    }
+
+    /// # Results (ms)
+    ///
+    ///   elements | time(ms) |
+    ///   129      | 0.08     |
+    #[bench]
+    fn bench_layer_workload_incremental(b: &mut Bencher) {
+        let (elements, dependencies, changes) = layer_workload();
+        let mut graph = IncrementalDependencyGraph::new();
+        for (first, second) in dependencies {
+            graph.insert_dependency(first, second).unwrap();
+        }
+        b.iter(move || {
+            for &element in &changes {
+                test::black_box(graph.topo_sort(&without(&elements, element)));
+                test::black_box(graph.topo_sort(&elements));
+            }
+        });
+    }
 }
@@ -4,18 +4,10 @@
 //! order and a predecessor and a successor function. Follow the link to learn more:
 //! https://web.engr.oregonstate.edu/~erwig/diet.
 //!
-//! # WARNING
-//! This implementation is not finished. It is provided as one of alternative solutions to the
-//! problem of efficient attribute memory management in EnsoGL. Read the docs of
-//! [`ensogl::AttributeScopeData`] to learn more. This implementation has the following
-//! shortcomings:
-//!
-//! 1. No implementation of merging of intervals in case they are not in the same layer. For
-//!    example, assuming a tree with values `1` and `7` in one layer, and value `3` in child-layer,
-//!    inserting the value `2` would not merge `1`,`2`, and `3` into a single [`Interval`].
-//!
-//! 2. No implementation of removing elements. This should be straightforward. The algorithm is
-//!    described here: https://en.wikipedia.org/wiki/B-tree#Algorithms.
+//! The tree is a B-tree of disjoint, non-adjacent [`Interval`]s. Besides inserting and removing
+//! single values and whole intervals, it supports checking whether a value is contained in it,
+//! iterating over the stored intervals in ascending order, computing the gaps between them, and the
+//! union, intersection, and difference of trees.
 //!
 //! # Benchmarks
 //! This module contains a lot of benchmarks in order to compare different techniques of managing
@@ -67,6 +59,94 @@ impl From<(usize, usize)> for Interval {
     }
 }
 
+impl From<RangeInclusive<usize>> for Interval {
+    fn from(t: RangeInclusive<usize>) -> Self {
+        Interval(*t.start(), *t.end())
+    }
+}
+
+
+
+// ========================
+// === Sorted Intervals ===
+// ========================
+
+// The functions below operate on sequences of disjoint, non-adjacent intervals in ascending order,
+// and return sequences of the same form.
+
+/// The intervals of values contained in any of the sequences.
+fn union_sorted(
+    a: impl IntoIterator<Item = Interval>,
+    b: impl IntoIterator<Item = Interval>,
+) -> Vec<Interval> {
+    let mut out: Vec<Interval> = vec![];
+    for interval in a.into_iter().merge_by(b, |x, y| x.start <= y.start) {
+        match out.last_mut() {
+            Some(last) if interval.start <= last.end.saturating_add(1) =>
+                last.end = last.end.max(interval.end),
+            _ => out.push(interval),
+        }
+    }
+    out
+}
+
+/// The intervals of values contained in both sequences.
+fn intersection_sorted(
+    a: impl IntoIterator<Item = Interval>,
+    b: impl IntoIterator<Item = Interval>,
+) -> Vec<Interval> {
+    let mut a = a.into_iter().peekable();
+    let mut b = b.into_iter().peekable();
+    let mut out = vec![];
+    while let (Some(x), Some(y)) = (a.peek().copied(), b.peek().copied()) {
+        let start = x.start.max(y.start);
+        let end = x.end.min(y.end);
+        if start <= end {
+            out.push(Interval(start, end));
+        }
+        if x.end < y.end {
+            a.next();
+        } else {
+            b.next();
+        }
+    }
+    out
+}
+
+/// The intervals of values contained in the first sequence, but not in the second one.
+fn difference_sorted(
+    a: impl IntoIterator<Item = Interval>,
+    b: impl IntoIterator<Item = Interval>,
+) -> Vec<Interval> {
+    let mut b = b.into_iter().peekable();
+    let mut out = vec![];
+    for x in a {
+        // The start of the part of `x` not yet covered by the intervals of `b`.
+        let mut start = Some(x.start);
+        while let Some(current) = start {
+            match b.peek().copied() {
+                Some(y) if y.end < current => {
+                    b.next();
+                }
+                Some(y) if y.start <= x.end => {
+                    if y.start > current {
+                        out.push(Interval(current, y.start - 1));
+                    }
+                    start = (y.end < x.end).as_some(y.end + 1);
+                    if y.end <= x.end {
+                        b.next();
+                    }
+                }
+                _ => {
+                    out.push(Interval(current, x.end));
+                    start = None;
+                }
+            }
+        }
+    }
+    out
+}
+
 
 
 // ============
@@ -119,7 +199,8 @@ pub mod $modname {
 
 // === Constants ===
 
-const DATA_SIZE : usize = $num;
+const DATA_SIZE      : usize = $num;
+const MIN_DATA_COUNT : usize = DATA_SIZE / 2;
 type DataArray           = [Interval;$num];
 // FIXME: Potential performance gain. We could initialize only the needed elements in this array
 //        by changing it to `[MaybeUninit<$name>;inc!{$num}]`.
@@ -178,15 +259,16 @@ impl $name {
     }
 
     /// Perform linear search of the data layer for the provided value. Returns [`Ok`] containing
-    /// the index of the value or [`Err`] if the value was not found. In the later case, the result
-    /// will contain the index where the value should be inserted in order to keep the right
-    /// ordering.
+    /// the index of the interval containing the value or [`Err`] containing the index of the child
+    /// the value would be stored in.
     fn search_data(&self, t:usize) -> Result<usize,usize> {
         let mut out = Err(self.data_count);
         for i in 0..self.data_count {
             let interval = &self.data[i];
-            if      t + 1 <  interval.start   { out = Err(i) ; break }
-            else if t     <= interval.end + 1 { out = Ok(i)  ; break }
+            if t <= interval.end {
+                out = if t < interval.start { Err(i) } else { Ok(i) };
+                break
+            }
         }
         out
     }
@@ -244,7 +326,154 @@ impl $name {
 
     /// Insert a new value into this tree.
     pub fn insert(&mut self, t:usize) {
-        if let Some((median,left,right)) = self.insert_internal(t) {
+        self.insert_range(t)
+    }
+
+    /// Insert all values of the interval into this tree. The stored intervals overlapping or
+    /// adjacent to the inserted one are merged with it.
+    pub fn insert_range(&mut self, interval:impl Into<Interval>) {
+        let interval = interval.into();
+        if interval.start > interval.end { return }
+        let start    = interval.start.saturating_sub(1);
+        let end      = interval.end.saturating_add(1);
+        let touching = self.intervals_overlapping(Interval(start,end));
+        match (touching.first(),touching.last()) {
+            (Some(first),Some(last)) => {
+                let merged = Interval(first.start.min(interval.start),last.end.max(interval.end));
+                for other in &touching[1..] {
+                    self.remove_interval(other.start);
+                }
+                self.replace_interval(first.start,merged);
+            }
+            _ => self.insert_interval(interval)
+        }
+    }
+
+    /// Remove the value from this tree.
+    pub fn remove(&mut self, t:usize) {
+        self.remove_range(t)
+    }
+
+    /// Remove all values of the interval from this tree. The stored intervals partially overlapping
+    /// the removed one are shrunk, and the stored interval containing it is split in two.
+    pub fn remove_range(&mut self, interval:impl Into<Interval>) {
+        let interval = interval.into();
+        if interval.start > interval.end { return }
+        for other in self.intervals_overlapping(interval) {
+            let left  = Interval(other.start,interval.start.saturating_sub(1));
+            let right = Interval(interval.end.saturating_add(1),other.end);
+            match (other.start < interval.start, other.end > interval.end) {
+                (false,false) => self.remove_interval(other.start),
+                (true,false)  => self.replace_interval(other.start,left),
+                (false,true)  => self.replace_interval(other.start,right),
+                (true,true)   => {
+                    self.replace_interval(other.start,left);
+                    self.insert_interval(right);
+                }
+            }
+        }
+    }
+
+    /// Remove all values from this tree.
+    pub fn clear(&mut self) {
+        *self = default()
+    }
+
+    /// Check whether this tree contains no values.
+    pub fn is_empty(&self) -> bool {
+        self.data_count == 0
+    }
+
+    /// Check whether the value is contained in this tree.
+    pub fn contains(&self, t:usize) -> bool {
+        match self.search_data(t) {
+            Ok(_)    => true,
+            Err(pos) => self.children.as_ref().map_or(false, |children| children[pos].contains(t))
+        }
+    }
+
+    /// Iterator over the non-overlapping intervals of this tree in ascending order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter::new(self)
+    }
+
+    /// The maximal intervals of values within the bounds which are not contained in this tree, in
+    /// ascending order.
+    pub fn gaps(&self, bounds:impl Into<Interval>) -> Vec<Interval> {
+        let bounds = bounds.into();
+        if bounds.start > bounds.end { return default() }
+        difference_sorted(iter::once(bounds),self.intervals_overlapping(bounds))
+    }
+
+    /// The tree containing the values contained in this or the other tree.
+    pub fn union(&self, other:&Self) -> Self {
+        Self::from_sorted(union_sorted(self,other))
+    }
+
+    /// The tree containing the values contained in both this and the other tree.
+    pub fn intersection(&self, other:&Self) -> Self {
+        Self::from_sorted(intersection_sorted(self,other))
+    }
+
+    /// The tree containing the values contained in this tree, but not in the other one.
+    pub fn difference(&self, other:&Self) -> Self {
+        Self::from_sorted(difference_sorted(self,other))
+    }
+
+    /// Build a tree from disjoint, non-adjacent intervals in ascending order.
+    fn from_sorted(intervals:impl IntoIterator<Item=Interval>) -> Self {
+        let mut tree = Self::new();
+        for interval in intervals {
+            tree.insert_interval(interval);
+        }
+        tree
+    }
+
+    /// Collect the stored intervals overlapping the provided one, in ascending order.
+    fn intervals_overlapping(&self, interval:Interval) -> Vec<Interval> {
+        let mut out = vec![];
+        self.collect_overlapping(interval,&mut out);
+        out
+    }
+
+    /// Internal helper for the `intervals_overlapping` function.
+    fn collect_overlapping(&self, interval:Interval, out:&mut Vec<Interval>) {
+        for i in 0..=self.data_count {
+            if let Some(children) = &self.children {
+                let after_prev  = i == 0 || self.data[i-1].end < interval.end;
+                let before_next = i == self.data_count || self.data[i].start > interval.start;
+                if after_prev && before_next {
+                    children[i].collect_overlapping(interval,out);
+                }
+            }
+            if i < self.data_count {
+                let current = self.data[i];
+                if current.end >= interval.start && current.start <= interval.end {
+                    out.push(current);
+                }
+            }
+        }
+    }
+
+    /// Get a mutable reference to the stored interval containing the value.
+    fn interval_mut(&mut self, t:usize) -> Option<&mut Interval> {
+        match self.search_data(t) {
+            Ok(pos)  => Some(&mut self.data[pos]),
+            Err(pos) => self.children.as_mut().and_then(|children| children[pos].interval_mut(t))
+        }
+    }
+
+    /// Replace the stored interval containing the value. The new interval has to be disjoint with
+    /// and not adjacent to all the other stored intervals.
+    fn replace_interval(&mut self, t:usize, interval:Interval) {
+        if let Some(stored) = self.interval_mut(t) {
+            *stored = interval;
+        }
+    }
+
+    /// Insert an interval disjoint with and not adjacent to all the stored intervals.
+    fn insert_interval(&mut self, interval:Interval) {
+        if let Some((median,left,right)) = self.insert_internal(interval) {
             let mut new_root = $name::default();
             new_root.data_count   = 1;
             new_root.data[0]      = median;
@@ -255,133 +484,223 @@ impl $name {
         }
     }
 
-    /// Internal helper for the `insert` function.
-    fn insert_internal(&mut self, t:usize) -> Option<(Interval,$name,$name)> {
+    /// Internal helper for the `insert_interval` function.
+    fn insert_internal(&mut self, interval:Interval) -> Option<(Interval,$name,$name)> {
+        let (Ok(pos) | Err(pos)) = self.search_data(interval.start);
+        match &mut self.children {
+            None => {
+                if self.data_count < DATA_SIZE {
+                    // Insert Case (1)
+                    self.data[pos..].rotate_right(1);
+                    self.data[pos] = interval;
+                    self.data_count += 1;
+                    None
+                } else {
+                    let median_ix = DATA_SIZE / 2;
+                    let (median,(left,right)) = if pos == median_ix {
+                        // Insert Case (2)
+                        (interval,self.split_leaf(median_ix,median_ix))
+                    } else if pos < median_ix {
+                        // Insert Case (3)
+                        let (mut left,right) = self.split_leaf(median_ix-1, median_ix);
+                        left.insert_internal(interval);
+                        (self.data[median_ix-1],(left,right))
+                    } else {
+                        // Insert Case (4)
+                        let (left, mut right) = self.split_leaf(median_ix, median_ix+1);
+                        right.insert_internal(interval);
+                        (self.data[median_ix],(left,right))
+                    };
+                    Some((median,left,right))
+                }
+            }
+            Some(children) => {
+                if let Some((median,left,right)) = children[pos].insert_internal(interval) {
+                    if self.data_count < DATA_SIZE {
+                        // Insert Case (1-4)
+                        self.data[pos..].rotate_right(1);
+                        children[pos..].rotate_right(1);
+                        self.data[pos] = median;
+                        children[pos] = left;
+                        children[pos+1] = right;
+                        self.data_count += 1;
+                        None
+                    } else {
+                        // NOTE: Stack-overflow causing branch. Read docs of the module to
+                        //       learn more.
+
+                        let median_ix = DATA_SIZE / 2;
+                        let data      = &mut self.data;
+
+                        if pos == median_ix {
+                            // Insert Case (5)
+
+                            let mut split = |l,r| Self::split(data,children,l,r);
+                            let (mut p_left, mut p_right) = split(median_ix,median_ix);
+
+                            let left_children        = p_left.children.as_mut().unwrap();
+                            let right_children       = p_right.children.as_mut().unwrap();
+                            left_children[median_ix] = left;
+                            right_children[0]        = right;
+
+                            Some((median,p_left,p_right))
+
+                        } else if pos < median_ix {
+                            // Insert Case (6)
+
+                            let left_split_ix  = median_ix - 1;
+                            let right_split_ix = median_ix;
+                            let mut split      = |l,r| Self::split(data,children,l,r);
+                            let (mut p_left,p_right) = split(left_split_ix,right_split_ix);
+
+                            let branch_median_ix = pos;
+                            let left_children    = p_left.children.as_mut().unwrap();
+                            left_children[branch_median_ix..].rotate_right(1);
+                            left_children[branch_median_ix]   = left;
+                            left_children[branch_median_ix+1] = right;
+                            p_left.data[branch_median_ix..].rotate_right(1);
+                            p_left.data[branch_median_ix] = median;
+                            p_left.data_count += 1;
+
+                            Some((self.data[left_split_ix],p_left,p_right))
+
+                        } else {
+                            // Insert Case (7)
+
+                            let left_split_ix  = median_ix;
+                            let right_split_ix = median_ix + 1;
+                            let mut split      = |l,r| Self::split(data,children,l,r);
+                            let (p_left,mut p_right) = split(left_split_ix,right_split_ix);
+
+                            let branch_median_ix = pos-right_split_ix;
+                            let right_children   = p_right.children.as_mut().unwrap();
+                            right_children[branch_median_ix..].rotate_right(1);
+                            right_children[branch_median_ix]   = left;
+                            right_children[branch_median_ix+1] = right;
+                            p_right.data[branch_median_ix..].rotate_right(1);
+                            p_right.data[branch_median_ix] = median;
+                            p_right.data_count += 1;
+
+                            Some((self.data[left_split_ix],p_left,p_right))
+                        }
+                    }
+                } else { None }
+            }
+        }
+    }
+
+    /// Remove the stored interval containing the value.
+    fn remove_interval(&mut self, t:usize) {
+        self.remove_internal(t);
+        if self.data_count == 0 {
+            if let Some(children) = &mut self.children {
+                let child = mem::take(&mut children[0]);
+                *self = child;
+            }
+        }
+    }
+
+    /// Internal helper for the `remove_interval` function. Returns the removed interval.
+    fn remove_internal(&mut self, t:usize) -> Option<Interval> {
         match self.search_data(t) {
-            Err(pos) => {
+            Ok(pos) => {
+                let removed = self.data[pos];
                 match &mut self.children {
                     None => {
-                        if self.data_count < DATA_SIZE {
-                            // Insert Case (1)
-                            self.data[pos..].rotate_right(1);
-                            self.data[pos] = Interval(t,t);
-                            self.data_count += 1;
-                            None
-                        } else {
-                            let median_ix = DATA_SIZE / 2;
-                            let (median,(left,right)) = if pos == median_ix {
-                                // Insert Case (2)
-                                (Interval(t,t),self.split_leaf(median_ix,median_ix))
-                            } else if pos < median_ix {
-                                // Insert Case (3)
-                                let (mut left,right) = self.split_leaf(median_ix-1, median_ix);
-                                left.insert_internal(t);
-                                (self.data[median_ix-1],(left,right))
-                            } else {
-                                // Insert Case (4)
-                                let (left, mut right) = self.split_leaf(median_ix, median_ix+1);
-                                right.insert_internal(t);
-                                (self.data[median_ix],(left,right))
-                            };
-                            Some((median,left,right))
-                        }
+                        self.data[pos..self.data_count].rotate_left(1);
+                        self.data_count -= 1;
                     }
                     Some(children) => {
-                        if let Some((median,left,right)) = children[pos].insert_internal(t) {
-                            if self.data_count < DATA_SIZE {
-                                // Insert Case (1-4)
-                                self.data[pos..].rotate_right(1);
-                                children[pos..].rotate_right(1);
-                                self.data[pos] = median;
-                                children[pos] = left;
-                                children[pos+1] = right;
-                                self.data_count += 1;
-                                None
-                            } else {
-                                // NOTE: Stack-overflow causing branch. Read docs of the module to
-                                //       learn more.
-
-                                let median_ix = DATA_SIZE / 2;
-                                let data      = &mut self.data;
-
-                                if pos == median_ix {
-                                    // Insert Case (5)
-
-                                    let mut split = |l,r| Self::split(data,children,l,r);
-                                    let (mut p_left, mut p_right) = split(median_ix,median_ix);
-
-                                    let left_children        = p_left.children.as_mut().unwrap();
-                                    let right_children       = p_right.children.as_mut().unwrap();
-                                    left_children[median_ix] = left;
-                                    right_children[0]        = right;
-
-                                    Some((median,p_left,p_right))
-
-                                } else if pos < median_ix {
-                                    // Insert Case (6)
-
-                                    let left_split_ix  = median_ix - 1;
-                                    let right_split_ix = median_ix;
-                                    let mut split      = |l,r| Self::split(data,children,l,r);
-                                    let (mut p_left,p_right) = split(left_split_ix,right_split_ix);
-
-                                    let branch_median_ix = pos;
-                                    let left_children    = p_left.children.as_mut().unwrap();
-                                    left_children[branch_median_ix..].rotate_right(1);
-                                    left_children[branch_median_ix]   = left;
-                                    left_children[branch_median_ix+1] = right;
-                                    p_left.data[branch_median_ix..].rotate_right(1);
-                                    p_left.data[branch_median_ix] = median;
-                                    p_left.data_count += 1;
-
-                                    Some((self.data[left_split_ix],p_left,p_right))
-
-                                } else {
-                                    // Insert Case (7)
-
-                                    let left_split_ix  = median_ix;
-                                    let right_split_ix = median_ix + 1;
-                                    let mut split      = |l,r| Self::split(data,children,l,r);
-                                    let (p_left,mut p_right) = split(left_split_ix,right_split_ix);
-
-                                    let branch_median_ix = pos-right_split_ix;
-                                    let right_children   = p_right.children.as_mut().unwrap();
-                                    right_children[branch_median_ix..].rotate_right(1);
-                                    right_children[branch_median_ix]   = left;
-                                    right_children[branch_median_ix+1] = right;
-                                    p_right.data[branch_median_ix..].rotate_right(1);
-                                    p_right.data[branch_median_ix] = median;
-                                    p_right.data_count += 1;
-
-                                    Some((self.data[left_split_ix],p_left,p_right))
-                                }
-                            }
-                        } else { None }
-                    },
+                        // The removed interval is replaced with its predecessor, the last interval
+                        // of the left subtree.
+                        self.data[pos] = children[pos].remove_last();
+                        self.rebalance_child(pos);
+                    }
                 }
-            },
-
-            Ok(pos) => {
-                let interval = &mut self.data[pos];
-                if t < interval.start {
-                    interval.start = t;
+                Some(removed)
+            }
+            Err(pos) => {
+                let removed = self.children.as_mut()?[pos].remove_internal(t);
+                if removed.is_some() {
+                    self.rebalance_child(pos);
                 }
-                else if t > interval.end {
-                    interval.end = t;
-                    let next_pos = pos + 1;
-                    if next_pos < self.data_count {
-                        let next_interval = self.data[next_pos];
-                        if next_interval.start == t + 1 {
-                            // Merging intervals.
-                            let interval = &mut self.data[pos];
-                            interval.end = next_interval.end;
-                            self.data[next_pos..].rotate_left(1);
-                            self.data_count -= 1;
-                        }
-                    }
+                removed
+            }
+        }
+    }
+
+    /// Remove the last interval of this tree, assuming that it is not empty.
+    fn remove_last(&mut self) -> Interval {
+        match &mut self.children {
+            None => {
+                self.data_count -= 1;
+                self.data[self.data_count]
+            }
+            Some(children) => {
+                let last = children[self.data_count].remove_last();
+                self.rebalance_child(self.data_count);
+                last
+            }
+        }
+    }
+
+    /// Restore the minimal size of the child after an interval was removed from it. The child
+    /// borrows an interval from its sibling through this node, or is merged with its sibling if
+    /// both of them are too small.
+    fn rebalance_child(&mut self, pos:usize) {
+        let count    = self.data_count;
+        let data     = &mut self.data;
+        let children = match &mut self.children {
+            Some(children) => children,
+            None           => return,
+        };
+        if children[pos].data_count >= MIN_DATA_COUNT { return }
+        if pos > 0 && children[pos-1].data_count > MIN_DATA_COUNT {
+            let (left,right) = children.split_at_mut(pos);
+            let left         = &mut left[pos-1];
+            let child        = &mut right[0];
+            left.data_count -= 1;
+            child.data[..=child.data_count].rotate_right(1);
+            child.data[0] = mem::replace(&mut data[pos-1],left.data[left.data_count]);
+            if let (Some(left_children),Some(child_children)) =
+                (&mut left.children,&mut child.children) {
+                child_children[..=child.data_count+1].rotate_right(1);
+                child_children[0] = mem::take(&mut left_children[left.data_count+1]);
+            }
+            child.data_count += 1;
+        } else if pos < count && children[pos+1].data_count > MIN_DATA_COUNT {
+            let (left,right) = children.split_at_mut(pos+1);
+            let child        = &mut left[pos];
+            let right        = &mut right[0];
+            child.data[child.data_count] = mem::replace(&mut data[pos],right.data[0]);
+            right.data[..right.data_count].rotate_left(1);
+            if let (Some(child_children),Some(right_children)) =
+                (&mut child.children,&mut right.children) {
+                child_children[child.data_count+1] = mem::take(&mut right_children[0]);
+                right_children[..=right.data_count].rotate_left(1);
+            }
+            right.data_count -= 1;
+            child.data_count += 1;
+        } else {
+            let ix           = if pos > 0 { pos - 1 } else { pos };
+            let (left,right) = children.split_at_mut(ix+1);
+            let left         = &mut left[ix];
+            let right        = &mut right[0];
+            let offset       = left.data_count + 1;
+            left.data[left.data_count] = data[ix];
+            let moved        = &right.data[..right.data_count];
+            left.data[offset..offset+right.data_count].copy_from_slice(moved);
+            if let (Some(left_children),Some(right_children)) =
+                (&mut left.children,&mut right.children) {
+                for i in 0..=right.data_count {
+                    left_children[offset+i] = mem::take(&mut right_children[i]);
                 }
-                None
             }
+            left.data_count += 1 + right.data_count;
+            data[ix..count].rotate_left(1);
+            children[ix+1..=count].rotate_left(1);
+            children[count] = default();
+            self.data_count -= 1;
         }
     }
 
@@ -403,6 +722,14 @@ impl $name {
     }
 }
 
+impl<'a> IntoIterator for &'a $name {
+    type Item     = Interval;
+    type IntoIter = Iter<'a>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
 impl Default for $name {
     fn default() -> Self {
         Self::new()
@@ -454,6 +781,55 @@ impl Debug for $name {
     }
 }
 
+
+
+// === Iter ===
+
+/// Iterator over the intervals of the tree in ascending order.
+#[derive(Clone,Debug)]
+pub struct Iter<'a> {
+    /// The path to the currently visited node, with the index of the next interval in each node.
+    stack : Vec<(&'a $name,usize)>,
+}
+
+impl<'a> Iter<'a> {
+    fn new(tree:&'a $name) -> Self {
+        let mut iter = Self {stack:default()};
+        iter.descend(tree);
+        iter
+    }
+
+    /// Push the path to the leftmost leaf of the subtree to the stack.
+    fn descend(&mut self, mut node:&'a $name) {
+        loop {
+            self.stack.push((node,0));
+            match &node.children {
+                Some(children) => node = &children[0],
+                None           => break,
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = Interval;
+    fn next(&mut self) -> Option<Interval> {
+        loop {
+            let (node,ix) = self.stack.last_mut()?;
+            let node      = *node;
+            let i         = *ix;
+            if i < node.data_count {
+                *ix += 1;
+                if let Some(children) = &node.children {
+                    self.descend(&children[i+1]);
+                }
+                return Some(node.data[i])
+            }
+            self.stack.pop();
+        }
+    }
+}
+
 })*};}
 
 define_trees! {
@@ -465,6 +841,9 @@ define_trees! {
     tree64::Tree64(64)
 }
 
+/// The default DIET implementation, which performs best in the benchmarks of this module.
+pub type Diet = Tree16;
+
 
 
 // =============
@@ -475,6 +854,11 @@ define_trees! {
 mod tests {
     use super::*;
 
+    use rand::Rng;
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+    use std::collections::BTreeSet;
+
 
     // === Tree4 Testing Utilities ===
 
@@ -913,6 +1297,162 @@ mod tests {
             )
         )
     }
+    #[test]
+    fn insertion_merges_layers() {
+        let mut v = t!(t!(10), 12, t!(14));
+        v.insert(11);
+        check(&v, &[(10, 12), (14, 14)]);
+        v.insert(13);
+        check(&v, &[(10, 14)]);
+        v.insert_range(8..=20);
+        check(&v, &[(8, 20)]);
+    }
+
+    #[test]
+    fn removal() {
+        let mut v = Tree4::default();
+        v.insert_range(0..=20);
+        v.remove(5);
+        check(&v, &[(0, 4), (6, 20)]);
+        v.remove(0);
+        check(&v, &[(1, 4), (6, 20)]);
+        v.remove_range(3..=10);
+        check(&v, &[(1, 2), (11, 20)]);
+        v.remove_range(0..=30);
+        check(&v, &[]);
+        assert!(v.is_empty());
+    }
+
+    #[test]
+    fn deep_removal() {
+        let mut v = Tree4::default();
+        for i in 0..100 {
+            v.insert(i * 2);
+        }
+        for i in 0..100 {
+            v.remove(i * 2);
+            assert_eq!(v.iter().count(), 99 - i);
+        }
+        assert_eq!(v, Tree4::default());
+    }
+
+    #[test]
+    fn contains() {
+        let mut v = t!(t!(10, 12), 20, t!(30));
+        v.insert_range(12..=14);
+        assert!(v.contains(10));
+        assert!(v.contains(13));
+        assert!(v.contains(20));
+        assert!(v.contains(30));
+        assert!(!v.contains(11));
+        assert!(!v.contains(15));
+        assert!(!v.contains(31));
+    }
+
+    #[test]
+    fn gaps() {
+        let mut v = t!(t!(10, 12), 20, t!(30));
+        v.insert_range(12..=14);
+        assert_eq!(v.gaps(0..=40), intervals(&[(0, 9), (11, 11), (15, 19), (21, 29), (31, 40)]));
+        assert_eq!(v.gaps(13..=25), intervals(&[(15, 19), (21, 25)]));
+        assert_eq!(v.gaps(12..=14), intervals(&[]));
+        assert_eq!(Tree4::default().gaps(1..=2), intervals(&[(1, 2)]));
+    }
+
+    #[test]
+    fn set_algebra() {
+        let mut a = Tree4::default();
+        a.insert_range(0..=10);
+        a.insert_range(20..=30);
+        let mut b = Tree4::default();
+        b.insert_range(5..=25);
+        b.insert(40);
+        check(&a.union(&b), &[(0, 30), (40, 40)]);
+        check(&a.intersection(&b), &[(5, 10), (20, 25)]);
+        check(&a.difference(&b), &[(0, 4), (26, 30)]);
+        check(&b.difference(&a), &[(11, 19), (40, 40)]);
+    }
+
+
+    // === Model Testing ===
+
+    const MODEL_MAX: usize = 200;
+
+    fn model_intervals(model: &BTreeSet<usize>) -> Vec<Interval> {
+        let mut out: Vec<Interval> = vec![];
+        for &t in model {
+            match out.last_mut() {
+                Some(last) if last.end + 1 == t => last.end = t,
+                _ => out.push(Interval(t, t)),
+            }
+        }
+        out
+    }
+
+    fn random_interval(rng: &mut ChaCha8Rng) -> Interval {
+        let start = rng.gen_range(0..MODEL_MAX);
+        let length =
+            if rng.gen_bool(0.1) { rng.gen_range(0..MODEL_MAX / 2) } else { rng.gen_range(0..4) };
+        Interval(start, (start + length).min(MODEL_MAX))
+    }
+
+    /// Compare the trees against a [`BTreeSet`] model, performing random operations on both.
+    macro_rules! model_test {
+        ($name:ident, $tree:ty) => {
+            #[test]
+            fn $name() {
+                let mut rng = ChaCha8Rng::seed_from_u64(0);
+                for _ in 0..50 {
+                    let mut trees = [<$tree>::default(), <$tree>::default()];
+                    let mut models = [BTreeSet::new(), BTreeSet::new()];
+                    for _ in 0..200 {
+                        let ix = rng.gen_range(0..2);
+                        let (tree, model) = (&mut trees[ix], &mut models[ix]);
+                        let interval = random_interval(&mut rng);
+                        let values = interval.start..=interval.end;
+                        match rng.gen_range(0..4) {
+                            0 => {
+                                tree.insert(interval.start);
+                                model.insert(interval.start);
+                            }
+                            1 => {
+                                tree.remove(interval.start);
+                                model.remove(&interval.start);
+                            }
+                            2 => {
+                                tree.insert_range(interval);
+                                model.extend(values);
+                            }
+                            _ => {
+                                tree.remove_range(interval);
+                                model.retain(|t| !values.contains(t));
+                            }
+                        }
+                        assert_eq!(tree.to_vec(), model_intervals(model));
+                        assert_eq!(tree.iter().collect_vec(), tree.to_vec());
+                    }
+                    let [a, b] = &trees;
+                    let [model_a, model_b] = &models;
+                    for t in 0..=MODEL_MAX {
+                        assert_eq!(a.contains(t), model_a.contains(&t));
+                    }
+                    let union = model_a.union(model_b).copied().collect();
+                    let intersection = model_a.intersection(model_b).copied().collect();
+                    let difference = model_a.difference(model_b).copied().collect();
+                    assert_eq!(a.union(b).to_vec(), model_intervals(&union));
+                    assert_eq!(a.intersection(b).to_vec(), model_intervals(&intersection));
+                    assert_eq!(a.difference(b).to_vec(), model_intervals(&difference));
+                    let bounds = random_interval(&mut rng);
+                    let gaps = (bounds.start..=bounds.end).filter(|t| !model_a.contains(t));
+                    assert_eq!(a.gaps(bounds), model_intervals(&gaps.collect()));
+                }
+            }
+        };
+    }
+
+    model_test!(tree2_matches_model, Tree2);
+    model_test!(tree4_matches_model, Tree4);
+    model_test!(tree16_matches_model, Tree16);
 }
 
 
@@ -9,6 +9,8 @@ use crate::frp::io::mouse::Mouse;
 
 use super::command;
 use enso_shortcuts as shortcuts;
+use serde::Deserialize;
+use std::collections::BTreeSet;
 
 
 // ==============
@@ -126,8 +128,57 @@ impl Condition {
             })
         }
     }
+
+    /// Evaluate the condition. The `is_set` function returns the value of the provided variable.
+    pub fn eval(&self, is_set: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Self::Always => true,
+            Self::Never => false,
+            Self::When(name) => is_set(name),
+            Self::Not(a) => !a.eval(is_set),
+            Self::Or(a, b) => a.eval(is_set) || b.eval(is_set),
+            Self::And(a, b) => a.eval(is_set) && b.eval(is_set),
+        }
+    }
+
+    /// Check whether both conditions can be true at the same time. The check enumerates all the
+    /// values of the used variables. Conditions using more than [`MAX_OVERLAP_CHECK_VARIABLES`]
+    /// variables together are assumed to overlap.
+    pub fn overlaps(&self, other: &Self) -> bool {
+        let mut variables = BTreeSet::new();
+        self.collect_variables(&mut variables);
+        other.collect_variables(&mut variables);
+        let variables = variables.into_iter().collect_vec();
+        if variables.len() > MAX_OVERLAP_CHECK_VARIABLES {
+            return true;
+        }
+        (0..1_u32 << variables.len()).any(|values| {
+            let is_set = |name: &str| {
+                let ix = variables.iter().position(|variable| *variable == name);
+                ix.map_or(false, |ix| values & (1 << ix) != 0)
+            };
+            self.eval(&is_set) && other.eval(&is_set)
+        })
+    }
+
+    fn collect_variables<'a>(&'a self, out: &mut BTreeSet<&'a str>) {
+        match self {
+            Self::Always | Self::Never => {}
+            Self::When(name) => {
+                out.insert(name);
+            }
+            Self::Not(a) => a.collect_variables(out),
+            Self::Or(a, b) | Self::And(a, b) => {
+                a.collect_variables(out);
+                b.collect_variables(out);
+            }
+        }
+    }
 }
 
+/// The maximum number of variables for which [`Condition::overlaps`] checks all their values.
+pub const MAX_OVERLAP_CHECK_VARIABLES: usize = 16;
+
 impl From<&str> for Condition {
     fn from(s: &str) -> Self {
         Self::parse(s)
@@ -220,6 +271,9 @@ impl Shortcut {
 /// dropped, the shortcut will be lazily removed. This is useful when defining shortcuts by GUI
 /// components. When a component is unloaded, all its default shortcuts should be removed as well.
 ///
+/// The default shortcuts can be overridden by the user [`Keymap`]. See
+/// [`RegistryModel::set_keymap`] to learn more.
+///
 /// ## Implementation Notes
 /// There should be a layer for user shortcuts which will remember handles permanently until a
 /// shortcut is unregistered.
@@ -237,6 +291,7 @@ pub struct RegistryModel {
     mouse:              Mouse,
     command_registry:   command::Registry,
     shortcuts_registry: shortcuts::HashSetRegistry<Shortcut>,
+    sources:            Rc<RefCell<Sources>>,
 }
 
 impl Deref for Registry {
@@ -282,14 +337,46 @@ impl RegistryModel {
         let mouse = mouse.clone_ref();
         let command_registry = command_registry.clone_ref();
         let shortcuts_registry = default();
-        Self { logger, keyboard, mouse, command_registry, shortcuts_registry }
+        let sources = default();
+        Self { logger, keyboard, mouse, command_registry, shortcuts_registry, sources }
+    }
+
+    /// Load the user keymap. Its bindings override the default shortcuts of the same targets and
+    /// commands, including the ones added later. The bindings of the previously loaded keymap are
+    /// disabled, unless they are default shortcuts as well. Returns the conflicts between the
+    /// enabled shortcuts, which are also reported as warnings.
+    pub fn set_keymap(&self, keymap: &Keymap) -> Vec<Conflict> {
+        let new_bindings = self.sources.borrow_mut().set_keymap(keymap.shortcuts());
+        for shortcut in new_bindings {
+            self.shortcuts_registry.add(shortcut.rule.tp, &shortcut.rule.pattern, shortcut.clone());
+        }
+        let conflicts = self.conflicts();
+        for conflict in &conflicts {
+            warning!(&self.logger, "{conflict}");
+        }
+        conflicts
+    }
+
+    /// The conflicts between the enabled shortcuts. See [`find_conflicts`] to learn more.
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let sources = self.sources.borrow();
+        find_conflicts(&sources.enabled(&sources.registered))
+    }
+
+    /// Add a default shortcut of a component. It is not added to the shortcuts registry if an equal
+    /// keymap binding was already added, so that its command is not emitted twice.
+    fn add_default_shortcut(&self, shortcut: Shortcut) {
+        if self.sources.borrow_mut().add_default(&shortcut) {
+            self.shortcuts_registry.add(shortcut.rule.tp, &shortcut.rule.pattern, shortcut.clone());
+        }
     }
 
     fn process_rules(&self, rules: &[Shortcut]) {
         let mut targets = Vec::new();
         {
             let borrowed_command_map = self.command_registry.name_map.borrow();
-            for rule in rules {
+            let rules = self.sources.borrow().enabled(rules);
+            for rule in &rules {
                 let target = &rule.action.target;
                 borrowed_command_map.get(target).for_each(|instances| {
                     for instance in instances {
@@ -319,25 +406,363 @@ impl RegistryModel {
         condition: &Condition,
         status: &Rc<RefCell<HashMap<String, frp::Sampler<bool>>>>,
     ) -> bool {
-        use Condition::*;
-        match condition {
-            Always => true,
-            Never => false,
-            When(name) => status.borrow().get(name).map(|t| t.value()).unwrap_or(false),
-            Not(a) => !Self::condition_checker(a, status),
-            Or(a, b) => Self::condition_checker(a, status) || Self::condition_checker(b, status),
-            And(a, b) => Self::condition_checker(a, status) && Self::condition_checker(b, status),
-        }
+        let status = status.borrow();
+        condition.eval(&|name| status.get(name).map(|t| t.value()).unwrap_or(false))
     }
 }
 
 impl Add<Shortcut> for &Registry {
     type Output = ();
     fn add(self, shortcut: Shortcut) {
-        self.model.shortcuts_registry.add(
-            shortcut.rule.tp,
-            &shortcut.rule.pattern,
-            shortcut.clone(),
-        );
+        self.model.add_default_shortcut(shortcut);
+    }
+}
+
+
+
+// ==============
+// === Keymap ===
+// ==============
+
+/// A binding of a user keymap. See [`Keymap`] to learn more.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+pub struct Binding {
+    /// The keys pattern, like "ctrl shift s", or a key sequence, like "ctrl k, ctrl s".
+    pub pattern: String,
+    /// The action type, like `press` or `double_press`. Defaults to `press`.
+    #[serde(default = "default_action_type")]
+    pub action:  ActionType,
+    /// The target identifier, like "TextEditor".
+    pub target:  String,
+    /// The command evaluated on the target.
+    pub command: String,
+    /// The condition expression, like "focused & !read_only". Defaults to always true.
+    #[serde(default)]
+    pub when:    String,
+}
+
+fn default_action_type() -> ActionType {
+    ActionType::Press
+}
+
+impl Binding {
+    /// The shortcut defined by this binding.
+    pub fn shortcut(&self) -> Shortcut {
+        let rule = Rule::new(self.action, &self.pattern);
+        Shortcut::new_when(rule, &self.target, self.command.as_str(), self.when.as_str())
+    }
+}
+
+/// A user keymap, a set of bindings overriding the default shortcuts of the components. All the
+/// default shortcuts of a target and a command are disabled if the keymap contains a binding for
+/// them. The keymap can be loaded from JSON or YAML, like:
+/// ```yaml
+/// bindings:
+///   - pattern: ctrl k, ctrl s
+///     target: TextEditor
+///     command: save
+///     when: focused
+/// ```
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub struct Keymap {
+    pub bindings: Vec<Binding>,
+}
+
+impl Keymap {
+    /// Parse the keymap from JSON.
+    pub fn from_json(source: &str) -> Result<Self, KeymapError> {
+        serde_json::from_str(source).map_err(|error| KeymapError::Json(error.to_string()))
+    }
+
+    /// Parse the keymap from YAML.
+    pub fn from_yaml(source: &str) -> Result<Self, KeymapError> {
+        serde_yaml::from_str(source).map_err(|error| KeymapError::Yaml(error.to_string()))
+    }
+
+    /// The shortcuts defined by the keymap.
+    pub fn shortcuts(&self) -> Vec<Shortcut> {
+        self.bindings.iter().map(|binding| binding.shortcut()).collect()
+    }
+}
+
+
+// === Sources ===
+
+/// The sources of the shortcuts added to the [`Registry`]: the default shortcuts of the components
+/// and the bindings of the keymaps. A shortcut added by both sources is registered only once.
+#[derive(Debug, Default)]
+struct Sources {
+    /// The shortcuts added to the shortcuts registry, in the order of adding. They can not be
+    /// removed from it.
+    registered:      Vec<Shortcut>,
+    /// The default shortcuts of the components.
+    defaults:        HashSet<Shortcut>,
+    /// The bindings of all keymaps loaded so far, which were registered as keymap bindings,
+    /// because they were not default shortcuts at the time of loading.
+    keymap_bindings: HashSet<Shortcut>,
+    /// The bindings of the current keymap.
+    active:          HashSet<Shortcut>,
+    /// The targets and commands of the current keymap bindings.
+    overridden:      HashSet<(String, Command)>,
+}
+
+impl Sources {
+    /// Add the default shortcut. Returns whether it needs to be added to the shortcuts registry.
+    fn add_default(&mut self, shortcut: &Shortcut) -> bool {
+        self.defaults.insert(shortcut.clone());
+        let is_registered = self.keymap_bindings.contains(shortcut);
+        if !is_registered {
+            self.registered.push(shortcut.clone());
+        }
+        !is_registered
+    }
+
+    /// Replace the current keymap bindings. Returns the bindings which need to be added to the
+    /// shortcuts registry.
+    fn set_keymap(&mut self, bindings: Vec<Shortcut>) -> Vec<Shortcut> {
+        self.overridden = bindings.iter().map(|t| (t.target.clone(), t.command.clone())).collect();
+        self.active = bindings.iter().cloned().collect();
+        let mut new_bindings = vec![];
+        for shortcut in bindings {
+            let is_new =
+                !self.defaults.contains(&shortcut) && self.keymap_bindings.insert(shortcut.clone());
+            if is_new {
+                self.registered.push(shortcut.clone());
+                new_bindings.push(shortcut);
+            }
+        }
+        new_bindings
+    }
+
+    /// Check whether the registered shortcut is enabled. The bindings of the current keymap are
+    /// always enabled. The default shortcuts are enabled unless the current keymap binds their
+    /// targets and commands. The other bindings come from the previous keymaps, and are disabled.
+    fn is_enabled(&self, shortcut: &Shortcut) -> bool {
+        if self.active.contains(shortcut) {
+            true
+        } else if self.defaults.contains(shortcut) {
+            !self.overridden.contains(&(shortcut.target.clone(), shortcut.command.clone()))
+        } else {
+            false
+        }
+    }
+
+    /// The enabled shortcuts of the provided ones.
+    fn enabled(&self, shortcuts: &[Shortcut]) -> Vec<Shortcut> {
+        shortcuts.iter().filter(|shortcut| self.is_enabled(shortcut)).cloned().collect()
+    }
+}
+
+
+// === Errors ===
+
+/// [`Keymap`] parsing error.
+#[derive(Clone, Debug, Fail)]
+#[allow(missing_docs)]
+pub enum KeymapError {
+    Json(String),
+    Yaml(String),
+}
+
+impl Display for KeymapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "Failed to parse the JSON keymap: {error}"),
+            Self::Yaml(error) => write!(f, "Failed to parse the YAML keymap: {error}"),
+        }
+    }
+}
+
+
+
+// =================
+// === Conflicts ===
+// =================
+
+/// Two shortcuts with the same rule, triggering different commands when their conditions are true
+/// at the same time.
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[allow(missing_docs)]
+pub struct Conflict {
+    pub first:  Shortcut,
+    pub second: Shortcut,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let pattern = &self.first.rule.pattern;
+        let first = format!("{}::{}", self.first.target, self.first.command.name);
+        let second = format!("{}::{}", self.second.target, self.second.command.name);
+        write!(f, "The shortcut '{pattern}' is bound to both {first} and {second}.")
+    }
+}
+
+/// Find the conflicts between the shortcuts. The patterns of the rules are compared regardless of
+/// the order and case of the keys.
+pub fn find_conflicts(shortcuts: &[Shortcut]) -> Vec<Conflict> {
+    let rules =
+        shortcuts.iter().map(|t| (t.rule.tp, normalized_pattern(&t.rule.pattern))).collect_vec();
+    let mut conflicts = Vec::<Conflict>::new();
+    for (i, first) in shortcuts.iter().enumerate() {
+        for (j, second) in shortcuts.iter().enumerate().skip(i + 1) {
+            let same_command = first.target == second.target && first.command == second.command;
+            let is_conflict = rules[i] == rules[j]
+                && !same_command
+                && first.condition.overlaps(&second.condition);
+            if is_conflict {
+                let conflict = Conflict { first: first.clone(), second: second.clone() };
+                if !conflicts.contains(&conflict) {
+                    conflicts.push(conflict);
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+/// The pattern with the keys of each stroke sorted and lowercase, like "ctrl s, a ctrl".
+fn normalized_pattern(pattern: &str) -> String {
+    let strokes = pattern.split(',').map(|t| t.trim()).filter(|t| !t.is_empty());
+    let strokes =
+        strokes.map(|t| t.split_whitespace().map(|t| t.to_lowercase()).sorted().join(" "));
+    strokes.collect_vec().join(", ")
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keymap_parsing() {
+        let json = r#"{"bindings": [
+            {"pattern": "ctrl k, ctrl s", "target": "TextEditor", "command": "save"},
+            {"pattern": "ctrl a", "action": "double_press", "target": "Editor", "command": "all",
+             "when": "focused & !read_only"}
+        ]}"#;
+        let yaml = "
+bindings:
+  - pattern: ctrl k, ctrl s
+    target: TextEditor
+    command: save
+  - pattern: ctrl a
+    action: double_press
+    target: Editor
+    command: all
+    when: focused & !read_only
+";
+        let keymap = Keymap::from_json(json).unwrap();
+        assert_eq!(Keymap::from_yaml(yaml).unwrap(), keymap);
+        let expected = vec![
+            Shortcut::new(Rule::new(ActionType::Press, "ctrl k, ctrl s"), "TextEditor", "save"),
+            Shortcut::new_when(
+                Rule::new(ActionType::DoublePress, "ctrl a"),
+                "Editor",
+                "all",
+                "focused & !read_only",
+            ),
+        ];
+        assert_eq!(keymap.shortcuts(), expected);
+        assert!(matches!(Keymap::from_json("{"), Err(KeymapError::Json(_))));
+        assert!(matches!(Keymap::from_yaml("bindings: 1"), Err(KeymapError::Yaml(_))));
+    }
+
+    #[test]
+    fn keymap_override_and_reload() {
+        let logger = Logger::new("Test");
+        let command_registry = command::Registry::create(&logger);
+        let model = RegistryModel::new(&logger, &default(), &default(), &command_registry);
+        let shortcut = |pattern: &str, command: &str| {
+            Shortcut::new(Rule::new(ActionType::Press, pattern), "Editor", command)
+        };
+        let binding = |pattern: &str, command: &str| Binding {
+            pattern: pattern.into(),
+            action:  ActionType::Press,
+            target:  "Editor".into(),
+            command: command.into(),
+            when:    default(),
+        };
+        // The enabled shortcuts triggered by pressing the keys.
+        let press = |keys: &[&str]| {
+            let mut rules = vec![];
+            for key in keys {
+                rules = model.shortcuts_registry.on_press(key);
+            }
+            for key in keys.iter().rev() {
+                model.shortcuts_registry.on_release(key);
+            }
+            model.sources.borrow().enabled(&rules)
+        };
+        let save = shortcut("ctrl s", "save");
+        let open = shortcut("ctrl o", "open");
+        let user_open = shortcut("ctrl shift o", "open");
+        let copied_save = shortcut("ctrl k", "save");
+        model.add_default_shortcut(save.clone());
+        model.add_default_shortcut(open.clone());
+        assert_eq!(press(&["ctrl-left", "s"]), vec![save.clone()]);
+        assert_eq!(press(&["ctrl-left", "o"]), vec![open.clone()]);
+
+        // The binding identical to the default shortcut is registered only once.
+        let keymap =
+            Keymap { bindings: vec![binding("ctrl s", "save"), binding("ctrl shift o", "open")] };
+        assert_eq!(model.set_keymap(&keymap), vec![]);
+        assert_eq!(press(&["ctrl-left", "s"]), vec![save.clone()]);
+        assert_eq!(press(&["ctrl-left", "o"]), vec![]);
+        assert_eq!(press(&["ctrl-left", "shift-left", "o"]), vec![user_open.clone()]);
+
+        // The defaults of the targets and commands not bound by the new keymap are enabled again.
+        let keymap = Keymap { bindings: vec![binding("ctrl k", "save")] };
+        model.set_keymap(&keymap);
+        assert_eq!(press(&["ctrl-left", "s"]), vec![]);
+        assert_eq!(press(&["ctrl-left", "k"]), vec![copied_save.clone()]);
+        assert_eq!(press(&["ctrl-left", "o"]), vec![open.clone()]);
+        assert_eq!(press(&["ctrl-left", "shift-left", "o"]), vec![]);
+        model.set_keymap(&default());
+        assert_eq!(press(&["ctrl-left", "s"]), vec![save.clone()]);
+        assert_eq!(press(&["ctrl-left", "k"]), vec![]);
+
+        // The default identical to an already registered binding is not registered again.
+        model.set_keymap(&Keymap { bindings: vec![binding("ctrl shift o", "open")] });
+        model.add_default_shortcut(user_open.clone());
+        assert_eq!(press(&["ctrl-left", "shift-left", "o"]), vec![user_open.clone()]);
+        model.set_keymap(&default());
+        assert_eq!(press(&["ctrl-left", "shift-left", "o"]), vec![user_open]);
+        assert_eq!(press(&["ctrl-left", "o"]), vec![open]);
+        assert_eq!(press(&["ctrl-left", "s"]), vec![save]);
+    }
+
+    #[test]
+    fn condition_overlaps() {
+        let overlaps = |a: &str, b: &str| Condition::from(a).overlaps(&Condition::from(b));
+        assert!(overlaps("", ""));
+        assert!(overlaps("", "a"));
+        assert!(overlaps("a", "b"));
+        assert!(overlaps("a | b", "!a"));
+        assert!(!overlaps("a", "!a"));
+        assert!(!overlaps("a & b", "!b | !a"));
+    }
+
+    #[test]
+    fn conflicts() {
+        let shortcut = |pattern: &str, target: &str, command: &str, when: &str| {
+            Shortcut::new_when(Rule::new(ActionType::Press, pattern), target, command, when)
+        };
+        let save = shortcut("ctrl s", "Editor", "save", "focused");
+        let save_copy = shortcut("s ctrl", "Editor", "save", "focused");
+        let search = shortcut("Ctrl S", "Search", "open", "focused & !read_only");
+        let hidden = shortcut("ctrl s", "Panel", "hide", "!focused");
+        let shortcuts = vec![save.clone(), save_copy.clone(), search.clone(), hidden];
+        let conflicts = find_conflicts(&shortcuts);
+        let expected = vec![Conflict { first: save, second: search.clone() }, Conflict {
+            first:  save_copy,
+            second: search,
+        }];
+        assert_eq!(conflicts, expected);
     }
 }
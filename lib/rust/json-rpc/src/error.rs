@@ -40,6 +40,10 @@ pub enum RpcError<Payload: Debug + Send + Sync + 'static = serde_json::Value> {
     #[allow(missing_docs)]
     #[fail(display = "Response timed out after {} ms.", millis)]
     TimeoutError { millis: u128 },
+
+    /// The request has been cancelled by the client before receiving a reply.
+    #[fail(display = "The request has been cancelled.")]
+    Cancelled,
 }
 
 impl RpcError {
@@ -5,6 +5,7 @@ use crate::prelude::*;
 use crate::api;
 use crate::api::Result;
 use crate::ensogl::sleep;
+use crate::ensogl::time_from_start;
 use crate::ensogl::Duration;
 use crate::error::HandlingError;
 use crate::error::RpcError;
@@ -17,11 +18,15 @@ use futures::channel::mpsc::unbounded;
 use futures::channel::mpsc::UnboundedSender;
 use futures::channel::oneshot;
 use futures::future;
+use futures::future::LocalBoxFuture;
 use futures::FutureExt;
 use futures::Stream;
 use futures::StreamExt;
 use serde::de::DeserializeOwned;
 use std::future::Future;
+use std::pin::Pin;
+use std::task::Context;
+use std::task::Poll;
 
 
 
@@ -92,6 +97,9 @@ impl Default for IdGenerator {
 /// Event emitted by the `Handler<N>`.
 #[derive(Debug)]
 pub enum Event<N> {
+    /// Transport has been opened again, after being closed. The requests sent before closing will
+    /// never be replied.
+    Opened,
     /// Transport has been closed.
     Closed,
     /// Error occurred.
@@ -102,6 +110,214 @@ pub enum Event<N> {
 
 
 
+// ================
+// === Priority ===
+// ================
+
+/// Priority of a request sent in a [`Batch`]. Requests of higher priority are placed earlier in
+/// the batch message, so a peer processing the batch in order handles them first.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[allow(missing_docs)]
+pub enum Priority {
+    Low,
+    Normal,
+    High,
+}
+
+impl Default for Priority {
+    fn default() -> Self {
+        Self::Normal
+    }
+}
+
+
+
+// ====================
+// === CancelHandle ===
+// ====================
+
+/// Cancels ongoing requests. Implemented by the data of handlers of any notification type, so the
+/// [`CancelHandle`] does not depend on it.
+trait CancelRequest {
+    /// Stop waiting for the reply to the request, optionally notifying the peer. Returns `false`
+    /// if the request was not ongoing.
+    fn cancel_request(&self, id: Id, notify_peer: bool) -> bool;
+}
+
+/// A handle allowing to cancel an ongoing request, e.g. when its result is no longer needed.
+///
+/// The future of a cancelled request yields [`RpcError::Cancelled`], and the reply to the request,
+/// if it comes later, is silently dropped.
+#[derive(Clone, Debug)]
+pub struct CancelHandle {
+    id:        Id,
+    handler:   Weak<dyn CancelRequest>,
+    cancelled: Rc<Cell<bool>>,
+}
+
+impl CancelHandle {
+    /// The identifier of the request.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Check if the request has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.get()
+    }
+
+    /// Cancel the request without informing the peer. Returns `false` if the request is no longer
+    /// ongoing, e.g. because its reply has already been received.
+    pub fn cancel(&self) -> bool {
+        self.cancel_with(false)
+    }
+
+    /// Cancel the request and send the peer a `$/cancelRequest` notification, so it may stop
+    /// processing the request. Returns `false` if the request is no longer ongoing.
+    pub fn cancel_and_notify_peer(&self) -> bool {
+        self.cancel_with(true)
+    }
+
+    fn cancel_with(&self, notify_peer: bool) -> bool {
+        let handler = self.handler.upgrade();
+        let cancelled =
+            handler.map_or(false, |handler| handler.cancel_request(self.id, notify_peer));
+        if cancelled {
+            self.cancelled.set(true);
+        }
+        cancelled
+    }
+}
+
+
+
+// =====================
+// === RequestFuture ===
+// =====================
+
+/// A future yielding the decoded reply to a request, returned by the `Handler` when a request is
+/// opened. It can be cancelled with its [`CancelHandle`].
+pub struct RequestFuture<Returned> {
+    cancel_handle: CancelHandle,
+    future:        LocalBoxFuture<'static, Result<Returned>>,
+}
+
+impl<Returned> RequestFuture<Returned> {
+    /// The identifier of the request.
+    pub fn id(&self) -> Id {
+        self.cancel_handle.id
+    }
+
+    /// Get a handle allowing to cancel the request, also after this future has been moved.
+    pub fn cancel_handle(&self) -> CancelHandle {
+        self.cancel_handle.clone()
+    }
+
+    /// Cancel the request without informing the peer. See [`CancelHandle::cancel`].
+    pub fn cancel(&self) -> bool {
+        self.cancel_handle.cancel()
+    }
+}
+
+impl<Returned> Debug for RequestFuture<Returned> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RequestFuture").field("cancel_handle", &self.cancel_handle).finish()
+    }
+}
+
+impl<Returned> Future for RequestFuture<Returned> {
+    type Output = Result<Returned>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.future.poll_unpin(cx)
+    }
+}
+
+
+
+// =============
+// === Batch ===
+// =============
+
+/// A batch of requests sent to the peer in a single message, created by [`Handler::batch`].
+///
+/// The requests are sent when [`Batch::send`] is called or when the batch is dropped. Within the
+/// message they are ordered by their priorities; requests of equal priority keep their order.
+#[derive(Debug)]
+pub struct Batch<Notification: 'static> {
+    handler:  Handler<Notification>,
+    requests: Vec<(Priority, Id, serde_json::Value)>,
+}
+
+impl<Notification: 'static> Batch<Notification> {
+    /// Add a request of the [`Priority::Normal`] priority to the batch.
+    pub fn open_request<In: api::RemoteMethodCall>(
+        &mut self,
+        input: In,
+    ) -> RequestFuture<In::Returned>
+    where
+        In::Returned: 'static,
+    {
+        self.open_request_with_priority(input, default())
+    }
+
+    /// Add a request of the given priority to the batch. The returned future is resolved after the
+    /// batch is sent and the reply is received.
+    pub fn open_request_with_priority<In: api::RemoteMethodCall>(
+        &mut self,
+        input: In,
+        priority: Priority,
+    ) -> RequestFuture<In::Returned>
+    where
+        In::Returned: 'static,
+    {
+        let id = self.handler.generate_new_id();
+        let message = api::into_request_message(input, id);
+        let message = serde_json::to_value(&message).unwrap();
+        self.requests.push((priority, id, message));
+        self.handler.register_request(id)
+    }
+
+    /// The number of requests in the batch.
+    pub fn len(&self) -> usize {
+        self.requests.len()
+    }
+
+    /// Check if the batch contains no requests.
+    pub fn is_empty(&self) -> bool {
+        self.requests.is_empty()
+    }
+
+    /// Send all the requests of the batch to the peer.
+    pub fn send(mut self) {
+        self.flush()
+    }
+
+    fn flush(&mut self) {
+        if self.requests.is_empty() {
+            return;
+        }
+        let mut requests = std::mem::take(&mut self.requests);
+        requests.sort_by_key(|(priority, _, _)| std::cmp::Reverse(*priority));
+        let messages = requests.iter().map(|(_, _, message)| message).collect_vec();
+        let serialized_message = serde_json::to_string(&messages).unwrap();
+        if self.handler.send_text_message(&serialized_message).is_err() {
+            // If message cannot be send, the futures of all its requests must be cancelled.
+            for (_, id, _) in requests {
+                self.handler.remove_ongoing_request(id);
+            }
+        }
+    }
+}
+
+impl<Notification: 'static> Drop for Batch<Notification> {
+    fn drop(&mut self) {
+        self.flush()
+    }
+}
+
+
+
 // ===================
 // === HandlerData ===
 // ===================
@@ -139,6 +355,10 @@ pub struct HandlerData<Notification> {
     timeout         : Duration,
     /// Ongoing calls.
     ongoing_calls   : OngoingCalls,
+    /// Calls cancelled by the client; their replies are dropped when received. The calls are
+    /// forgotten after the timeout, as the peer is not expected to reply to them anymore. Values
+    /// are the times of expiry, in milliseconds since the program start.
+    cancelled_calls : HashMap<Id,f64>,
     /// Handle to send outgoing events.
     outgoing_events : Option<UnboundedSender<Event<Notification>>>,
     /// Provides identifiers for requests.
@@ -167,7 +387,27 @@ impl<Notification> {
     /// Removes all the ongoing requests. This will be recognized by the `Future`s
     /// as losing connection error.
     pub fn clear_ongoing_requests(&mut self) {
-        self.ongoing_calls.clear()
+        self.ongoing_calls.clear();
+        self.cancelled_calls.clear();
+    }
+
+    /// Removes the request from the map of ongoing requests, remembering until the timeout that its
+    /// reply should be ignored. Returns `false` if the request was not ongoing.
+    pub fn cancel_ongoing_request(&mut self, id:Id) -> bool {
+        let cancelled = self.ongoing_calls.remove(&id).is_some();
+        if cancelled {
+            let now = time_from_start();
+            self.cancelled_calls.retain(|_,expiry| *expiry > now);
+            self.cancelled_calls.insert(id,now + self.timeout.as_millis() as f64);
+        }
+        cancelled
+    }
+
+    /// Checks if the request has been cancelled within the timeout, forgetting about it. Should be
+    /// called when the reply to the request is received.
+    pub fn take_cancelled_request(&mut self, id:Id) -> bool {
+        let expiry = self.cancelled_calls.remove(&id);
+        expiry.map_or(false, |expiry| expiry > time_from_start())
     }
 
     /// Obtains an id for a new request to be made.
@@ -210,6 +450,24 @@ impl<Notification> {
 } // shared!
 
 
+impl<Notification> CancelRequest for RefCell<HandlerData<Notification>> {
+    fn cancel_request(&self, id: Id, notify_peer: bool) -> bool {
+        let mut data = self.borrow_mut();
+        let cancelled = data.cancel_ongoing_request(id);
+        if cancelled && notify_peer {
+            let message = messages::Message::new_notification(
+                messages::CancelRequest::METHOD,
+                messages::CancelRequest { id },
+            );
+            let serialized_message = serde_json::to_string(&message).unwrap();
+            // The request is cancelled regardless of whether the peer has been notified.
+            data.send_text_message(&serialized_message).ok();
+        }
+        cancelled
+    }
+}
+
+
 // === Handler methods ===
 
 impl<Notification> Handler<Notification> {
@@ -235,6 +493,7 @@ impl<Notification> Handler<Notification> {
         let data = HandlerData {
             timeout:         crate::constants::TIMEOUT,
             ongoing_calls:   default(),
+            cancelled_calls: default(),
             id_generator:    IdGenerator::new(),
             transport:       Box::new(transport),
             outgoing_events: None,
@@ -247,7 +506,11 @@ impl<Notification> Handler<Notification> {
     pub fn open_request<In: api::RemoteMethodCall>(
         &self,
         input: In,
-    ) -> impl Future<Output = Result<In::Returned>> {
+    ) -> RequestFuture<In::Returned>
+    where
+        In::Returned: 'static,
+        Notification: 'static,
+    {
         let id = self.generate_new_id();
         let message = api::into_request_message(input, id);
         let serialized_message = serde_json::to_string(&message).unwrap();
@@ -265,11 +528,14 @@ impl<Notification> Handler<Notification> {
     /// This is suboptimal but still less evil than cloning all input arguments like before.
     ///
     /// FIXME: when possible unify with `open_request`
-    pub fn open_request_with_json<Returned: DeserializeOwned>(
+    pub fn open_request_with_json<Returned: DeserializeOwned + 'static>(
         &self,
         method_name: &str,
         input: &serde_json::Value,
-    ) -> impl Future<Output = Result<Returned>> {
+    ) -> RequestFuture<Returned>
+    where
+        Notification: 'static,
+    {
         let id = self.generate_new_id();
         let message = crate::messages::Message::new_request(id, method_name, input);
         let serialized_message = serde_json::to_string(&message).unwrap();
@@ -280,28 +546,55 @@ impl<Notification> Handler<Notification> {
     ///
     /// Helper common \code for `open_request` and `open_request_with_json`. See
     /// `open_request_with_json` docstring for more information.
-    pub fn open_request_with_message<Returned: DeserializeOwned>(
+    pub fn open_request_with_message<Returned: DeserializeOwned + 'static>(
         &self,
         id: Id,
         message_json: &str,
-    ) -> impl Future<Output = Result<Returned>> {
+    ) -> RequestFuture<Returned>
+    where
+        Notification: 'static,
+    {
+        let ret = self.register_request(id);
+        if self.send_text_message(message_json).is_err() {
+            // If message cannot be send, future ret must be cancelled.
+            self.remove_ongoing_request(id);
+        }
+        ret
+    }
+
+    /// Creates a batch of requests, which will be sent to the peer in a single message.
+    pub fn batch(&self) -> Batch<Notification>
+    where Notification: 'static {
+        Batch { handler: self.clone(), requests: default() }
+    }
+
+    /// Registers an ongoing request with the given id and returns a `Future` that shall yield the
+    /// reply message. The request message should be sent by the caller.
+    fn register_request<Returned: DeserializeOwned + 'static>(
+        &self,
+        id: Id,
+    ) -> RequestFuture<Returned>
+    where
+        Notification: 'static,
+    {
         let (sender, receiver) = oneshot::channel::<ReplyMessage>();
         let ret = receiver.map(|result_or_cancel| {
             let result = result_or_cancel?;
             decode_result(result)
         });
-
         self.insert_ongoing_request(id, sender);
-        if self.send_text_message(message_json).is_err() {
-            // If message cannot be send, future ret must be cancelled.
-            self.remove_ongoing_request(id);
-        }
 
+        let cancelled = Rc::new(Cell::new(false));
+        let handler = Rc::downgrade(&self.rc) as Weak<dyn CancelRequest>;
+        let cancel_handle = CancelHandle { id, handler, cancelled: cancelled.clone() };
         let millis = self.timeout().as_millis();
-        future::select(ret, sleep(self.timeout()).boxed_local()).map(move |either| match either {
+        let future = future::select(ret, sleep(self.timeout()).boxed_local());
+        let future = future.map(move |either| match either {
+            _ if cancelled.get() => Err(RpcError::Cancelled),
             future::Either::Left((x, _)) => x,
             future::Either::Right((_, _)) => Err(RpcError::TimeoutError { millis }),
-        })
+        });
+        RequestFuture { cancel_handle, future: future.boxed_local() }
     }
 
     /// Deal with `Response` message from the peer.
@@ -312,6 +605,8 @@ impl<Notification> Handler<Notification> {
             // Disregard any error. We do not care if RPC caller already
             // dropped the future.
             sender.send(message.result).ok();
+        } else if self.take_cancelled_request(message.id) {
+            // The caller is no longer interested in the reply.
         } else {
             self.error_occurred(HandlingError::UnexpectedResponse(message));
         }
@@ -338,10 +633,20 @@ impl<Notification> Handler<Notification> {
     /// Deal with incoming text message from the peer.
     ///
     /// The message must conform either to the `Response` or to the
-    /// `Notification` JSON-serialized format. Otherwise, an error is raised.
+    /// `Notification` JSON-serialized format, or be a batch of such messages. Otherwise, an error
+    /// is raised.
     pub fn process_incoming_message(&self, message: String)
     where Notification: DeserializeOwned {
-        match messages::decode_incoming_message(&message) {
+        match messages::decode_incoming_messages(&message) {
+            Ok(messages) => messages.into_iter().for_each(|message| self.process_decoded(message)),
+            Err(err) => self.error_occurred(HandlingError::InvalidMessage(err)),
+        }
+    }
+
+    /// Deal with a single incoming message, partially decoded.
+    fn process_decoded(&self, message: serde_json::Result<messages::IncomingMessage>)
+    where Notification: DeserializeOwned {
+        match message {
             Ok(messages::IncomingMessage::Response(response)) => self.process_response(response),
             Ok(messages::IncomingMessage::Notification(notification)) =>
                 self.process_notification(notification),
@@ -364,7 +669,7 @@ impl<Notification> Handler<Notification> {
             TransportEvent::TextMessage(msg) => self.process_incoming_message(msg),
             TransportEvent::BinaryMessage(data) =>
                 self.error_occurred(HandlingError::UnexpectedBinaryMessage(data)),
-            TransportEvent::Opened => {}
+            TransportEvent::Opened => self.emit_event(Event::Opened),
             TransportEvent::Closed => {
                 // Dropping all ongoing calls will cancel their futures.
                 self.clear_ongoing_requests();
@@ -26,6 +26,7 @@ pub mod handler;
 pub mod log;
 pub mod macros;
 pub mod messages;
+pub mod server;
 pub mod test_util;
 pub mod transport;
 
@@ -38,6 +39,7 @@ pub use enso_web as ensogl;
 pub use error::RpcError;
 pub use handler::Event;
 pub use handler::Handler;
+pub use server::Server;
 pub use transport::Transport;
 pub use transport::TransportEvent;
 
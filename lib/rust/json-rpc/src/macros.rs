@@ -1,4 +1,4 @@
-//! Helper macros to generate RemoteClient and MockClient.
+//! Helper macros to generate RemoteClient, MockClient and the server side of an API.
 
 
 
@@ -22,6 +22,14 @@
 ///     fn expect_call_me_please
 ///     (&mut self, my_number_is:String,result:json_rpc::api::Result<()>) { /* impl */ }
 /// ```
+///
+/// The other side of the API is generated in the `server` module: the `server::API` trait, taking
+/// the arguments by value, and the `server::Dispatcher` routing the requests received by
+/// [`json_rpc::Server`] to its implementation:
+/// ```rust,compile_fail
+///     let server = server::serve(transport, MyFakeServer::default());
+///     executor.spawn_local(server.runner());
+/// ```
 #[macro_export]
 macro_rules! make_rpc_methods {
     (
@@ -139,6 +147,83 @@ macro_rules! make_rpc_methods {
 
 
 
+        // ==============
+        // === Server ===
+        // ==============
+
+        /// The server side of the API, allowing to implement peers of the `Client`.
+        pub mod server {
+            use super::*;
+
+            $(#[doc = $impl_doc])+
+            ///
+            /// This is the server side of the interface. The methods not implemented reply with
+            /// the "method not found" error.
+            pub trait API {
+                $(
+                    $(#[doc = $doc])+
+                    #[allow(unused_variables)]
+                    fn $method(&self $(,$param_name:$param_ty)*)
+                    -> futures::future::LocalBoxFuture<'static,Result<$result>> {
+                        use json_rpc::server::error_code::METHOD_NOT_FOUND;
+                        use json_rpc::RpcError;
+                        let message = concat!("Method not implemented: ", $rpc_name, ".");
+                        let error   = RpcError::new_remote_error(METHOD_NOT_FOUND,message);
+                        Box::pin(futures::future::ready(Err(error)))
+                    }
+                )*
+            }
+
+            /// Routes the requests received by the [`json_rpc::Server`] to the methods of the
+            /// wrapped server `API` implementation.
+            #[derive(Clone,Debug,Default)]
+            pub struct Dispatcher<T> {
+                /// The server `API` implementation.
+                pub api : T,
+            }
+
+            impl<T:API+Debug> json_rpc::server::Dispatch for Dispatcher<T> {
+                fn dispatch(&self, method:&str, params:serde_json::Value)
+                -> Option<futures::future::LocalBoxFuture<'static,json_rpc::server::Reply>> {
+                    use futures::FutureExt;
+                    $(
+                        if method == $rpc_name {
+                            let input = match serde_json::from_value::<$method_input>(params) {
+                                Ok(input) => input,
+                                Err(error) => {
+                                    let error = json_rpc::server::invalid_params(error);
+                                    return Some(futures::future::ready(Err(error)).boxed_local());
+                                }
+                            };
+                            let $method_input { $($param_name),* } = input;
+                            let result = self.api.$method($($param_name),*);
+                            return Some(result.map(json_rpc::server::encode_result).boxed_local());
+                        }
+                    )*
+                    None
+                }
+            }
+
+            /// Create a server replying to the requests received through the given transport with
+            /// the given `API` implementation. Its `runner` should be spawned on an executor.
+            pub fn serve
+            (transport:impl json_rpc::Transport + 'static, api:impl API + Debug + 'static)
+            -> json_rpc::Server {
+                json_rpc::Server::new(transport,Dispatcher {api})
+            }
+
+            $(
+                /// Structure transporting method arguments received by the server.
+                #[derive(serde::Deserialize,Debug,PartialEq)]
+                #[serde(rename_all="camelCase")]
+                struct $method_input {
+                    $($param_name : $param_ty),*
+                }
+            )*
+        }
+
+
+
         // ==================
         // === MockClient ===
         // ==================
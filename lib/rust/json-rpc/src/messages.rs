@@ -4,6 +4,7 @@
 
 use crate::prelude::*;
 
+use serde::de::DeserializeOwned;
 use serde::Deserialize;
 use serde::Serialize;
 use shrinkwraprs::Shrinkwrap;
@@ -216,6 +217,76 @@ pub fn decode_incoming_message(message: &str) -> serde_json::Result<IncomingMess
     from_value::<IncomingMessage>(message.payload)
 }
 
+/// A message that can come from Client to Server — either a request or a notification.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum IncomingRequest {
+    /// A call expecting a response.
+    Request(Request<MethodCall<serde_json::Value>>),
+    /// A call not expecting any response.
+    Notification(Notification<MethodCall<serde_json::Value>>),
+}
+
+/// Contents of a single text message: either one message, or a batch of messages sent as a JSON
+/// array.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Batch<T> {
+    /// A single message.
+    Single(T),
+    /// A batch of messages.
+    Multiple(Vec<T>),
+}
+
+impl<T> Batch<T> {
+    /// Get all the messages, regardless of whether they were sent in a batch.
+    pub fn into_vec(self) -> Vec<T> {
+        match self {
+            Batch::Single(message) => vec![message],
+            Batch::Multiple(messages) => messages,
+        }
+    }
+}
+
+/// Partially decodes incoming text, which is either a single message or a batch of messages.
+///
+/// Every message of a batch is decoded separately, so an invalid message does not prevent handling
+/// the other ones. The outer error is returned only if the text is not a valid JSON.
+pub fn decode_batch<T: DeserializeOwned>(
+    message: &str,
+) -> serde_json::Result<Batch<serde_json::Result<T>>> {
+    use serde_json::from_value;
+    use serde_json::Value;
+    let decode = |message: Value| {
+        let message = from_value::<Message<Value>>(message)?;
+        from_value::<T>(message.payload)
+    };
+    match serde_json::from_str::<Value>(message)? {
+        Value::Array(messages) => Ok(Batch::Multiple(messages.into_iter().map(decode).collect())),
+        message => Ok(Batch::Single(decode(message))),
+    }
+}
+
+/// Partially decodes incoming text, which is either a single message or a batch of messages sent
+/// to the client. See [`decode_batch`].
+pub fn decode_incoming_messages(
+    message: &str,
+) -> serde_json::Result<Vec<serde_json::Result<IncomingMessage>>> {
+    decode_batch(message).map(Batch::into_vec)
+}
+
+/// Parameters of the notification informing the peer that the client is no longer interested in
+/// the reply to a request.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CancelRequest {
+    /// Identifier of the cancelled request.
+    pub id: Id,
+}
+
+impl CancelRequest {
+    /// Name of the notification method.
+    pub const METHOD: &'static str = "$/cancelRequest";
+}
+
 /// Message from server to client.
 ///
 /// `In` is any serializable (or already serialized) representation of the
@@ -368,4 +439,56 @@ mod tests {
             _ => panic!("Invalid decoding result of {}: {:?}", text, decoding_result),
         }
     }
+
+    #[test]
+    fn decode_incoming_batch_text() {
+        let text = r#"[
+            {"jsonrpc":"2.0","id":1,"result":null},
+            {"jsonrpc":"2.0","method":"update","params":{}},
+            {"id":2,"result":null}
+        ]"#;
+        let messages = decode_incoming_messages(text).unwrap();
+        assert_eq!(messages.len(), 3);
+        assert!(matches!(&messages[0], Ok(IncomingMessage::Response(Response { id: Id(1), .. }))));
+        assert!(matches!(&messages[1], Ok(IncomingMessage::Notification(_))));
+        assert!(messages[2].is_err());
+
+        let text = r#"{"jsonrpc":"2.0","id":1,"result":null}"#;
+        let messages = decode_incoming_messages(text).unwrap();
+        assert!(matches!(messages.as_slice(), [Ok(IncomingMessage::Response(_))]));
+        assert!(decode_incoming_messages("[{").is_err());
+    }
+
+    #[test]
+    fn decode_incoming_requests_text() {
+        let text = r#"[
+            {"jsonrpc":"2.0","id":1,"method":"file/read","params":{"path":"Main.enso"}},
+            {"jsonrpc":"2.0","method":"$/cancelRequest","params":{"id":1}},
+            {"jsonrpc":"2.0","id":1,"result":null}
+        ]"#;
+        let messages = decode_batch::<IncomingRequest>(text).unwrap();
+        let messages = match messages {
+            Batch::Multiple(messages) => messages,
+            Batch::Single(_) => panic!("Expected a batch of messages."),
+        };
+        if let Ok(IncomingRequest::Request(request)) = &messages[0] {
+            assert_eq!(request.id, Id(1));
+            assert_eq!(request.method, "file/read");
+            assert_eq!(request.params["path"], "Main.enso");
+        } else {
+            panic!("Expected a request, got {:?}", messages[0]);
+        }
+        if let Ok(IncomingRequest::Notification(Notification(call))) = &messages[1] {
+            let params = serde_json::from_value::<CancelRequest>(call.params.clone()).unwrap();
+            assert_eq!(call.method, CancelRequest::METHOD);
+            assert_eq!(params, CancelRequest { id: Id(1) });
+        } else {
+            panic!("Expected a notification, got {:?}", messages[1]);
+        }
+        assert!(messages[2].is_err());
+
+        let text = r#"{"jsonrpc":"2.0","id":2,"method":"file/list","params":{}}"#;
+        let message = decode_batch::<IncomingRequest>(text).unwrap();
+        assert!(matches!(message, Batch::Single(Ok(IncomingRequest::Request(_)))));
+    }
 }
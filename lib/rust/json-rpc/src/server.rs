@@ -0,0 +1,249 @@
+//! Module providing `Server`, the other side of the protocol than the `Handler`. It allows
+//! implementing in-process peers, e.g. fake servers for integration tests.
+
+use crate::prelude::*;
+
+use crate::api;
+use crate::error::RpcError;
+use crate::messages;
+use crate::messages::Batch;
+use crate::messages::Id;
+use crate::messages::IncomingRequest;
+use crate::messages::Message;
+use crate::transport::Transport;
+use crate::transport::TransportEvent;
+
+use futures::future;
+use futures::future::LocalBoxFuture;
+use futures::StreamExt;
+use serde::Serialize;
+use serde_json::Value;
+use std::future::Future;
+
+
+
+// ==================
+// === Error Code ===
+// ==================
+
+/// Error codes defined by the JSON-RPC 2.0 specification.
+pub mod error_code {
+    /// The text received is not a valid JSON.
+    pub const PARSE_ERROR: i64 = -32700;
+    /// The JSON received is not a valid request.
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// The method does not exist or is not available.
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// Invalid method parameters.
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// Internal JSON-RPC error.
+    pub const INTERNAL_ERROR: i64 = -32603;
+}
+
+
+
+// ================
+// === Dispatch ===
+// ================
+
+/// The reply to a request: either the JSON-serialized result or an error.
+pub type Reply = std::result::Result<Value, messages::Error>;
+
+/// Routes the calls received by the `Server` to the implementation of the methods.
+///
+/// Implemented by the `Dispatcher` generated by the `make_rpc_methods!` macro.
+pub trait Dispatch: Debug {
+    /// Call the method of the given name. Returns `None` if there is no such method.
+    fn dispatch(&self, method: &str, params: Value) -> Option<LocalBoxFuture<'static, Reply>>;
+}
+
+/// Converts the result of a method call into a `Reply`. The remote errors are passed to the peer
+/// as they are; all the other errors are reported as internal errors.
+pub fn encode_result<T: Serialize>(result: api::Result<T>) -> Reply {
+    match result {
+        Ok(value) => serde_json::to_value(value).map_err(internal_error),
+        Err(RpcError::RemoteError(error)) => Err(error),
+        Err(error) => Err(internal_error(error)),
+    }
+}
+
+/// The error replied when the method parameters can't be decoded.
+pub fn invalid_params(error: serde_json::Error) -> messages::Error {
+    let message = format!("Invalid params: {}.", error);
+    messages::Error { code: error_code::INVALID_PARAMS, message, data: None }
+}
+
+/// The error replied when the received text can't be parsed as JSON.
+pub fn parse_error(error: serde_json::Error) -> messages::Error {
+    let message = format!("Parse error: {}.", error);
+    messages::Error { code: error_code::PARSE_ERROR, message, data: None }
+}
+
+/// The error replied when the received JSON is not a valid request.
+pub fn invalid_request(error: impl Display) -> messages::Error {
+    let message = format!("Invalid request: {}.", error);
+    messages::Error { code: error_code::INVALID_REQUEST, message, data: None }
+}
+
+/// The error replied when there is no method of the given name.
+pub fn method_not_found(method: &str) -> messages::Error {
+    let message = format!("Method not found: {}.", method);
+    messages::Error { code: error_code::METHOD_NOT_FOUND, message, data: None }
+}
+
+fn internal_error(error: impl Display) -> messages::Error {
+    let message = error.to_string();
+    messages::Error { code: error_code::INTERNAL_ERROR, message, data: None }
+}
+
+
+
+// ================
+// === Response ===
+// ================
+
+/// A response sent by the `Server`.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+enum Response {
+    /// The response to a request.
+    Request(messages::Response<Value>),
+    /// The response to a message which is not a valid request. Its `id` is always `None`, as the
+    /// id of the request could not be read.
+    Invalid { id: Option<Id>, error: messages::Error },
+}
+
+impl Response {
+    fn invalid(error: messages::Error) -> Message<Self> {
+        Message::new(Response::Invalid { id: None, error })
+    }
+}
+
+
+
+// ==============
+// === Server ===
+// ==============
+
+/// Shared state of the `Server`.
+#[derive(Debug)]
+struct ServerData {
+    /// Transports text messages between this server and the peer.
+    transport:  RefCell<Box<dyn Transport>>,
+    /// Calls the methods requested by the peer.
+    dispatcher: Box<dyn Dispatch>,
+}
+
+/// Server is a provider of the server side of RPC protocol. Given with a transport capable of
+/// transporting text messages, it decodes the requests of a peer, calls the requested methods
+/// through the `Dispatch` implementation, and sends back their replies.
+///
+/// A batch of requests is replied with a batch of responses. Notifications received from the
+/// peer are dispatched like requests, but their results are dropped. Text which is not a valid
+/// JSON is replied with the "parse error", and messages which are not valid requests with the
+/// "invalid request" error; as the ids of these messages can't be read, the `id` of the responses
+/// is `null`.
+#[derive(Clone, CloneRef, Debug)]
+pub struct Server {
+    data: Rc<ServerData>,
+}
+
+impl Server {
+    /// Creates a new server working on a given `Transport`.
+    pub fn new(transport: impl Transport + 'static, dispatcher: impl Dispatch + 'static) -> Self {
+        let transport = RefCell::new(Box::new(transport) as Box<dyn Transport>);
+        let dispatcher = Box::new(dispatcher);
+        Self { data: Rc::new(ServerData { transport, dispatcher }) }
+    }
+
+    /// Sends a notification to the peer. The notification should serialize to a JSON map with
+    /// `method` and `params` fields, like the notification types of the client.
+    pub fn send_notification<N: Serialize>(&self, notification: &N) -> FallibleResult {
+        let message = Message::new(notification);
+        self.send_text_message(&serde_json::to_string(&message)?)
+    }
+
+    /// Sends a text message to the peer.
+    fn send_text_message(&self, text: &str) -> FallibleResult {
+        self.data.transport.borrow_mut().send_text(text)
+    }
+
+    /// Sends a JSON-serialized message to the peer. There is no one to report the failure to, as
+    /// the peer could not receive the reply anyway.
+    fn send_json_message(&self, message: &impl Serialize) {
+        if let Ok(text) = serde_json::to_string(message) {
+            self.send_text_message(&text).ok();
+        }
+    }
+
+    /// Calls the method and returns its reply.
+    async fn call(&self, call: messages::MethodCall<Value>) -> messages::Result<Value> {
+        let reply = match self.data.dispatcher.dispatch(&call.method, call.params) {
+            Some(reply) => reply.await,
+            None => Err(method_not_found(&call.method)),
+        };
+        match reply {
+            Ok(result) => messages::Result::new_success(result),
+            Err(error) => messages::Result::Error { error },
+        }
+    }
+
+    /// Processes a single decoded message from the peer, returning the response to be sent.
+    async fn process_request(
+        &self,
+        message: serde_json::Result<IncomingRequest>,
+    ) -> Option<Message<Response>> {
+        match message {
+            Ok(IncomingRequest::Request(request)) => {
+                let id = request.id;
+                let result = self.call(request.call).await;
+                Some(Message::new(Response::Request(messages::Response { id, result })))
+            }
+            Ok(IncomingRequest::Notification(messages::Notification(call))) => {
+                self.call(call).await;
+                None
+            }
+            Err(error) => Some(Response::invalid(invalid_request(error))),
+        }
+    }
+
+    /// Processes a text message from the peer, replying to all the requests it contains.
+    pub async fn process_incoming_message(&self, message: String) {
+        match messages::decode_batch::<IncomingRequest>(&message) {
+            Ok(Batch::Single(request)) =>
+                if let Some(response) = self.process_request(request).await {
+                    self.send_json_message(&response);
+                },
+            Ok(Batch::Multiple(requests)) if requests.is_empty() =>
+                self.send_json_message(&Response::invalid(invalid_request("empty batch"))),
+            Ok(Batch::Multiple(requests)) => {
+                let responses = requests.into_iter().map(|request| self.process_request(request));
+                let responses = future::join_all(responses).await.into_iter().flatten();
+                let responses = responses.collect_vec();
+                if !responses.is_empty() {
+                    self.send_json_message(&responses);
+                }
+            }
+            Err(error) => self.send_json_message(&Response::invalid(parse_error(error))),
+        }
+    }
+
+    /// Returns a `Future` that processes transport events incoming to this `Server`. The requests
+    /// are processed concurrently, so a long-running method does not block the other ones.
+    ///
+    /// A returned `Future` holds a weak handle to the data and finishes when the `Transport`'s
+    /// event stream finishes. It is expected to be passed to the executor when setting up the
+    /// `Server`.
+    pub fn runner(&self) -> impl Future<Output = ()> {
+        let event_receiver = self.data.transport.borrow_mut().establish_event_stream();
+        let weak_data = Rc::downgrade(&self.data);
+        event_receiver.for_each_concurrent(None, move |event| {
+            let server = weak_data.upgrade().map(|data| Server { data });
+            async move {
+                if let (Some(server), TransportEvent::TextMessage(message)) = (server, event) {
+                    server.process_incoming_message(message).await
+                }
+            }
+        })
+    }
+}
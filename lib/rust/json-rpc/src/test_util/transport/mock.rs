@@ -61,6 +61,9 @@ pub struct MockTransportData {
     pub sent_binary_msgs:  VecDeque<Vec<u8>>,
     /// Transport status.
     pub is_closed:         bool,
+    /// The number of messages which may be sent before the connection is lost. See
+    /// [`MockTransport::mock_drop_after`].
+    pub drop_after:        Option<usize>,
 }
 
 
@@ -138,6 +141,24 @@ impl MockTransport {
         })
     }
 
+    /// Mocks event generated when the connection is established again after being lost, e.g. by
+    /// automatic reconnecting.
+    pub fn mock_connection_reopened(&mut self) {
+        self.with_mut_data(|data| {
+            if let Some(ref mut transmitter) = data.event_transmitter {
+                data.is_closed = false;
+                channel::emit(transmitter, TransportEvent::Opened);
+            }
+        })
+    }
+
+    /// Mocks losing the connection in the middle of a request: after sending the given number of
+    /// further messages, the connection is closed. The last of these messages is recorded as sent,
+    /// but it was lost on the way, so the peer will never reply to it.
+    pub fn mock_drop_after(&mut self, messages: usize) {
+        self.with_mut_data(|data| data.drop_after = Some(messages));
+    }
+
     /// Takes the text message sent by the client and returns its contents.
     ///
     /// If the client has not sent any text messages, panics.
@@ -174,13 +195,25 @@ impl MockTransport {
     /// Fails if the transport is not open.
     pub fn send_helper<F>(&mut self, f: F) -> Result<(), Error>
     where F: FnOnce(&mut MockTransportData) {
-        self.with_mut_data(|data| {
+        let sent = self.with_mut_data(|data| {
             if data.is_closed {
                 Err(SendError::TransportClosed.into())
             } else {
                 f(data);
                 Ok(())
             }
-        })
+        });
+        let dropped = self.with_mut_data(|data| match data.drop_after.as_mut() {
+            Some(remaining) if sent.is_ok() => {
+                *remaining = remaining.saturating_sub(1);
+                *remaining == 0
+            }
+            _ => false,
+        });
+        if dropped {
+            self.with_mut_data(|data| data.drop_after = None);
+            self.mock_connection_closed();
+        }
+        sent
     }
 }
@@ -0,0 +1,263 @@
+// === Non-Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+
+use json_rpc::prelude::*;
+use json_rpc::*;
+
+use futures::future::ready;
+use futures::future::LocalBoxFuture;
+use futures::task::LocalSpawnExt;
+use futures::FutureExt;
+use futures::StreamExt;
+use json_rpc::api::Result;
+use json_rpc::messages::Id;
+use json_rpc::messages::ResponseMessage;
+use json_rpc::server::error_code;
+use json_rpc::test_util::transport::mock::MockTransport;
+use serde::Deserialize;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+use std::future::Future;
+
+
+
+// =====================
+// === Mock Protocol ===
+// =====================
+
+/// Notifications sent by the counter server.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Clone)]
+#[serde(tag = "method", content = "params")]
+pub enum Notification {
+    #[serde(rename = "counter/changed")]
+    CounterChanged { value: i64 },
+}
+
+/// Events emitted by the counter client.
+pub type Event = json_rpc::handler::Event<Notification>;
+
+make_rpc_methods! {
+/// A remote counter.
+trait API {
+    /// Add the given amount to the counter and return its new value.
+    #[MethodInput=AddInput, rpc_name="counter/add"]
+    fn add(&self, amount: i64) -> i64;
+
+    /// Set the counter to zero.
+    #[MethodInput=ResetInput, rpc_name="counter/reset"]
+    fn reset(&self) -> ();
+
+    /// Get the name of the counter. Not implemented by the test server.
+    #[MethodInput=NameInput, rpc_name="counter/name"]
+    fn name(&self) -> String;
+}}
+
+
+// === Fake Server ===
+
+#[derive(Clone, Debug, Default)]
+struct Counter {
+    value: Rc<Cell<i64>>,
+}
+
+impl server::API for Counter {
+    fn add(&self, amount: i64) -> LocalBoxFuture<'static, Result<i64>> {
+        if amount < 0 {
+            let error = RpcError::new_remote_error(1, "Negative amount");
+            return ready(Err(error)).boxed_local();
+        }
+        self.value.set(self.value.get() + amount);
+        ready(Ok(self.value.get())).boxed_local()
+    }
+
+    fn reset(&self) -> LocalBoxFuture<'static, Result<()>> {
+        self.value.set(0);
+        ready(Ok(())).boxed_local()
+    }
+}
+
+
+
+// ===============
+// === Fixture ===
+// ===============
+
+struct Fixture {
+    client_transport: MockTransport,
+    server_transport: MockTransport,
+    client:           Client,
+    server:           json_rpc::Server,
+    counter:          Counter,
+    pool:             futures::executor::LocalPool,
+}
+
+impl Fixture {
+    fn new() -> Self {
+        let client_transport = MockTransport::new();
+        let server_transport = MockTransport::new();
+        let client = Client::new(client_transport.clone());
+        let counter = Counter::default();
+        let server = server::serve(server_transport.clone(), counter.clone());
+        let pool = futures::executor::LocalPool::new();
+        pool.spawner().spawn_local(client.runner()).unwrap();
+        pool.spawner().spawn_local(server.runner()).unwrap();
+        Self { client_transport, server_transport, client, server, counter, pool }
+    }
+
+    /// Pass the messages sent by the transport to the peer of the other one.
+    fn forward(from: &mut MockTransport, to: &mut MockTransport) -> bool {
+        let messages = from.with_mut_data(|data| std::mem::take(&mut data.sent_text_msgs));
+        let forwarded = !messages.is_empty();
+        for message in messages {
+            to.mock_peer_text_message(message);
+        }
+        forwarded
+    }
+
+    /// Run the client and the server until no more messages are exchanged.
+    fn run(&mut self) {
+        loop {
+            self.pool.run_until_stalled();
+            let to_server = Self::forward(&mut self.client_transport, &mut self.server_transport);
+            let to_client = Self::forward(&mut self.server_transport, &mut self.client_transport);
+            if !to_server && !to_client {
+                break;
+            }
+        }
+    }
+
+    /// Send a raw text message to the server and return its reply, if any.
+    fn raw_request(&mut self, text: &str) -> Option<Value> {
+        self.server_transport.mock_peer_text_message(text);
+        self.pool.run_until_stalled();
+        let reply = self.server_transport.with_mut_data(|data| data.sent_text_msgs.pop_front());
+        reply.map(|reply| serde_json::from_str(&reply).unwrap())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn test_calls_through_server() {
+    let mut fixture = Fixture::new();
+    let mut first = Box::pin(fixture.client.add(&2));
+    let mut second = Box::pin(fixture.client.add(&3));
+    fixture.run();
+    assert_eq!(first.expect_ok(), 2);
+    assert_eq!(second.expect_ok(), 5);
+
+    let mut reset = Box::pin(fixture.client.reset());
+    fixture.run();
+    reset.expect_ok();
+    assert_eq!(fixture.counter.value.get(), 0);
+}
+
+#[test]
+fn test_errors_through_server() {
+    let mut fixture = Fixture::new();
+    let mut negative = Box::pin(fixture.client.add(&-1));
+    let mut name = Box::pin(fixture.client.name());
+    fixture.run();
+    match negative.expect_err() {
+        RpcError::RemoteError(error) => assert_eq!(error.code, 1),
+        error => panic!("Expected a remote error, got {:?}", error),
+    }
+    match name.expect_err() {
+        RpcError::RemoteError(error) => assert_eq!(error.code, error_code::METHOD_NOT_FOUND),
+        error => panic!("Expected a remote error, got {:?}", error),
+    }
+}
+
+#[test]
+fn test_server_notification() {
+    let mut fixture = Fixture::new();
+    let mut events = fixture.client.events();
+    let notification = Notification::CounterChanged { value: 7 };
+    fixture.server.send_notification(&notification).unwrap();
+    fixture.run();
+    match events.expect_next() {
+        Event::Notification(received) => assert_eq!(received, notification),
+        event => panic!("Expected a notification, got {:?}", event),
+    }
+}
+
+#[test]
+fn test_server_replies() {
+    let mut fixture = Fixture::new();
+    let reply = fixture
+        .raw_request(r#"{"jsonrpc":"2.0","id":1,"method":"counter/add","params":{"amount":4}}"#);
+    assert_eq!(reply, Some(json!({"jsonrpc":"2.0","id":1,"result":4})));
+
+    let reply = fixture.raw_request(
+        r#"{"jsonrpc":"2.0","id":2,"method":"counter/add","params":{"amount":"four"}}"#,
+    );
+    let reply = serde_json::from_value::<ResponseMessage<Value>>(reply.unwrap()).unwrap();
+    assert_eq!(reply.id, Id(2));
+    match reply.payload.result {
+        messages::Result::Error { error } => assert_eq!(error.code, error_code::INVALID_PARAMS),
+        result => panic!("Expected an error, got {:?}", result),
+    }
+
+    let reply = fixture.raw_request(r#"{"jsonrpc":"2.0","id":3,"method":"unknown","params":{}}"#);
+    let reply = serde_json::from_value::<ResponseMessage<Value>>(reply.unwrap()).unwrap();
+    match reply.payload.result {
+        messages::Result::Error { error } => assert_eq!(error.code, error_code::METHOD_NOT_FOUND),
+        result => panic!("Expected an error, got {:?}", result),
+    }
+
+    // Notifications are processed, but not replied.
+    let reply = fixture.raw_request(r#"{"jsonrpc":"2.0","method":"counter/reset","params":{}}"#);
+    assert_eq!(reply, None);
+    assert_eq!(fixture.counter.value.get(), 0);
+}
+
+#[test]
+fn test_server_invalid_messages() {
+    let mut fixture = Fixture::new();
+    let code_of = |reply: Option<Value>| {
+        let reply = reply.unwrap();
+        assert_eq!(reply["jsonrpc"], "2.0");
+        assert_eq!(reply["id"], Value::Null);
+        reply["error"]["code"].as_i64().unwrap()
+    };
+    assert_eq!(code_of(fixture.raw_request("garbage")), error_code::PARSE_ERROR);
+    assert_eq!(code_of(fixture.raw_request(r#"{"jsonrpc":"2.0","id":1"#)), error_code::PARSE_ERROR);
+    assert_eq!(code_of(fixture.raw_request(r#""garbage""#)), error_code::INVALID_REQUEST);
+    assert_eq!(code_of(fixture.raw_request("[]")), error_code::INVALID_REQUEST);
+    let reply = fixture.raw_request(r#"{"jsonrpc":"1.0","id":1,"method":"counter/reset"}"#);
+    assert_eq!(code_of(reply), error_code::INVALID_REQUEST);
+    let reply = fixture.raw_request(r#"{"jsonrpc":"2.0","id":1,"params":{}}"#);
+    assert_eq!(code_of(reply), error_code::INVALID_REQUEST);
+}
+
+#[test]
+fn test_server_batch() {
+    let mut fixture = Fixture::new();
+    let reply = fixture.raw_request(
+        r#"[
+            {"jsonrpc":"2.0","id":1,"method":"counter/add","params":{"amount":1}},
+            {"jsonrpc":"2.0","method":"counter/add","params":{"amount":10}},
+            "garbage",
+            {"jsonrpc":"2.0","id":2,"method":"counter/add","params":{"amount":100}}
+        ]"#,
+    );
+    let invalid_request = error_code::INVALID_REQUEST;
+    let reply = reply.unwrap();
+    assert_eq!(reply[1]["error"]["code"], invalid_request);
+    let expected = json!([
+        {"jsonrpc":"2.0","id":1,"result":1},
+        {"jsonrpc":"2.0","id":null,"error":reply[1]["error"]},
+        {"jsonrpc":"2.0","id":2,"result":111},
+    ]);
+    assert_eq!(reply, expected);
+
+    let reply = fixture.raw_request(r#"[{"jsonrpc":"2.0","method":"counter/reset","params":{}}]"#);
+    assert_eq!(reply, None);
+    assert_eq!(fixture.counter.value.get(), 0);
+}
@@ -13,6 +13,8 @@ use json_rpc::api::RemoteMethodCall;
 use json_rpc::api::Result;
 use json_rpc::error::HandlingError;
 use json_rpc::error::RpcError;
+use json_rpc::handler::Priority;
+use json_rpc::messages::CancelRequest;
 use json_rpc::messages::Id;
 use json_rpc::messages::Message;
 use json_rpc::messages::Version;
@@ -73,6 +75,8 @@ type MockRequestMessage = messages::RequestMessage<MockRequest>;
 
 type MockResponseMessage = messages::ResponseMessage<MockResponse>;
 
+type MockCancelMessage = messages::NotificationMessage<CancelRequest>;
+
 
 
 // ===================
@@ -144,6 +148,11 @@ impl Fixture {
         pool.spawner().spawn_local(fut).unwrap();
         Fixture { transport, client, pool }
     }
+
+    pub fn expect_no_message_sent(&mut self) {
+        let sent = self.transport.with_mut_data(|data| data.sent_text_msgs.clone());
+        assert!(sent.is_empty(), "Unexpected messages sent: {:?}", sent);
+    }
 }
 
 #[test]
@@ -254,6 +263,26 @@ fn test_disconnect_error() {
     }
 }
 
+#[test]
+fn test_reconnecting_after_drop_mid_request() {
+    let mut fixture = Fixture::new();
+    fixture.transport.mock_drop_after(1);
+    let mut fut = Box::pin(fixture.client.pow(8));
+    fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.pool.run_until_stalled();
+    assert!(matches!(fut.expect_err(), RpcError::LostConnection));
+    assert!(matches!(fixture.client.events_stream.expect_next(), MockEvent::Closed));
+
+    fixture.transport.mock_connection_reopened();
+    fixture.pool.run_until_stalled();
+    assert!(matches!(fixture.client.events_stream.expect_next(), MockEvent::Opened));
+    let mut fut = Box::pin(fixture.client.pow(3));
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok(), 9);
+}
+
 #[test]
 fn test_sending_while_disconnected() {
     let mut fixture = Fixture::new();
@@ -304,3 +333,142 @@ fn test_handling_invalid_notification() {
         panic!("expected InvalidNotification error");
     }
 }
+
+#[test]
+fn test_batch_call() {
+    let mut fixture = Fixture::new();
+    let mut batch = fixture.client.handler.batch();
+    let mut low = Box::pin(batch.open_request_with_priority(MockRequest { i: 2 }, Priority::Low));
+    let mut normal = Box::pin(batch.open_request(MockRequest { i: 3 }));
+    let mut high = Box::pin(batch.open_request_with_priority(MockRequest { i: 4 }, Priority::High));
+    assert_eq!(batch.len(), 3);
+    fixture.expect_no_message_sent();
+    batch.send();
+
+    // validate a single batch message sent, ordered by priorities
+    let requests = fixture.transport.expect_json_message::<Vec<MockRequestMessage>>();
+    let inputs = requests.iter().map(|request| request.i).collect_vec();
+    assert_eq!(inputs, vec![4, 3, 2]);
+    let ids = requests.iter().map(|request| request.id).collect_vec();
+    assert_eq!(ids, vec![high.id(), normal.id(), low.id()]);
+    fixture.expect_no_message_sent();
+
+    // reply with a batch in a different order
+    let replies = requests.into_iter().rev().map(pow_impl).collect_vec();
+    fixture.transport.mock_peer_json_message(replies);
+    low.expect_pending();
+    fixture.pool.run_until_stalled();
+
+    assert_eq!(low.expect_ok().result, 4);
+    assert_eq!(normal.expect_ok().result, 9);
+    assert_eq!(high.expect_ok().result, 16);
+    fixture.client.expect_no_notification_yet();
+}
+
+#[test]
+fn test_dropped_batch_is_sent() {
+    let mut fixture = Fixture::new();
+    let mut batch = fixture.client.handler.batch();
+    let mut fut = Box::pin(batch.open_request(MockRequest { i: 5 }));
+    drop(batch);
+    let requests = fixture.transport.expect_json_message::<Vec<MockRequestMessage>>();
+    assert_eq!(requests.len(), 1);
+    fixture.transport.mock_peer_json_message(pow_impl(requests.into_iter().next().unwrap()));
+    fixture.pool.run_until_stalled();
+    assert_eq!(fut.expect_ok().result, 25);
+
+    // An empty batch is not sent at all.
+    fixture.client.handler.batch().send();
+    fixture.expect_no_message_sent();
+}
+
+#[test]
+fn test_batch_with_invalid_message() {
+    let mut fixture = Fixture::new();
+    let mut fut = Box::pin(fixture.client.pow(6));
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    let reply = serde_json::to_value(pow_impl(request)).unwrap();
+    let garbage = serde_json::Value::String("hello, nice to meet you".into());
+    fixture.transport.mock_peer_json_message(vec![garbage, reply]);
+    fixture.pool.run_until_stalled();
+
+    assert_eq!(fut.expect_ok(), 36);
+    let internal_error = fixture.client.expect_handling_error();
+    if let HandlingError::InvalidMessage(_) = internal_error {
+    } else {
+        panic!("Expected an error to be InvalidMessage");
+    }
+}
+
+#[test]
+fn test_cancel_call() {
+    let mut fixture = Fixture::new();
+    let mut fut = Box::pin(fixture.client.handler.open_request(MockRequest { i: 7 }));
+    let cancel_handle = fut.cancel_handle();
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fut.expect_pending();
+
+    assert!(cancel_handle.cancel());
+    assert!(cancel_handle.is_cancelled());
+    assert!(!cancel_handle.cancel());
+    if let RpcError::Cancelled = fut.expect_err() {
+    } else {
+        panic!("Expected an error to be Cancelled");
+    }
+    fixture.expect_no_message_sent();
+
+    // The late reply is ignored.
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    fixture.client.expect_no_notification_yet();
+}
+
+#[test]
+fn test_cancelled_call_expiry() {
+    let mut fixture = Fixture::new();
+    fixture.client.handler.set_timeout(Duration::from_millis(1));
+    let fut = fixture.client.handler.open_request(MockRequest { i: 7 });
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    let id = request.id;
+    assert!(fut.cancel());
+    sleep(Duration::from_millis(10));
+
+    // The reply received after the timeout is no longer expected.
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+    if let HandlingError::UnexpectedResponse(response) = fixture.client.expect_handling_error() {
+        assert_eq!(response.id, id);
+    } else {
+        panic!("Expected an UnexpectedResponse error");
+    }
+}
+
+#[test]
+fn test_cancel_call_notifying_peer() {
+    let mut fixture = Fixture::new();
+    let mut fut = Box::pin(fixture.client.handler.open_request(MockRequest { i: 7 }));
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+
+    assert!(fut.cancel_handle().cancel_and_notify_peer());
+    let notification = fixture.transport.expect_json_message::<MockCancelMessage>();
+    let call = notification.payload.0;
+    assert_eq!(call.method, CancelRequest::METHOD);
+    assert_eq!(call.params, CancelRequest { id: request.id });
+    if let RpcError::Cancelled = fut.expect_err() {
+    } else {
+        panic!("Expected an error to be Cancelled");
+    }
+}
+
+#[test]
+fn test_cancel_completed_call() {
+    let mut fixture = Fixture::new();
+    let mut fut = Box::pin(fixture.client.handler.open_request(MockRequest { i: 3 }));
+    let request = fixture.transport.expect_json_message::<MockRequestMessage>();
+    fixture.transport.mock_peer_json_message(pow_impl(request));
+    fixture.pool.run_until_stalled();
+
+    assert!(!fut.cancel_handle().cancel_and_notify_peer());
+    fixture.expect_no_message_sent();
+    assert_eq!(fut.expect_ok().result, 9);
+}
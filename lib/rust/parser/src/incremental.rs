@@ -0,0 +1,304 @@
+//! Incremental reparsing. After an edit of the source code, only the top-level lines around the
+//! edited range are parsed again; the trees of all the other top-level lines are reused.
+//!
+//! A top-level line starting in the first column always begins a new statement of the module,
+//! regardless of the lines preceding it. The lines reparsed after an edit are therefore extended
+//! in both directions until such a line is found, which guarantees that the result is the same as
+//! the result of parsing the whole new code.
+//!
+//! The reused lines borrow the code of the original tree, while the serialized tree refers to the
+//! code by its offsets in the new source buffer. The code of all the lines is therefore sliced
+//! again from the new buffer.
+
+use crate::prelude::*;
+
+use crate::source::span;
+use crate::source::Code;
+use crate::syntax;
+use crate::syntax::tree::block;
+use crate::syntax::tree::SpanVisitableMut;
+use crate::syntax::tree::SpanVisitorMut;
+use crate::syntax::tree::Visitor;
+use crate::Parser;
+
+use std::ops::Range;
+
+
+
+// ============
+// === Edit ===
+// ============
+
+/// A change of the source code: replacement of a range of the original code with a new text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit<'a> {
+    /// The replaced range of the original code.
+    pub range: Range<Bytes>,
+    /// The text inserted in place of the replaced range.
+    pub text:  &'a str,
+}
+
+impl<'a> Edit<'a> {
+    /// Constructor.
+    pub fn new(range: Range<Bytes>, text: &'a str) -> Self {
+        Self { range, text }
+    }
+
+    /// Return the code resulting from applying this edit to the given code.
+    pub fn apply(&self, code: &str) -> String {
+        let mut result = String::with_capacity(code.len() + self.text.len());
+        result.push_str(code.slice(Bytes(0)..self.range.start));
+        result.push_str(self.text);
+        result.push_str(code.slice(self.range.end..));
+        result
+    }
+
+    /// The position in the new code corresponding to a position of the original code that follows
+    /// the replaced range.
+    fn shift(&self, position: Bytes) -> Bytes {
+        position + Bytes(self.text.len()) - (self.range.end - self.range.start)
+    }
+}
+
+
+
+// ==============
+// === Parser ===
+// ==============
+
+impl Parser {
+    /// Parse `code`, which is the result of applying the `edit` to the code of the given `tree`,
+    /// reusing the top-level lines of the tree not affected by the edit. The result is the same as
+    /// the result of [`Parser::run`] applied to `code`. If the tree is not a result of parsing a
+    /// complete module, the whole code is parsed again.
+    pub fn reparse<'s>(
+        &self,
+        tree: syntax::Tree<'s>,
+        edit: &Edit,
+        code: &'s str,
+    ) -> syntax::Tree<'s> {
+        reparse_lines(self, tree, edit, code).unwrap_or_else(|| self.run(code))
+    }
+}
+
+/// Reparse the top-level lines affected by the edit. Return [`None`] if the lines of the tree can't
+/// be reused.
+fn reparse_lines<'s>(
+    parser: &Parser,
+    tree: syntax::Tree<'s>,
+    edit: &Edit,
+    code: &'s str,
+) -> Option<syntax::Tree<'s>> {
+    let mut lines = match *tree.variant {
+        syntax::tree::Variant::BodyBlock(block) => block.statements,
+        _ => return None,
+    };
+    let mut starts = Vec::with_capacity(lines.len() + 1);
+    let mut end_of_code = Bytes(0);
+    for line in &lines {
+        starts.push(end_of_code);
+        end_of_code += line_length(line);
+    }
+    let edit_is_valid = edit.range.start <= edit.range.end
+        && edit.range.end <= end_of_code
+        && edit.shift(end_of_code) == Bytes(code.len());
+    if lines.is_empty() || !edit_is_valid {
+        return None;
+    }
+    // The line containing the beginning of the edit, and the line preceding it, which could be
+    // joined with the edited line.
+    let edited = starts.partition_point(|&start| start <= edit.range.start) - 1;
+    let mut first = edited.saturating_sub(1);
+    while first > 0 && !starts_statement(&lines[first]) {
+        first -= 1;
+    }
+    let mut end = starts.partition_point(|&start| start <= edit.range.end);
+    while end < lines.len() && !starts_statement(&lines[end]) {
+        end += 1;
+    }
+    let first_newline = lines[first].newline.clone();
+    let reparsed_start = starts[first] + first_newline.left_offset.len() + first_newline.code.len();
+    let reparsed_end = edit.shift(starts.get(end).copied().unwrap_or(end_of_code));
+    let reparsed = parser.run(code.slice(reparsed_start..reparsed_end));
+    let mut reparsed_lines = match *reparsed.variant {
+        syntax::tree::Variant::BodyBlock(block) => block.statements,
+        _ => return None,
+    };
+    // An annotation at the end of the reparsed lines could be attached to a following line.
+    let last_statement = reparsed_lines.iter().rev().find_map(|line| line.expression.as_ref());
+    if end < lines.len() && last_statement.map_or(false, syntax::tree::is_unattached_annotation) {
+        return None;
+    }
+    reparsed_lines.first_mut()?.newline = first_newline;
+    lines.splice(first..end, reparsed_lines);
+    let mut reslicer = Reslicer { code, position: Bytes(0) };
+    for line in &mut lines {
+        line.visit_span_mut(&mut reslicer);
+    }
+    debug_assert_eq!(reslicer.position, Bytes(code.len()));
+    Some(syntax::Tree::body_block(lines))
+}
+
+/// A visitor replacing the borrowed code of the visited tokens and offsets with the consecutive
+/// slices of the new source code. The tokens and offsets are visited in the order of the code they
+/// represent.
+#[derive(Debug)]
+struct Reslicer<'s> {
+    code:     &'s str,
+    position: Bytes,
+}
+
+impl<'s> Reslicer<'s> {
+    fn reslice(&mut self, code: &mut Code<'s>) {
+        let end = self.position + code.len();
+        // Owned code is not a part of the source buffer, and it is serialized without an offset.
+        if let Cow::Borrowed(_) = code.repr {
+            *code = self.code.slice(self.position..end).into();
+        }
+        self.position = end;
+    }
+}
+
+impl<'s> Visitor for Reslicer<'s> {}
+impl<'s> SpanVisitorMut<'s> for Reslicer<'s> {
+    fn visit_mut(&mut self, span: span::RefMut<'s, '_>) -> bool {
+        self.reslice(&mut span.left_offset.code);
+        if let Some(code) = span.code {
+            self.reslice(code);
+        }
+        true
+    }
+}
+
+/// The length of the code of the line, including its leading newline token.
+fn line_length(line: &block::Line) -> Bytes {
+    let newline = line.newline.left_offset.len() + line.newline.code.len();
+    let expression = line.expression.as_ref();
+    newline
+        + expression.map_or(Bytes(0), |tree| tree.span.left_offset.len() + tree.span.code_length)
+}
+
+/// Check whether the line starts in the first column with an expression, which means it begins a
+/// new statement independently of the lines preceding it.
+fn starts_statement(line: &block::Line) -> bool {
+    line.expression.as_ref().map_or(false, |tree| tree.span.left_offset.is_empty())
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialization::serialize_tree;
+
+    /// Check that reparsing the code after replacing the given range with the given text gives the
+    /// same result as parsing the new code from scratch. The serialized trees are compared as well,
+    /// as they contain the offsets of all the tokens in the new code.
+    fn test_reparse(code: &str, range: Range<usize>, text: &str) {
+        let parser = Parser::new();
+        let edit = Edit::new(Bytes(range.start)..Bytes(range.end), text);
+        let new_code = edit.apply(code);
+        let tree = parser.run(code);
+        let reparsed = parser.reparse(tree, &edit, &new_code);
+        let expected = parser.run(&new_code);
+        assert_eq!(reparsed, expected, "Reparsing {new_code:?}.");
+        assert_eq!(reparsed.code(), new_code);
+        let serialized = serialize_tree(&reparsed).unwrap();
+        assert_eq!(serialized, serialize_tree(&expected).unwrap(), "Reparsing {new_code:?}.");
+    }
+
+    const MODULE: &str = "\
+import Standard.Base
+
+type Pair
+    Pair first second
+
+    swap self = Pair self.second self.first
+
+main =
+    x = 1 + 2
+    y = x * 3
+
+    y.to_text
+
+other = 4
+";
+
+    #[test]
+    fn test_edit_in_line() {
+        let start = MODULE.find("1 + 2").unwrap();
+        test_reparse(MODULE, start..start + 1, "10");
+        let start = MODULE.find("other").unwrap();
+        test_reparse(MODULE, start..start + 5, "another");
+    }
+
+    #[test]
+    fn test_reused_lines_offsets() {
+        let parser = Parser::new();
+        let start = MODULE.find("main").unwrap();
+        let edit = Edit::new(Bytes(start)..Bytes(start), "helper = 5\n");
+        let new_code = edit.apply(MODULE);
+        let reparsed = parser.reparse(parser.run(MODULE), &edit, &new_code);
+        // The tokens of the lines before and after the edit are located in the new code.
+        let trees = reparsed.collect_vec_ref();
+        let identifiers = trees.iter().filter_map(|tree| match &*tree.variant {
+            syntax::tree::Variant::Ident(ident) => Some(&*ident.token.code.repr),
+            _ => None,
+        });
+        let identifiers = identifiers.collect_vec();
+        assert!(identifiers.contains(&"other"));
+        for code in identifiers {
+            let offset = (code.as_ptr() as usize).wrapping_sub(new_code.as_ptr() as usize);
+            let is_in_code = offset <= new_code.len().saturating_sub(code.len());
+            assert!(is_in_code, "{code:?} is not in the new code.");
+            assert_eq!(&new_code[offset..offset + code.len()], code);
+        }
+        test_reparse(MODULE, start..start, "helper = 5\n");
+    }
+
+    #[test]
+    fn test_edit_at_boundaries() {
+        test_reparse(MODULE, 0..0, "from Standard.Base import all\n");
+        test_reparse(MODULE, 0..6, "export");
+        test_reparse(MODULE, MODULE.len()..MODULE.len(), "last = 5");
+        test_reparse(MODULE, MODULE.len() - 1..MODULE.len(), "");
+        test_reparse("", 0..0, "x = 1");
+    }
+
+    #[test]
+    fn test_edit_lines() {
+        let start = MODULE.find("main").unwrap();
+        test_reparse(MODULE, start..start, "helper = 5\n");
+        test_reparse(MODULE, start - 1..start, "");
+        test_reparse(MODULE, start..start, "    ");
+        let start = MODULE.find("    y.to_text").unwrap();
+        test_reparse(MODULE, start..start + 4, "");
+        let start = MODULE.find("other").unwrap();
+        test_reparse(MODULE, start..start, "    ");
+        test_reparse(MODULE, start..start, "# ");
+    }
+
+    #[test]
+    fn test_edit_text_literal() {
+        let start = MODULE.find("main =").unwrap();
+        test_reparse(MODULE, start..start, "text = \"\"\"\n");
+        let start = MODULE.find("y = x").unwrap();
+        test_reparse(MODULE, start + 4..start + 4, "'unclosed ");
+    }
+
+    #[test]
+    fn test_edit_annotations() {
+        let start = MODULE.find("other").unwrap();
+        test_reparse(MODULE, start..start, "@Builtin_Method\n");
+        test_reparse(MODULE, start..start, "@x 1\n\n");
+        let code = MODULE.replace("type Pair", "@Builtin_Type\ntype Pair");
+        let start = code.find("type Pair").unwrap();
+        test_reparse(&code, start..start + 4, "type");
+        test_reparse(&code, start - 1..start, "");
+        test_reparse(&code, start..start, "foo = 1\n");
+    }
+}
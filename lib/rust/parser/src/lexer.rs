@@ -96,6 +96,8 @@ pub struct LexerState {
     pub last_spaces_visible_offset: VisibleOffset,
     pub current_block_indent:       VisibleOffset,
     pub block_indent_stack:         Vec<VisibleOffset>,
+    /// States of the text literals containing the interpolated expressions being lexed.
+    pub splice_stack:               Vec<TextState>,
     pub internal_error:             Option<String>,
 }
 
@@ -541,62 +543,104 @@ impl<'s> Lexer<'s> {
         if let Some(token) = token {
             if token.code == "+-" {
                 let (left, right) = token.split_at_(Bytes(1));
-                let (binary, unary) = compute_precedence(&left.code);
-                self.submit_token(
-                    left.with_variant(token::Variant::operator(binary, unary, false, true)),
-                );
-                let (_, unary) = compute_precedence(&right.code);
-                let rhs = token::Variant::operator(None, unary, false, true);
-                self.submit_token(right.with_variant(rhs));
+                let lhs = analyze_operator(&left.code);
+                self.submit_token(left.with_variant(token::Variant::Operator(lhs)));
+                let rhs = token::variant::Operator {
+                    binary_infix_precedence: None,
+                    ..analyze_operator(&right.code)
+                };
+                self.submit_token(right.with_variant(token::Variant::Operator(rhs)));
             } else {
                 let only_eq = token.code.chars().all(|t| t == '=');
-                let is_mod = token.code.ends_with('=') && !only_eq;
+                let is_comparison = matches!(token.code.as_ref(), "<=" | ">=" | "!=");
+                let is_mod = token.code.ends_with('=') && !only_eq && !is_comparison;
                 let tp = if is_mod {
                     token::Variant::modifier()
+                } else if token.code == "@" && self.starts_annotation(&token.left_offset) {
+                    token::Variant::annotation_operator()
                 } else {
-                    let (binary, unary) = compute_precedence(&token.code);
-                    token::Variant::operator(binary, unary, token.code == ":", token.code != ",")
+                    token::Variant::Operator(analyze_operator(&token.code))
                 };
                 let token = token.with_variant(tp);
                 self.submit_token(token);
             }
         }
     }
+
+    /// Check whether the `@` operator token introduces an annotation: it is directly followed by an
+    /// identifier, and it does not follow an operand immediately.
+    fn starts_annotation(&self, left_offset: &Offset<'s>) -> bool {
+        let followed_by_space = self.last_spaces_offset != Bytes(0);
+        let followed_by_ident =
+            !followed_by_space && self.current_char.map_or(false, |char| char.is_alphabetic());
+        let has_space = left_offset.visible.width_in_spaces != 0;
+        let starts_line = match self.output.last() {
+            None => true,
+            Some(prev) => matches!(
+                prev.variant,
+                token::Variant::Newline(_)
+                    | token::Variant::BlockStart(_)
+                    | token::Variant::BlockEnd(_)
+            ),
+        };
+        followed_by_ident && (has_space || starts_line)
+    }
 }
 
 
 // === Precedence ===
 
-// FIXME: Compute precedences according to spec. Issue: #182497344
-fn compute_precedence(token: &str) -> (Option<token::Precedence>, Option<token::Precedence>) {
-    let binary = match token {
+/// Compute the properties of an operator: its precedence in the binary infix and unary prefix
+/// roles, and its associativity.
+///
+/// The operators with a special meaning in the language have their precedence defined explicitly.
+/// The precedence of any other operator is determined by its first character, called the
+/// *precedence character*, so that user-defined operators bind like the built-in operators they
+/// resemble.
+fn analyze_operator(token: &str) -> token::variant::Operator {
+    let (binary, unary) = match token {
         // Special handling for tokens that can be unary.
-        "~" => return (None, Some(token::Precedence { value: 100 })),
-        "-" =>
-            return (Some(token::Precedence { value: 14 }), Some(token::Precedence { value: 100 })),
+        "~" => (None, Some(100)),
+        "-" => (Some(15), Some(100)),
         // "There are a few operators with the lowest precedence possible."
-        "=" => 1,
-        ":" => 2,
-        "->" => 3,
-        "|" | "\\\\" | "&" => 4,
-        ">>" | "<<" => 5,
-        "|>" | "|>>" | "<|" | "<<|" => 6,
+        "=" => (Some(1), None),
+        ":" => (Some(2), None),
+        "->" => (Some(3), None),
+        "," => (Some(4), None),
+        "|" | "\\\\" | "&" => (Some(5), None),
+        ">>" | "<<" => (Some(6), None),
+        "|>" | "|>>" | "<|" | "<<|" => (Some(7), None),
+        // Comparisons, whose precedence characters vary.
+        "!=" => (Some(14), None),
         // "The precedence of all other operators is determined by the operator's Precedence
         // Character:"
-        "!" => 10,
-        "||" => 11,
-        "^" => 12,
-        "&&" => 13,
-        "+" | "++" => 14,
-        "*" | "/" | "%" => 15,
-        // FIXME: Not sure about these:
-        "==" => 1,
-        "," => 1,
-        "@" => 20,
-        "." => 21,
-        _ => return (None, None),
+        _ => (token.chars().next().map(precedence_of_character), None),
     };
-    (Some(token::Precedence { value: binary }), None)
+    let to_precedence = |value| token::Precedence { value };
+    token::variant::Operator {
+        binary_infix_precedence: binary.map(to_precedence),
+        unary_prefix_precedence: unary.map(to_precedence),
+        is_type_annotation:      token == ":",
+        can_form_section:        token != ",",
+        is_right_associative:    matches!(token, "->" | "<|" | "<<|" | "^"),
+    }
+}
+
+/// The binary infix precedence of an operator with the given precedence character and no
+/// explicitly defined precedence.
+fn precedence_of_character(char: char) -> usize {
+    match char {
+        '!' => 10,
+        '|' => 11,
+        '^' => 12,
+        '&' => 13,
+        '=' | '<' | '>' => 14,
+        '+' | '-' => 15,
+        '*' | '/' | '%' => 16,
+        '@' => 20,
+        '.' => 21,
+        _ => 17,
+    }
 }
 
 
@@ -620,16 +664,134 @@ impl<'s> Lexer<'s> {
 // === Number ===
 // ==============
 
+/// Return the base denoted by the letter following `0` in the prefix of a numeric literal, like `x`
+/// in `0x1F`.
+fn base_from_prefix(char: char) -> Option<token::Base> {
+    match char {
+        'b' | 'B' => Some(token::Base::Binary),
+        'o' | 'O' => Some(token::Base::Octal),
+        'x' | 'X' => Some(token::Base::Hexadecimal),
+        _ => None,
+    }
+}
+
+/// Return the base denoted by the radix in the prefix of a numeric literal, like `16` in `16_ff`.
+fn base_from_radix(radix: &str) -> Option<token::Base> {
+    match radix {
+        "2" => Some(token::Base::Binary),
+        "8" => Some(token::Base::Octal),
+        "16" => Some(token::Base::Hexadecimal),
+        _ => None,
+    }
+}
+
 impl<'s> Lexer<'s> {
-    /// Parse a number.
+    /// Parse a number. A number is lexed as a sequence of tokens with no spaces between them: an
+    /// optional [`token::NumberBase`], the [`token::Digits`] of the integer part, and, in case of
+    /// decimal numbers, the optional fractional part and exponent.
     fn number(&mut self) {
+        if !self.current_char.map_or(false, is_digit) {
+            return;
+        }
+        let base = self.number_base();
+        self.digits(base);
+        if base.is_some() || !self.last_spaces_offset.is_zero() {
+            return;
+        }
+        if self.current_char == Some('.') && self.lookahead().next().map_or(false, is_digit) {
+            if let Some(token) = self.token(|this| this.take_next()) {
+                self.submit_token(token.with_variant(token::Variant::decimal_point()));
+            }
+            self.digits(None);
+            if !self.last_spaces_offset.is_zero() {
+                return;
+            }
+        }
+        if let Some(length) = self.exponent_marker_length() {
+            let token = self.token(|this| {
+                for _ in 0..length {
+                    this.take_next();
+                }
+            });
+            if let Some(token) = token {
+                self.submit_token(token.with_variant(token::Variant::exponent()));
+            }
+            self.digits(None);
+        }
+    }
+
+    /// Iterate over the characters following the current one, without consuming them.
+    fn lookahead(&self) -> impl Iterator<Item = char> + 's {
+        self.iterator.clone().map(|(_, char)| char)
+    }
+
+    /// Parse the base prefix of a number, like `0x` in `0x1F`, or `16_` in `16_ff`, if present.
+    /// Return the base denoted by the prefix.
+    ///
+    /// The radix form is ambiguous with digits separated by underscores, like `16_000`; it is only
+    /// used if the digits following the first underscore contain a letter.
+    fn number_base(&mut self) -> Option<token::Base> {
+        let current = self.current_char?;
+        let mut lookahead = self.lookahead();
+        let (base, length) = if current == '0'
+                && let Some(base) = lookahead.next().and_then(base_from_prefix)
+                && lookahead.next().map_or(false, is_ident_char) {
+            (base, 2)
+        } else {
+            let radix_length = 1 + self.lookahead().take_while(|&char| is_digit(char)).count();
+            let start = self.current_offset;
+            let radix = self.input.slice(start..start + Bytes(radix_length));
+            let base = base_from_radix(radix)?;
+            let mut digits = self.lookahead().skip(radix_length - 1);
+            if digits.next() != Some('_') {
+                return None;
+            }
+            let mut digits = digits.take_while(|&char| is_ident_char(char));
+            if !digits.any(|char| char.is_alphabetic()) {
+                return None;
+            }
+            (base, radix_length + 1)
+        };
+        let token = self.token(|this| {
+            for _ in 0..length {
+                this.take_next();
+            }
+        });
+        if let Some(token) = token {
+            self.submit_token(token.with_variant(token::Variant::number_base()));
+        }
+        Some(base)
+    }
+
+    /// Parse the digits of a number in the given base. The digits of a decimal number end before
+    /// its exponent, like `e-2` in `1e-2`. Any other characters that could be part of an identifier
+    /// are included; they will be reported as invalid digits by the parser.
+    fn digits(&mut self, base: Option<token::Base>) {
         let token = self.token(|this| {
-            if this.take_1(is_digit) {
-                this.take_while(|t| !is_ident_split_char(t));
+            while let Some(char) = this.current_char {
+                let is_exponent = base.is_none() && this.exponent_marker_length().is_some();
+                if !is_ident_char(char) || is_exponent {
+                    break;
+                }
+                this.take_next();
             }
         });
         if let Some(token) = token {
-            self.submit_token(token.with_variant(token::Variant::number()));
+            self.submit_token(token.with_variant(token::Variant::digits(base)));
+        }
+    }
+
+    /// If the current character begins the exponent of a decimal number, like `e` or `e-` in
+    /// `1e-2`, return the length of the exponent marker.
+    fn exponent_marker_length(&self) -> Option<usize> {
+        if !matches!(self.current_char, Some('e' | 'E')) {
+            return None;
+        }
+        let mut lookahead = self.lookahead();
+        match lookahead.next() {
+            Some(char) if is_digit(char) => Some(1),
+            Some('+' | '-') if lookahead.next().map_or(false, is_digit) => Some(2),
+            _ => None,
         }
     }
 }
@@ -640,14 +802,31 @@ impl<'s> Lexer<'s> {
 // === Text ===
 // ============
 
+/// The state of a text literal whose lexing was interrupted by an interpolated expression. It is
+/// used to resume reading the literal when the closing backtick of the expression is found.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextState {
+    /// An inline text literal.
+    Inline,
+    /// A multiline text literal, introduced by a line with the given indentation.
+    Multiline {
+        /// The indentation of the line introducing the literal.
+        indent: VisibleOffset,
+    },
+}
+
 impl<'s> Lexer<'s> {
     /// Read a text literal.
     fn text(&mut self) {
-        // TODO: Interpolation within "'" quotes (#182496932); for now, treat them as raw.
         let quote_char = match self.current_char {
             Some(char @ ('"' | '\'')) => char,
+            Some('`') if !self.splice_stack.is_empty() => {
+                self.text_splice_end();
+                return;
+            }
             _ => return,
         };
+        let interpolate = quote_char == '\'';
         let indent = self.last_spaces_visible_offset;
         let open_quote_start = self.mark();
         self.last_spaces_visible_offset = VisibleOffset(0);
@@ -668,14 +847,9 @@ impl<'s> Lexer<'s> {
                 let token = self.make_token(open_quote_start, text_start.clone(),
                     token::Variant::TextStart(token::variant::TextStart()));
                 self.output.push(token);
-                self.take_rest_of_line();
-                let next_line_start = self.mark();
-                let token = self.make_token(text_start, next_line_start,
-                    token::Variant::TextSection(token::variant::TextSection()));
-                if !token.code.len().is_zero() {
-                    self.output.push(token);
+                if !self.text_line(text_start, indent, interpolate, false) {
+                    self.text_lines(indent, interpolate);
                 }
-                self.text_lines(indent);
             } else {
                 // Exactly two quote characters: Open and shut case.
                 let close_quote_end = self.mark();
@@ -688,49 +862,75 @@ impl<'s> Lexer<'s> {
             }
         } else {
             // One quote followed by non-quote character: Inline quote.
-            let mut text_start = self.mark();
-            let token = self.make_token(open_quote_start, text_start.clone(),
+            let text_start = self.mark();
+            let token = self.make_token(open_quote_start, text_start,
                 token::Variant::TextStart(token::variant::TextStart()));
             self.output.push(token);
-            while let Some(char) = self.current_char {
-                if char == quote_char || is_newline_char(char) {
-                    break;
-                }
-                if char == '\\' {
-                    let escape_start = self.mark();
-                    self.take_next();
-                    if let Some(char) = self.current_char && char == quote_char {
-                        let token = self.make_token(text_start, escape_start.clone(),
-                            token::Variant::TextSection(token::variant::TextSection()));
-                        self.output.push(token);
-                        let escape_end = self.mark();
-                        let token = self.make_token(escape_start, escape_end.clone(),
-                            token::Variant::TextEscape(token::variant::TextEscape()));
-                        self.output.push(token);
-                        text_start = escape_end;
-                    }
-                }
-                self.take_next();
+            self.inline_text_body(quote_char);
+        }
+        self.spaces_after_lexeme();
+    }
+
+    /// Read the content of an inline text literal, starting after the opening quote or after the
+    /// end of an interpolated expression. Stops after the closing quote, at the end of the line, or
+    /// after the opening backtick of an interpolated expression.
+    fn inline_text_body(&mut self, quote_char: char) {
+        let interpolate = quote_char == '\'';
+        let is_escapable = |char: char| char == quote_char || (interpolate && char == '`');
+        let mut text_start = self.mark();
+        while let Some(char) = self.current_char {
+            if char == quote_char || is_newline_char(char) {
+                break;
             }
-            let close_quote_start = self.mark();
-            let token = self.make_token(text_start, close_quote_start.clone(),
-                token::Variant::TextSection(token::variant::TextSection()));
-            if !token.code.len().is_zero() {
-                self.output.push(token);
+            if interpolate && char == '`' {
+                let splice_start = self.mark();
+                let token = self.make_token(
+                    text_start,
+                    splice_start.clone(),
+                    token::Variant::TextSection(token::variant::TextSection()),
+                );
+                if !token.code.len().is_zero() {
+                    self.output.push(token);
+                }
+                self.text_splice_start(splice_start, TextState::Inline);
+                return;
             }
-            if let Some(char) = self.current_char && char == quote_char {
+            if char == '\\' {
+                let escape_start = self.mark();
                 self.take_next();
-                let close_quote_end = self.mark();
-                let token = self.make_token(close_quote_start, close_quote_end,
-                    token::Variant::TextEnd(token::variant::TextEnd()));
-                self.output.push(token);
+                if let Some(char) = self.current_char && is_escapable(char) {
+                    let token = self.make_token(text_start, escape_start.clone(),
+                        token::Variant::TextSection(token::variant::TextSection()));
+                    self.output.push(token);
+                    let escape_end = self.mark();
+                    let token = self.make_token(escape_start, escape_end.clone(),
+                        token::Variant::TextEscape(token::variant::TextEscape()));
+                    self.output.push(token);
+                    text_start = escape_end;
+                }
             }
+            self.take_next();
+        }
+        let close_quote_start = self.mark();
+        let token = self.make_token(
+            text_start,
+            close_quote_start.clone(),
+            token::Variant::TextSection(token::variant::TextSection()),
+        );
+        if !token.code.len().is_zero() {
+            self.output.push(token);
+        }
+        if let Some(char) = self.current_char && char == quote_char {
+            self.take_next();
+            let close_quote_end = self.mark();
+            let token = self.make_token(close_quote_start, close_quote_end,
+                token::Variant::TextEnd(token::variant::TextEnd()));
+            self.output.push(token);
         }
-        self.spaces_after_lexeme();
     }
 
     /// Read the lines of a text literal, after the initial line introducing it.
-    fn text_lines(&mut self, indent: VisibleOffset) {
+    fn text_lines(&mut self, indent: VisibleOffset, interpolate: bool) {
         while self.current_char.is_some() {
             let start = self.mark();
             // Consume the newline and any spaces.
@@ -762,15 +962,86 @@ impl<'s> Lexer<'s> {
             );
             self.output.push(token);
             // Output the line as a text section.
-            self.take_rest_of_line();
-            let next_line_start = self.mark();
-            let token = self.make_token(
-                after_space,
-                next_line_start,
-                token::Variant::TextSection(token::variant::TextSection()),
-            );
+            if self.text_line(after_space, indent, interpolate, true) {
+                return;
+            }
+        }
+    }
+
+    /// Read the rest of the current line of a multiline text literal, starting at `text_start`,
+    /// and output it as a text section. If `push_empty` is not set, an empty section is omitted.
+    /// Returns [`true`] if the line was interrupted by the opening backtick of an interpolated
+    /// expression.
+    fn text_line(
+        &mut self,
+        text_start: (Bytes, Offset<'s>),
+        indent: VisibleOffset,
+        interpolate: bool,
+        push_empty: bool,
+    ) -> bool {
+        while let Some(char) = self.current_char {
+            if is_newline_char(char) {
+                break;
+            }
+            if interpolate && char == '`' {
+                let splice_start = self.mark();
+                let token = self.make_token(
+                    text_start,
+                    splice_start.clone(),
+                    token::Variant::TextSection(token::variant::TextSection()),
+                );
+                if push_empty || !token.code.len().is_zero() {
+                    self.output.push(token);
+                }
+                self.text_splice_start(splice_start, TextState::Multiline { indent });
+                return true;
+            }
+            self.take_next();
+        }
+        let line_end = self.mark();
+        let token = self.make_token(
+            text_start,
+            line_end,
+            token::Variant::TextSection(token::variant::TextSection()),
+        );
+        if push_empty || !token.code.len().is_zero() {
             self.output.push(token);
         }
+        false
+    }
+
+    /// Read the backtick opening an interpolated expression. The text literal will be resumed
+    /// in the provided state when the matching closing backtick is found.
+    fn text_splice_start(&mut self, splice_start: (Bytes, Offset<'s>), state: TextState) {
+        self.take_next();
+        let splice_end = self.mark();
+        let token = self.make_token(splice_start, splice_end, token::Variant::symbol());
+        self.output.push(token);
+        self.splice_stack.push(state);
+    }
+
+    /// Read the backtick closing an interpolated expression, and resume reading the text literal
+    /// containing the expression.
+    fn text_splice_end(&mut self) {
+        let state = match self.splice_stack.pop() {
+            Some(state) => state,
+            None => return,
+        };
+        let splice_start = self.mark();
+        self.last_spaces_visible_offset = VisibleOffset(0);
+        self.last_spaces_offset = Bytes(0);
+        self.take_next();
+        let splice_end = self.mark();
+        let token = self.make_token(splice_start, splice_end.clone(), token::Variant::symbol());
+        self.output.push(token);
+        match state {
+            TextState::Inline => self.inline_text_body('\''),
+            TextState::Multiline { indent } =>
+                if !self.text_line(splice_end, indent, true, false) {
+                    self.text_lines(indent, true);
+                },
+        }
+        self.spaces_after_lexeme();
     }
 
     fn mark(&self) -> (Bytes, Offset<'s>) {
@@ -844,6 +1115,8 @@ impl<'s> Lexer<'s> {
 
     fn newline(&mut self) {
         if let Some(token) = self.line_break() {
+            // An interpolated expression cannot span multiple lines.
+            self.splice_stack.clear();
             let mut newlines = self.token_storage.take();
             while let Some(token) = self.line_break() {
                 newlines.push(token.with_variant(token::Variant::newline()));
@@ -946,7 +1219,8 @@ pub fn run(input: &'_ str) -> ParseResult<Vec<Item<'_>>> {
 pub fn build_block_hierarchy(tokens: Vec<Token<'_>>) -> Vec<Item<'_>> {
     let mut stack = vec![];
     let mut out: Vec<Item<'_>> = vec![];
-    for token in tokens {
+    let mut tokens = tokens.into_iter().peekable();
+    while let Some(token) = tokens.next() {
         match token.variant {
             token::Variant::BlockStart(_) => stack.push(mem::take(&mut out)),
             token::Variant::BlockEnd(_) => {
@@ -954,6 +1228,15 @@ pub fn build_block_hierarchy(tokens: Vec<Token<'_>>) -> Vec<Item<'_>> {
                 let block = mem::replace(&mut out, new_out);
                 out.push(Item::Block(block));
             }
+            token::Variant::NumberBase(base) => {
+                let base = Some(token.with_variant(base));
+                let integer = next_token_if(&mut tokens, digits_variant).unwrap_or_default();
+                out.push(number_literal(base, integer, &mut tokens).into());
+            }
+            token::Variant::Digits(digits) => {
+                let integer = token.with_variant(digits);
+                out.push(number_literal(None, integer, &mut tokens).into());
+            }
             _ => out.push(token.into()),
         }
     }
@@ -963,6 +1246,49 @@ pub fn build_block_hierarchy(tokens: Vec<Token<'_>>) -> Vec<Item<'_>> {
     out
 }
 
+/// Build a numeric literal tree out of its already consumed base and integer part, consuming the
+/// tokens of the optional fractional and exponent parts from the input.
+fn number_literal<'s>(
+    base: Option<token::NumberBase<'s>>,
+    integer: token::Digits<'s>,
+    tokens: &mut iter::Peekable<impl Iterator<Item = Token<'s>>>,
+) -> Tree<'s> {
+    let decimal_point = |variant| match variant {
+        token::Variant::DecimalPoint(dot) => Some(dot),
+        _ => None,
+    };
+    let exponent = |variant| match variant {
+        token::Variant::Exponent(marker) => Some(marker),
+        _ => None,
+    };
+    let fractional_digits = next_token_if(tokens, decimal_point).map(|dot| {
+        let digits = next_token_if(tokens, digits_variant).unwrap_or_default();
+        tree::FractionalDigits { dot, digits }
+    });
+    let exponent = next_token_if(tokens, exponent).map(|marker| {
+        let digits = next_token_if(tokens, digits_variant).unwrap_or_default();
+        tree::NumberExponent { marker, digits }
+    });
+    tree::number(base, integer, fractional_digits, exponent)
+}
+
+/// Select the [`Digits`] variant of a token.
+fn digits_variant(variant: token::Variant) -> Option<token::variant::Digits> {
+    match variant {
+        token::Variant::Digits(digits) => Some(digits),
+        _ => None,
+    }
+}
+
+/// Consume the next token if its variant is accepted by the provided function.
+fn next_token_if<'s, T>(
+    tokens: &mut iter::Peekable<impl Iterator<Item = Token<'s>>>,
+    f: impl Fn(token::Variant) -> Option<T>,
+) -> Option<Token<'s, T>> {
+    let variant = f(tokens.peek()?.variant)?;
+    tokens.next().map(|token| token.with_variant(variant))
+}
+
 
 
 // =============
@@ -994,8 +1320,7 @@ pub mod test {
 
     /// Constructor.
     pub fn operator_<'s>(left_offset: &'s str, code: &'s str) -> Token<'s> {
-        let (binary, unary) = compute_precedence(code);
-        Token(left_offset, code, token::Variant::operator(binary, unary, code == ":", code != ","))
+        Token(left_offset, code, token::Variant::Operator(analyze_operator(code)))
     }
 }
 
@@ -1103,7 +1428,22 @@ mod tests {
 
     #[test]
     fn test_numeric_literal() {
-        test_lexer("10", vec![number_("", "10")]);
+        let hexadecimal = Some(token::Base::Hexadecimal);
+        test_lexer_many(vec![
+            ("10", vec![digits_("", "10", None)]),
+            ("1_000_000", vec![digits_("", "1_000_000", None)]),
+            ("16_000", vec![digits_("", "16_000", None)]),
+            ("0x1F", vec![number_base_("", "0x"), digits_("", "1F", hexadecimal)]),
+            ("16_ff", vec![number_base_("", "16_"), digits_("", "ff", hexadecimal)]),
+            ("3.14e-2", vec![
+                digits_("", "3", None),
+                decimal_point_("", "."),
+                digits_("", "14", None),
+                exponent_("", "e-"),
+                digits_("", "2", None),
+            ]),
+            ("1.x", vec![digits_("", "1", None), operator_("", "."), ident_("", "x")]),
+        ]);
     }
 
     #[test]
@@ -1142,10 +1482,52 @@ mod tests {
 
     #[test]
     fn test_case_operators() {
-        test_lexer_many(lexer_case_operators(&["+", "-", "=", "==", "===", ":", ","]));
-        let (_, unary) = compute_precedence("-");
-        let unary_minus = Token("", "-", token::Variant::operator(None, unary, false, true));
+        test_lexer_many(lexer_case_operators(&["+", "-", "=", "==", "===", ":", ",", "<=", "!="]));
+        let unary = analyze_operator("-").unary_prefix_precedence;
+        let unary_minus = Token("", "-", token::Variant::operator(None, unary, false, true, false));
         test_lexer_many(vec![("+-", vec![operator_("", "+"), unary_minus])]);
+        test_lexer_many(vec![("+=", vec![Token("", "+=", token::Variant::modifier())])]);
+    }
+
+    #[test]
+    fn test_case_annotations() {
+        let annotation =
+            |left_offset| Token(left_offset, "@", token::Variant::annotation_operator());
+        test_lexer_many(vec![
+            ("@Tail_Call go", vec![annotation(""), ident_("", "Tail_Call"), ident_(" ", "go")]),
+            ("f @x", vec![ident_("", "f"), annotation(" "), ident_("", "x")]),
+            ("f@x", vec![ident_("", "f"), operator_("", "@"), ident_("", "x")]),
+            ("f @ x", vec![ident_("", "f"), operator_(" ", "@"), ident_(" ", "x")]),
+        ]);
+    }
+
+    #[test]
+    fn test_case_text_splice() {
+        test_lexer_many(vec![
+            ("'a `b` c'", vec![
+                text_start_("", "'"),
+                text_section_("", "a "),
+                symbol_("", "`"),
+                ident_("", "b"),
+                symbol_("", "`"),
+                text_section_("", " c"),
+                text_end_("", "'"),
+            ]),
+            ("'` x + 1 `'", vec![
+                text_start_("", "'"),
+                symbol_("", "`"),
+                ident_(" ", "x"),
+                operator_(" ", "+"),
+                digits_(" ", "1", None),
+                symbol_(" ", "`"),
+                text_end_("", "'"),
+            ]),
+            ("\"a `b`\"", vec![
+                text_start_("", "\""),
+                text_section_("", "a `b`"),
+                text_end_("", "\""),
+            ]),
+        ]);
     }
 
     /// Based on https://www.cl.cam.ac.uk/~mgk25/ucs/examples/UTF-8-test.txt.
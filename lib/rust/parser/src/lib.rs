@@ -109,6 +109,7 @@ use crate::prelude::*;
 // === Export ===
 // ==============
 
+pub mod incremental;
 pub mod lexer;
 pub mod macros;
 pub mod metadata;
@@ -364,4 +365,43 @@ mod benches {
             parser.run(&str);
         });
     }
+
+    /// A module of 5000 lines, and an edit of a line in its middle.
+    fn module_and_edit() -> (String, incremental::Edit<'static>) {
+        let methods = 1_000;
+        let mut str = String::new();
+        for i in 0..methods {
+            let method =
+                format!("method_{i} a b =\n    x = a + b * {i}\n    y = x - 1\n    y.foo\n\n");
+            str.push_str(&method);
+        }
+        str.push_str("main = method_0 1 2\n");
+        let start = str.find("* 500").unwrap() + 2;
+        let edit = incremental::Edit::new(Bytes(start)..Bytes(start + 3), "5000");
+        (str, edit)
+    }
+
+    #[bench]
+    fn bench_reparse_full(bencher: &mut Bencher) {
+        let (str, edit) = module_and_edit();
+        let str = edit.apply(&str);
+        let parser = Parser::new();
+        bencher.bytes = str.len() as u64;
+        bencher.iter(move || {
+            parser.run(&str);
+        });
+    }
+
+    #[bench]
+    fn bench_reparse_incremental(bencher: &mut Bencher) {
+        let (str, edit) = module_and_edit();
+        let new_str = edit.apply(&str);
+        let parser = Parser::new();
+        let tree = parser.run(&str);
+        bencher.bytes = new_str.len() as u64;
+        // The measured time includes cloning the previous tree, which is consumed by the reparse.
+        bencher.iter(|| {
+            parser.reparse(tree.clone(), &edit, &new_str);
+        });
+    }
 }
@@ -20,6 +20,11 @@ pub fn all() -> resolver::SegmentMap<'static> {
     register_export_macros(&mut macro_map);
     macro_map.register(group());
     macro_map.register(type_def());
+    macro_map.register(case());
+    macro_map.register(lambda());
+    macro_map.register(unclosed_lambda());
+    macro_map.register(splice());
+    macro_map.register(unclosed_splice());
     macro_map
 }
 
@@ -121,6 +126,197 @@ pub fn if_then<'s>() -> Definition<'s> {
     crate::macro_definition! {("if", everything(), "then", everything())}
 }
 
+/// Case expression macro definition.
+pub fn case<'s>() -> Definition<'s> {
+    crate::macro_definition! {("case", everything(), "of", everything()) case_body}
+}
+
+fn case_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
+    use operator::resolve_operator_precedence_if_non_empty;
+    use syntax::tree::*;
+    let (of_segment, mut segments) = segments.pop();
+    let case_segment = segments.pop().unwrap();
+    let keyword = case_segment.header;
+    let expression = resolve_operator_precedence_if_non_empty(case_segment.result.tokens());
+    let of = of_segment.header;
+    let mut body = of_segment.result.tokens();
+    let arms = match body.pop() {
+        Some(syntax::Item::Block(items)) if body.is_empty() => block::lines(items)
+            .map(|block::Line { newline, expression }| {
+                let arm = expression.map(to_case_arm);
+                CaseLine { newline, arm }
+            })
+            .collect(),
+        last => {
+            body.extend(last);
+            let arm = resolve_operator_precedence_if_non_empty(body).map(to_case_arm);
+            arm.map(|arm| CaseLine { newline: default(), arm: Some(arm) }).into_iter().collect()
+        }
+    };
+    let no_expression = expression.is_none();
+    let no_arms = arms.iter().all(|line| line.arm.is_none());
+    let result = Tree::case(keyword, expression, of, arms);
+    if no_expression {
+        result.with_error("Expected an expression after the `case` keyword.")
+    } else if no_arms {
+        result.with_error("Expected at least one arm after the `of` keyword.")
+    } else {
+        result
+    }
+}
+
+/// Interpret the given expression as an arm of a `case` expression. If it is not of the form
+/// `pattern -> expression`, the result will contain the expression with an error attached.
+fn to_case_arm(expression: syntax::Tree<'_>) -> syntax::tree::CaseArm<'_> {
+    use syntax::tree::*;
+    match expression {
+        Tree { variant: box Variant::Lambda(lambda), span }
+            if lambda.operator.is_none()
+                && lambda.arrow.is_some()
+                && lambda.arguments.len() == 1
+                && lambda.arguments[0].default_value.is_none() =>
+        {
+            let Lambda { mut arguments, arrow, body, .. } = lambda;
+            let ArgumentDefinition { mut pattern, .. } = arguments.pop().unwrap();
+            let mut left_offset = span.left_offset;
+            left_offset += &pattern.span.left_offset;
+            pattern.span.left_offset = left_offset;
+            CaseArm { pattern: Some(pattern), arrow, expression: body }
+        }
+        expression => {
+            let error = match arrow_operands(&expression) {
+                Some((false, false)) => "Expected a pattern and an expression in case arm.",
+                Some((false, true)) => "Expected a pattern before `->` in case arm.",
+                Some((true, false)) => "Expected an expression after `->` in case arm.",
+                _ => "Expected `->` in case arm.",
+            };
+            let expression = Some(expression.with_error(error));
+            CaseArm { pattern: None, arrow: None, expression }
+        }
+    }
+}
+
+/// If the given expression is an application of the `->` operator with a missing operand, return
+/// whether its left and right operands are present.
+fn arrow_operands(expression: &syntax::Tree) -> Option<(bool, bool)> {
+    use syntax::tree::*;
+    let expression = match &*expression.variant {
+        Variant::OprSectionBoundary(OprSectionBoundary { ast }) => ast,
+        _ => expression,
+    };
+    match &*expression.variant {
+        Variant::OprApp(OprApp { lhs, opr: Ok(opr), rhs }) if opr.code == "->" =>
+            Some((lhs.is_some(), rhs.is_some())),
+        _ => None,
+    }
+}
+
+/// Lambda macro definition, like `\x y=1 -> x + y`. The single-argument form, `x -> x + 1`, is
+/// not a macro; it is produced when the `->` operator is applied.
+pub fn lambda<'s>() -> Definition<'s> {
+    crate::macro_definition! {("\\", everything(), "->", everything()) lambda_body}
+}
+
+/// Definition of the lambda macro whose `->` token is missing.
+pub fn unclosed_lambda<'s>() -> Definition<'s> {
+    crate::macro_definition! {("\\", everything()) lambda_body}
+}
+
+fn lambda_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
+    use operator::resolve_operator_precedence_if_non_empty;
+    use syntax::tree::*;
+    let (last, mut segments) = segments.pop();
+    let (segment, arrow, body) = match segments.pop() {
+        Some(segment) => {
+            let arrow = into_operator(last.header);
+            let body = resolve_operator_precedence_if_non_empty(last.result.tokens());
+            (segment, Some(arrow), body.map(block_to_body))
+        }
+        None => (last, None, None),
+    };
+    let operator = Some(into_operator(segment.header));
+    let arguments = resolve_operator_precedence_if_non_empty(segment.result.tokens());
+    let arguments = arguments.map(to_argument_definitions).unwrap_or_default();
+    let no_arguments = arguments.is_empty();
+    let no_arrow = arrow.is_none();
+    let no_body = body.is_none();
+    let result = Tree::lambda(operator, arguments, arrow, body);
+    if no_arguments {
+        result.with_error("Expected at least one argument after `\\`.")
+    } else if no_arrow {
+        result.with_error("Expected `->` after the lambda arguments.")
+    } else if no_body {
+        result.with_error("Expected an expression after `->`.")
+    } else {
+        result
+    }
+}
+
+/// Interpret an argument block with no preceding expression as a body block.
+fn block_to_body(tree: syntax::Tree<'_>) -> syntax::Tree<'_> {
+    use syntax::tree::*;
+    match tree {
+        Tree { variant: box Variant::ArgumentBlockApplication(application), span }
+            if application.lhs.is_none() =>
+        {
+            let mut body = block::body_from_lines(application.arguments);
+            body.span.left_offset = span.left_offset;
+            body
+        }
+        tree => tree,
+    }
+}
+
+/// Split the application of patterns to each other into a sequence of lambda arguments.
+fn to_argument_definitions(
+    expression: syntax::Tree<'_>,
+) -> Vec<syntax::tree::ArgumentDefinition<'_>> {
+    use syntax::tree::*;
+    let mut patterns = vec![];
+    let mut left_offset = crate::source::span::Offset::default();
+    let mut expression = expression;
+    loop {
+        match expression {
+            Tree { variant: box Variant::App(App { func, arg }), span } => {
+                left_offset += span.left_offset;
+                patterns.push(arg);
+                expression = func;
+            }
+            mut first => {
+                left_offset += &first.span.left_offset;
+                first.span.left_offset = left_offset;
+                patterns.push(first);
+                break;
+            }
+        }
+    }
+    patterns.into_iter().rev().map(to_argument_definition).collect()
+}
+
+/// Interpret the given pattern as a lambda argument, which has a default value if the pattern is of
+/// the form `name=value`.
+fn to_argument_definition(pattern: syntax::Tree<'_>) -> syntax::tree::ArgumentDefinition<'_> {
+    use syntax::tree::*;
+    match pattern {
+        Tree {
+            variant:
+                box Variant::OprApp(OprApp {
+                    lhs: Some(mut pattern),
+                    opr: Ok(equals),
+                    rhs: Some(expression),
+                }),
+            span,
+        } if equals.code == "=" => {
+            let mut left_offset = span.left_offset;
+            left_offset += &pattern.span.left_offset;
+            pattern.span.left_offset = left_offset;
+            let default_value = Some(ArgumentDefault { equals, expression });
+            ArgumentDefinition { pattern, default_value }
+        }
+        pattern => ArgumentDefinition { pattern, default_value: None },
+    }
+}
+
 /// Group macro definition.
 pub fn group<'s>() -> Definition<'s> {
     crate::macro_definition! {("(", everything(), ")", nothing()) group_body}
@@ -128,22 +324,53 @@ pub fn group<'s>() -> Definition<'s> {
 
 fn group_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
     use operator::resolve_operator_precedence_if_non_empty;
-    use syntax::token;
-    macro_rules! into_symbol {
-        ($token:expr) => {{
-            let token::Token { left_offset, code, .. } = $token;
-            token::symbol(left_offset, code)
-        }};
-    }
     let (close, mut segments) = segments.pop();
-    let close = into_symbol!(close.header);
+    let close = into_symbol(close.header);
     let segment = segments.pop().unwrap();
-    let open = into_symbol!(segment.header);
+    let open = into_symbol(segment.header);
     let body = segment.result.tokens();
     let body = resolve_operator_precedence_if_non_empty(body);
     syntax::Tree::group(open, body, close)
 }
 
+fn into_symbol(token: syntax::Token) -> syntax::token::Symbol {
+    let syntax::token::Token { left_offset, code, .. } = token;
+    syntax::token::symbol(left_offset, code)
+}
+
+fn into_operator(token: syntax::Token) -> syntax::token::Operator {
+    let variant = token.variant;
+    match variant {
+        syntax::token::Variant::Operator(operator) => token.with_variant(operator),
+        _ => unreachable!(),
+    }
+}
+
+/// Text literal interpolation macro definition, like `` `name` `` in `'Hello `name`!'`.
+pub fn splice<'s>() -> Definition<'s> {
+    crate::macro_definition! {("`", everything(), "`", nothing()) splice_body}
+}
+
+/// Definition of the text literal interpolation macro whose closing backtick is missing.
+pub fn unclosed_splice<'s>() -> Definition<'s> {
+    crate::macro_definition! {("`", everything()) splice_body}
+}
+
+/// Build a fragment of a text literal containing only the interpolated expression. The fragment is
+/// merged with the surrounding text sections when the literal's elements are applied to each other.
+fn splice_body(segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree {
+    use operator::resolve_operator_precedence_if_non_empty;
+    let (last, mut segments) = segments.pop();
+    let (segment, close) = match segments.pop() {
+        Some(segment) => (segment, Some(into_symbol(last.header))),
+        None => (last, None),
+    };
+    let open = into_symbol(segment.header);
+    let expression = resolve_operator_precedence_if_non_empty(segment.result.tokens());
+    let splice = syntax::tree::TextElement::Splice { open, expression, close };
+    syntax::Tree::text_literal(default(), vec![splice], default(), default())
+}
+
 /// New type definition macro definition.
 pub fn type_def<'s>() -> Definition<'s> {
     use pattern::*;
@@ -166,10 +393,14 @@ fn type_def_body(matched_segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree
     let segment = matched_segments.pop().0;
     let match_tree = segment.result.into_var_map();
     let mut v = match_tree.view();
-    let name = v.query("name").map(|name| name[0].clone()).unwrap_or_default();
+    let name = v.query("name");
+    let params = v.nested().query("param");
+    let statements = v.query("statements");
+    let scope_error =
+        [&name, &params, &statements].into_iter().find_map(|r| r.as_ref().err().cloned());
+    let name = name.ok().flatten().map(|name| name[0].clone()).unwrap_or_default();
     let name = operator::resolve_operator_precedence_if_non_empty(name);
-    let no_params = [];
-    let params = v.nested().query("param").unwrap_or(&no_params);
+    let params = params.ok().flatten().unwrap_or_default();
     let params = params
         .iter()
         .map(|tokens| {
@@ -178,7 +409,7 @@ fn type_def_body(matched_segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree
         .collect_vec();
     let mut constructors = default();
     let mut body = default();
-    if let Some(items) = v.query("statements") {
+    if let Some(items) = statements.ok().flatten() {
         let items = items[0].iter().cloned();
         let mut builder = TypeDefBodyBuilder::default();
         for syntax::tree::block::Line { newline, expression } in syntax::tree::block::lines(items) {
@@ -188,13 +419,17 @@ fn type_def_body(matched_segments: NonEmptyVec<MatchedSegment>) -> syntax::Tree
         constructors = constructors_;
         body = body_;
     }
-    match name {
+    let result = match name {
         Some(name) => syntax::Tree::type_def(segment.header, name, params, constructors, body),
         None => {
             let name = syntax::Tree::ident(syntax::token::ident("", "", false, 0));
             let result = syntax::Tree::type_def(segment.header, name, params, constructors, body);
             result.with_error("Expected identifier after `type` keyword.")
         }
+    };
+    match scope_error {
+        Some(error) => result.with_error(error.to_string()),
+        None => result,
     }
 }
 
@@ -238,7 +473,7 @@ impl<'s> TypeDefBodyBuilder<'s> {
     pub fn finish(
         self,
     ) -> (Vec<syntax::tree::TypeConstructorLine<'s>>, Vec<syntax::tree::block::Line<'s>>) {
-        (self.constructors, self.body)
+        (self.constructors, syntax::tree::block::attach_annotations(self.body))
     }
 
     /// Interpret the given expression as an `TypeConstructorDef`, if its syntax is compatible.
@@ -303,42 +303,65 @@ impl<'t, 's, V: Validator> VarMapView<'t, 's, V> {
 }
 
 impl<'t, 's, V: Validator> VarMapView<'t, 's, V> {
-    /// Query for a variable.
-    pub fn query(&mut self, name: &str) -> Option<&'t [Vec<syntax::Item<'s>>]> {
-        self.tree.and_then(|t| {
-            t.map.get(name).map(|entry| {
-                match &self.resolved_validator {
-                    Some(validator) =>
-                        if !validator.check(name) {
-                            todo!("Report nice error that the name does not belong to the scope.")
-                        },
-                    None => {
-                        let resolved_validator = entry.validator.clone_ref();
-                        if let Some(parent_validator_to_check) = &self.parent_validator_to_check {
-                            let mut ok = false;
-                            let mut validator = resolved_validator.clone();
-                            loop {
-                                if &validator == parent_validator_to_check {
-                                    ok = true;
-                                    break;
-                                } else {
-                                    match validator.parent() {
-                                        Some(p) => validator = p,
-                                        None => break,
-                                    }
-                                }
+    /// Query for a variable. Fails if the variable does not belong to the repetition scope of the
+    /// variables queried before.
+    pub fn query(&mut self, name: &str) -> Result<Option<&'t [Vec<syntax::Item<'s>>]>, ScopeError> {
+        let entry = match self.tree.and_then(|t| t.map.get(name)) {
+            Some(entry) => entry,
+            None => return Ok(None),
+        };
+        match &self.resolved_validator {
+            Some(validator) =>
+                if !validator.check(name) {
+                    return Err(ScopeError::NotInScope(name.into()));
+                },
+            None => {
+                let resolved_validator = entry.validator.clone_ref();
+                if let Some(parent_validator_to_check) = &self.parent_validator_to_check {
+                    let mut ok = false;
+                    let mut validator = resolved_validator.clone();
+                    loop {
+                        if &validator == parent_validator_to_check {
+                            ok = true;
+                            break;
+                        } else {
+                            match validator.parent() {
+                                Some(p) => validator = p,
+                                None => break,
                             }
-                            if !ok {
-                                todo!("Report nice error that the name does not belong to the same scope as previous variables.")
-                            }
-                            self.parent_validator_to_check = None;
                         }
-                        self.resolved_validator = Some(resolved_validator);
                     }
+                    if !ok {
+                        return Err(ScopeError::NotInParentScope(name.into()));
+                    }
+                    self.parent_validator_to_check = None;
                 }
-                &entry.tokens[..]
-            })
-        })
+                self.resolved_validator = Some(resolved_validator);
+            }
+        }
+        Ok(Some(&entry.tokens[..]))
+    }
+}
+
+/// An error reported when a variable is used in a repetition scope it does not belong to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[allow(missing_docs)]
+pub enum ScopeError {
+    NotInScope(String),
+    NotInParentScope(String),
+}
+
+impl Display for ScopeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotInScope(name) =>
+                write!(f, "The variable `{name}` does not belong to the current repetition scope."),
+            Self::NotInParentScope(name) => write!(
+                f,
+                "The variable `{name}` does not belong to the same repetition scope as the \
+                previously used variables."
+            ),
+        }
     }
 }
 
@@ -490,23 +490,33 @@ impl<'s> Resolver<'s> {
                 Ok(segment) => mem::swap(&mut not_used_items_of_last_segment, &mut segment.rest),
             }
 
+            let all_matched = pattern_matched_segments
+                .iter()
+                .all(|(_, result)| matches!(result, Ok(result) if result.rest.is_empty()));
+            if !all_matched {
+                let segments = pattern_matched_segments.mapped(|(header, match_result)| {
+                    let items = match match_result {
+                        Ok(result) =>
+                            result.matched.tokens().into_iter().chain(result.rest).collect(),
+                        Err(unmatched_items) => unmatched_items,
+                    };
+                    (header, items)
+                });
+                let error = "Unexpected tokens in macro invocation.";
+                return (invalid_macro_invocation(segments, error), not_used_items_of_last_segment);
+            }
+
             let pattern_matched_segments =
                 pattern_matched_segments.mapped(|(header, match_result)| match match_result {
-                    Ok(result) => {
-                        if !result.rest.is_empty() {
-                            todo!("Mark unmatched tokens as unexpected.");
-                        }
-                        pattern::MatchedSegment::new(header, result.matched)
-                    }
-                    Err(_unmatched_items) => todo!("Mark unmatched tokens as unexpected."),
+                    Ok(result) => pattern::MatchedSegment::new(header, result.matched),
+                    Err(_) => unreachable!(),
                 });
 
             let out = (macro_def.body)(pattern_matched_segments);
             (out, not_used_items_of_last_segment)
         } else {
-            let message = format!("Macro was not matched with any known macro definition.\nResolved segments: {resolved_segments:?}");
-            let error = syntax::tree::Error::new(message);
-            (syntax::tree::Tree::invalid(error, default()), default())
+            let error = "Macro was not matched with any known macro definition.";
+            (invalid_macro_invocation(resolved_segments, error), default())
         }
     }
 
@@ -536,3 +546,18 @@ impl<'s> Resolver<'s> {
         new_section_tree
     }
 }
+
+/// Build an [`syntax::tree::Invalid`] node reporting the provided error for a macro invocation that
+/// could not be resolved. The node contains all the segments of the invocation, so that no part of
+/// the source code is lost.
+fn invalid_macro_invocation<'s>(
+    segments: NonEmptyVec<(Token<'s>, VecDeque<syntax::Item<'s>>)>,
+    error: &'static str,
+) -> syntax::Tree<'s> {
+    let items = segments
+        .into_iter()
+        .flat_map(|(header, items)| iter::once(syntax::Item::from(header)).chain(items));
+    let tree = syntax::operator::resolve_operator_precedence_if_non_empty(items);
+    // This can't fail: every segment contains at least its header.
+    tree.unwrap().with_error(error)
+}
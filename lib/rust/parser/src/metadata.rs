@@ -1,29 +1,213 @@
 //! Data associated with a syntax tree.
 //!
 //! This data is currently represented as two lines containing one JSON value each, placed at the
-//! end of a file after a line containing exactly the text "#### METADATA ####".
+//! end of a file after a line containing exactly the text "#### METADATA ####". The first line is
+//! the ID map, attaching stable IDs to the spans of the code; the second line contains the
+//! metadata of the IDE, such as the positions of the nodes.
 
+use crate::prelude::*;
+
+use serde::Deserialize;
+use serde::Serialize;
 use std::collections::BTreeMap;
 use std::str::FromStr;
 use uuid::Uuid;
 
 
 
+// =================
+// === Constants ===
+// =================
+
+/// The line separating the code from the metadata.
+pub const METADATA_TAG: &str = "#### METADATA ####";
+
+
+
 // ================
 // === Metadata ===
 // ================
 
 /// Attaches stable IDs to AST nodes, and associates properties with them.
-#[derive(Debug)]
+#[derive(Clone, Debug, Default, PartialEq)]
 pub struct Metadata {
-    id_map: BTreeMap<Location, Uuid>,
+    id_map:    BTreeMap<Location, Uuid>,
+    /// Metadata used within the IDE.
+    pub ide:   IdeMetadata,
+    /// Metadata of other tools, preserved when the metadata is written.
+    pub other: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Metadata {
     /// Return the UUID associated with the node identified by offset/length, if any is found.
     pub fn get_uuid(&self, index: usize, size: usize) -> Option<Uuid> {
-        let loc = Location { index: Number { value: index }, size: Number { value: size } };
-        Some(*self.id_map.get(&loc)?)
+        Some(*self.id_map.get(&Location::new(index, size))?)
+    }
+
+    /// Associate a UUID with the node identified by offset/length. Return the UUID previously
+    /// associated with the node, if any.
+    pub fn set_uuid(&mut self, index: usize, size: usize, id: Uuid) -> Option<Uuid> {
+        self.id_map.insert(Location::new(index, size), id)
+    }
+
+    /// Remove the UUID associated with the node identified by offset/length, returning it.
+    pub fn remove_uuid(&mut self, index: usize, size: usize) -> Option<Uuid> {
+        self.id_map.remove(&Location::new(index, size))
+    }
+
+    /// Iterate over the offset, length, and UUID of every node in the ID map.
+    pub fn uuids(&self) -> impl Iterator<Item = (usize, usize, Uuid)> + '_ {
+        self.id_map.iter().map(|(loc, id)| (loc.index.value, loc.size.value, *id))
+    }
+
+    /// Return the given code followed by the metadata section describing this metadata. If the
+    /// code does not end with a newline, one is inserted before the metadata tag.
+    pub fn write(&self, code: &str) -> String {
+        let newline = if code.is_empty() || code.ends_with('\n') { "" } else { "\n" };
+        format!("{code}{newline}{METADATA_TAG}\n{}", MetadataFormat::from(self))
+    }
+}
+
+
+// === IDE Metadata ===
+
+/// The part of the metadata owned by the IDE.
+///
+/// Like the IDE itself, decoding is lenient: a malformed entry is replaced with its default value
+/// (or, for the entries of `node`, dropped), rather than failing the whole metadata section.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct IdeMetadata {
+    /// The metadata of the nodes, by the IDs of their expressions.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(deserialize_with = "node_map::deserialize")]
+    pub node:    BTreeMap<Uuid, NodeMetadata>,
+    /// The metadata of the imports, by the IDs of the imports.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub import:  BTreeMap<String, ImportMetadata>,
+    /// The project metadata. This is stored only in the main module's metadata; its format is
+    /// defined by the IDE.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<serde_json::Value>,
+    /// Fields not recognized by this model; they are preserved when the metadata is written.
+    #[serde(flatten)]
+    pub other:   serde_json::Map<String, serde_json::Value>,
+}
+
+/// The metadata of a node.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct NodeMetadata {
+    /// The position of the node in the graph editor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub position:        Option<Position>,
+    /// The method the user intends the node to call, e.g. as picked in the searcher.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub intended_method: Option<MethodId>,
+    /// Information about the upload of a file the node reads, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uploading_file:  Option<serde_json::Value>,
+    /// Whether the node is selected in the graph editor.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub selected:        bool,
+    /// The visualization enabled for the node, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(deserialize_with = "enso_prelude::deserialize_or_default")]
+    pub visualization:   Option<Visualization>,
+    /// Fields not recognized by this model; they are preserved when the metadata is written.
+    #[serde(flatten)]
+    pub other:           serde_json::Map<String, serde_json::Value>,
+}
+
+/// The position of a node in the graph editor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Position {
+    /// The x and y coordinates.
+    pub vector: [f32; 2],
+}
+
+/// Identifies a method.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[allow(missing_docs)]
+pub struct MethodId {
+    pub module:          String,
+    pub defined_on_type: String,
+    pub name:            String,
+}
+
+/// A visualization attached to a node.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Visualization {
+    /// The name of the visualization, unique within its project.
+    #[serde(with = "visualization_name")]
+    pub name:    String,
+    /// The project defining the visualization.
+    pub project: VisualizationProject,
+}
+
+/// The project defining a visualization.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum VisualizationProject {
+    /// A visualization embedded in the IDE.
+    Builtin,
+    /// The project the module belongs to.
+    CurrentProject,
+    /// A library the project depends on, identified by its qualified name.
+    Library(String),
+}
+
+/// The metadata of an import.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportMetadata {
+    /// Whether the import was added by the IDE temporarily, e.g. while the user edits a node.
+    #[serde(default)]
+    pub is_temporary: bool,
+}
+
+/// The IDE stores visualization names wrapped in two levels of `content` objects.
+mod visualization_name {
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use serde::Serialize;
+    use serde::Serializer;
+
+    #[derive(Serialize, Deserialize)]
+    struct Content<T> {
+        content: T,
+    }
+
+    pub fn serialize<S: Serializer>(name: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        Content { content: Content { content: name } }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        let name: Content<Content<String>> = Deserialize::deserialize(deserializer)?;
+        Ok(name.content.content)
+    }
+}
+
+/// The metadata of each node is decoded separately, so that a malformed entry is dropped without
+/// affecting the metadata of the other nodes.
+mod node_map {
+    use super::NodeMetadata;
+    use serde::Deserialize;
+    use serde::Deserializer;
+    use std::collections::BTreeMap;
+    use uuid::Uuid;
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<BTreeMap<Uuid, NodeMetadata>, D::Error> {
+        let entries = match serde_json::Value::deserialize(deserializer)? {
+            serde_json::Value::Object(entries) => entries,
+            _ => return Ok(BTreeMap::new()),
+        };
+        let decode = |(id, node): (String, serde_json::Value)| {
+            Some((id.parse().ok()?, serde_json::from_value(node).ok()?))
+        };
+        Ok(entries.into_iter().filter_map(decode).collect())
     }
 }
 
@@ -33,20 +217,40 @@ impl Metadata {
 /// Corresponds to the JSON structure used to store `Metadata`.
 #[derive(Debug)]
 struct MetadataFormat {
-    id_map: Vec<(Location, Uuid)>,
+    id_map:   Vec<(Location, Uuid)>,
+    sections: Sections,
+}
+
+/// The JSON object of the second line of the metadata section.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Sections {
+    #[serde(default, deserialize_with = "enso_prelude::deserialize_or_default")]
+    ide:   IdeMetadata,
+    /// Metadata of tools other than the IDE.
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
 }
 
 impl From<MetadataFormat> for Metadata {
     fn from(metadata: MetadataFormat) -> Self {
         let id_map = metadata.id_map.into_iter().collect();
-        Self { id_map }
+        let Sections { ide, other } = metadata.sections;
+        Self { id_map, ide, other }
+    }
+}
+
+impl From<&Metadata> for MetadataFormat {
+    fn from(metadata: &Metadata) -> Self {
+        let id_map = metadata.id_map.iter().map(|(loc, id)| (*loc, *id)).collect();
+        let sections = Sections { ide: metadata.ide.clone(), other: metadata.other.clone() };
+        Self { id_map, sections }
     }
 }
 
 /// Given source code, if a metadata section is found: Attempt to parse it; return the result, and
 /// the non-metadata portion of the input.
 pub fn parse(input: &str) -> Option<(Result, &str)> {
-    let (code, metadata) = input.rsplit_once("#### METADATA ####\n")?;
+    let (code, metadata) = input.rsplit_once(&format!("{METADATA_TAG}\n"))?;
     Some((metadata.parse().map(|data: MetadataFormat| data.into()), code))
 }
 
@@ -57,8 +261,26 @@ impl FromStr for MetadataFormat {
     type Err = String;
     fn from_str(s: &str) -> Result<MetadataFormat> {
         let mut lines = s.lines();
-        let id_map = serde_json::from_str(lines.next().unwrap()).unwrap();
-        Ok(MetadataFormat { id_map })
+        let id_map = lines.next().ok_or_else(|| "Missing ID map.".to_string())?;
+        let id_map = serde_json::from_str(id_map).map_err(|e| format!("Invalid ID map: {e}"))?;
+        // Files written by tools unaware of the IDE may lack the second line.
+        let sections = match lines.next() {
+            Some(line) =>
+                serde_json::from_str(line).map_err(|e| format!("Invalid IDE metadata: {e}"))?,
+            None => default(),
+        };
+        if let Some(line) = lines.find(|line| !line.trim().is_empty()) {
+            return Err(format!("Unexpected line after metadata: {line:?}"));
+        }
+        Ok(MetadataFormat { id_map, sections })
+    }
+}
+
+impl Display for MetadataFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let id_map = serde_json::to_string(&self.id_map).map_err(|_| fmt::Error)?;
+        let sections = serde_json::to_string(&self.sections).map_err(|_| fmt::Error)?;
+        write!(f, "{id_map}\n{sections}")
     }
 }
 
@@ -74,6 +296,12 @@ struct Location {
     size:  Number,
 }
 
+impl Location {
+    fn new(index: usize, size: usize) -> Self {
+        Self { index: Number { value: index }, size: Number { value: size } }
+    }
+}
+
 #[derive(Debug, Copy, Clone, serde::Serialize, serde::Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 struct Number {
     value: usize,
@@ -235,6 +235,9 @@ pub struct RefMut<'s, 'a> {
     pub left_offset: &'a mut Offset<'s>,
     /// The length of the code, excluding [`left_offset`].
     pub code_length: Bytes,
+    /// The code of the token, if this is a span of a token. The code of an AST node is not
+    /// available, as it is stored in the tokens the node contains.
+    pub code:        Option<&'a mut Code<'s>>,
 }
 
 
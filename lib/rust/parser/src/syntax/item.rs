@@ -46,7 +46,9 @@ impl<'s> Item<'s> {
         match self {
             Item::Token(token) => match token.variant {
                 token::Variant::Ident(ident) => Tree::ident(token.with_variant(ident)),
-                token::Variant::Number(number) => Tree::number(token.with_variant(number)),
+                token::Variant::Wildcard(wildcard) => Tree::wildcard(token.with_variant(wildcard)),
+                token::Variant::Digits(digits) =>
+                    tree::number(None, token.with_variant(digits), None, None),
                 token::Variant::TextStart(open) => Tree::text_literal(
                     Some(token.with_variant(open)),
                     default(),
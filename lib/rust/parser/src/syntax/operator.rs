@@ -40,6 +40,14 @@ pub fn resolve_operator_precedence_if_non_empty<'s>(
     items: impl IntoIterator<Item = syntax::Item<'s>>,
 ) -> Option<syntax::Tree<'s>> {
     type Tokens<'s> = Vec<syntax::Item<'s>>;
+    let mut items = items.into_iter().collect_vec();
+    let annotation = (0..items.len()).find_map(|i| Some((i, as_annotation(&items[i..])?)));
+    if let Some((start, (token, annotation))) = annotation {
+        // An annotation applies to the whole remainder of the expression.
+        let rest = items.split_off(start).into_iter().skip(2);
+        let expression = resolve_operator_precedence_if_non_empty(rest);
+        items.push(syntax::tree::annotation(token, annotation, expression).into());
+    }
     let mut flattened: Tokens<'s> = default();
     let mut no_space_group: Tokens<'s> = default();
     let process_no_space_group = |flattened: &mut Tokens<'s>, no_space_group: &mut Tokens<'s>| {
@@ -73,6 +81,25 @@ pub fn resolve_operator_precedence_if_non_empty<'s>(
     resolve_operator_precedence_internal(flattened, false)
 }
 
+/// If the items start with the `@` token of an annotation followed by the name of the annotation,
+/// return these tokens.
+fn as_annotation<'s>(
+    items: &[syntax::Item<'s>],
+) -> Option<(token::AnnotationOperator<'s>, token::Ident<'s>)> {
+    use syntax::Item;
+    match items {
+        [Item::Token(operator), Item::Token(name), ..] =>
+            match (&operator.variant, &name.variant) {
+                (token::Variant::AnnotationOperator(operator_), token::Variant::Ident(name_)) => {
+                    let operator = operator.clone().with_variant(*operator_);
+                    Some((operator, name.clone().with_variant(*name_)))
+                }
+                _ => None,
+            },
+        _ => None,
+    }
+}
+
 fn resolve_operator_precedence_internal<'s>(
     items: impl IntoIterator<Item = syntax::Item<'s>>,
     nospace: bool,
@@ -172,13 +199,17 @@ impl<'s> ExpressionBuilder<'s> {
     /// Add an operator to the stack; [`reduce`] the stack first, as appropriate for the specified
     /// precedence.
     fn push_operator(&mut self, precedence: token::Precedence, opr: Arity<'s>) {
+        let right_associative = match &opr {
+            Arity::Binary(oprs) => oprs[0].is_right_associative,
+            Arity::Unary(_) => false,
+        };
         let opr = Operator { precedence, opr };
         if self.prev_type != Some(ItemType::Opr) {
             // If the previous item was also an operator, this must be a unary operator following a
             // binary operator; we cannot reduce the stack because the unary operator must be
             // evaluated before the binary operator, regardless of precedence.
             let mut rhs = self.output.pop().map(|rhs| rhs.to_ast());
-            self.reduce(precedence, &mut rhs);
+            self.reduce(precedence, right_associative, &mut rhs);
             if let Some(rhs) = rhs {
                 self.output.push(rhs.into());
             }
@@ -188,10 +219,19 @@ impl<'s> ExpressionBuilder<'s> {
     }
 
     /// Given a starting value, replace it with the result of successively applying to it all
-    /// operators in the `operator_stack` that have precedence greater than or equal to the
-    /// specified value, consuming LHS values from the `output` stack as needed.
-    fn reduce(&mut self, prec: token::Precedence, rhs: &mut Option<syntax::Tree<'s>>) {
-        while let Some(opr) = self.operator_stack.pop_if(|opr| opr.precedence >= prec) {
+    /// operators in the `operator_stack` that have precedence greater than the specified value,
+    /// consuming LHS values from the `output` stack as needed. Operators of equal precedence are
+    /// applied too, unless the operator being added is right-associative.
+    fn reduce(
+        &mut self,
+        prec: token::Precedence,
+        right_associative: bool,
+        rhs: &mut Option<syntax::Tree<'s>>,
+    ) {
+        let binds_tighter = |opr: &Operator| {
+            opr.precedence > prec || (opr.precedence == prec && !right_associative)
+        };
+        while let Some(opr) = self.operator_stack.pop_if(binds_tighter) {
             let rhs_ = rhs.take();
             let ast = match opr.opr {
                 Arity::Unary(opr) => syntax::Tree::unary_opr_app(opr, rhs_),
@@ -213,7 +253,7 @@ impl<'s> ExpressionBuilder<'s> {
         use ItemType::*;
         let mut item =
             (self.prev_type == Some(Ast)).and_option_from(|| self.output.pop().map(|t| t.to_ast()));
-        self.reduce(token::Precedence::minimum(), &mut item);
+        self.reduce(token::Precedence::minimum(), false, &mut item);
         if !self.output.is_empty() {
             panic!(
                 "Internal error. Not all tokens were consumed while constructing the expression."
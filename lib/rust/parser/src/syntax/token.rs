@@ -267,10 +267,17 @@ macro_rules! with_token_definition { ($f:ident ($($args:tt)*)) => { $f! { $($arg
             pub unary_prefix_precedence: Option<Precedence>,
             pub is_type_annotation: bool,
             pub can_form_section: bool,
+            pub is_right_associative: bool,
         },
         Modifier,
+        AnnotationOperator,
         DocComment,
-        Number,
+        NumberBase,
+        Digits {
+            pub base: Option<Base>
+        },
+        DecimalPoint,
+        Exponent,
         TextStart,
         TextEnd,
         TextSection,
@@ -285,6 +292,42 @@ impl Default for Variant {
 }
 
 
+// === Numeric literal properties ===
+
+/// The base of a numeric literal, if it is not decimal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Reflect, Deserialize)]
+pub enum Base {
+    /// Base 2, introduced by `0b` or `2_`.
+    Binary,
+    /// Base 8, introduced by `0o` or `8_`.
+    Octal,
+    /// Base 16, introduced by `0x` or `16_`.
+    Hexadecimal,
+}
+
+impl Base {
+    /// The number of distinct digits in this base.
+    pub fn radix(self) -> u32 {
+        match self {
+            Base::Binary => 2,
+            Base::Octal => 8,
+            Base::Hexadecimal => 16,
+        }
+    }
+}
+
+impl<'s> Digits<'s> {
+    /// Check whether the token consists of digits valid in its base, optionally separated by
+    /// underscores.
+    pub fn is_valid(&self) -> bool {
+        let radix = self.variant.base.map(Base::radix).unwrap_or(10);
+        let mut chars = self.code.chars();
+        chars.next().map_or(false, |first| first.is_digit(radix))
+            && chars.all(|char| char == '_' || char.is_digit(radix))
+    }
+}
+
+
 // === Operator properties ===
 
 /// Value that can be compared to determine which operator will bind more tightly within an
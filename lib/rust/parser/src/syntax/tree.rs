@@ -47,7 +47,11 @@ impl<'s> Debug for Tree<'s> {
         let ellipsis = "...";
         let mut code = self.code();
         if code.len() > max_code_len {
-            code = format!("{}{}", &code[..max_code_len - ellipsis.len()], ellipsis);
+            let mut end = max_code_len - ellipsis.len();
+            while !code.is_char_boundary(end) {
+                end -= 1;
+            }
+            code = format!("{}{}", &code[..end], ellipsis);
         }
         write!(f, "[{}:{}:\"{}\"] ", self.span.left_offset.visible, self.span.code_length, code)?;
         Debug::fmt(&self.variant, f)
@@ -117,9 +121,21 @@ macro_rules! with_ast_definition { ($f:ident ($($args:tt)*)) => { $f! { $($args)
         Ident {
             pub token: token::Ident<'s>,
         },
-        /// A numeric literal, like `10`.
+        /// A wildcard, like `_` in the pattern `Cons _ tail`.
+        Wildcard {
+            pub token: token::Wildcard<'s>,
+        },
+        /// A numeric literal, like `10`, `0x1F`, or `3.14e-2`. If any of its digits is not valid in
+        /// the base of the literal, it is wrapped in an [`Invalid`] node.
         Number {
-            pub token: token::Number<'s>,
+            /// The base prefix, like `0x` or `16_`, present only in non-decimal numbers.
+            pub base: Option<token::NumberBase<'s>>,
+            /// The digits of the integer part.
+            pub integer: token::Digits<'s>,
+            /// The fractional part, like `.14` in `3.14`.
+            pub fractional_digits: Option<FractionalDigits<'s>>,
+            /// The exponent, like `e-2` in `3.14e-2`.
+            pub exponent: Option<NumberExponent<'s>>,
         },
         TextLiteral {
             pub open_quote: Option<token::TextStart<'s>>,
@@ -243,6 +259,59 @@ macro_rules! with_ast_definition { ($f:ident ($($args:tt)*)) => { $f! { $($args)
             #[reflect(rename = "type")]
             pub type_: Tree<'s>,
         },
+        /// A lambda expression, like `x -> x + 1` or `\x y=1 -> x + y`. The first form has
+        /// exactly one argument, which may be an arbitrary pattern; the second form is introduced
+        /// by the `\` operator, and its arguments may have default values.
+        Lambda {
+            /// The `\` operator; it is present only in the multi-argument form.
+            pub operator: Option<token::Operator<'s>>,
+            /// The arguments of the lambda.
+            pub arguments: Vec<ArgumentDefinition<'s>>,
+            /// The `->` token. It is an error for this to be missing.
+            pub arrow: Option<token::Operator<'s>>,
+            /// The body of the lambda. It is an error for this to be empty.
+            pub body: Option<Tree<'s>>,
+        },
+        /// A pattern-matching expression, like `case x of Nothing -> 0`. The arms are either given
+        /// in a block following the `of` keyword, or as a single arm on the same line.
+        Case {
+            /// The `case` keyword.
+            pub keyword: Token<'s>,
+            /// The expression whose value is matched. It is an error for this to be empty.
+            pub expression: Option<Tree<'s>>,
+            /// The `of` keyword.
+            pub of: Token<'s>,
+            /// The arms of the expression.
+            pub arms: Vec<CaseLine<'s>>,
+        },
+        /// An expression annotated with a built-in annotation, like `@Tail_Call go x` or
+        /// `@Builtin_Type` on the line preceding a type definition.
+        AnnotatedBuiltin {
+            /// The `@` token.
+            pub token: token::AnnotationOperator<'s>,
+            /// The name of the annotation; it starts with an uppercase letter.
+            pub annotation: token::Ident<'s>,
+            /// The line breaks between the annotation and the expression, if the annotation
+            /// occupies a line of its own.
+            pub newlines: Vec<token::Newline<'s>>,
+            /// The annotated expression.
+            pub expression: Option<Tree<'s>>,
+        },
+        /// A definition annotated with an argument of the annotation, like
+        /// `@column Widget.Select` on the line preceding a method definition.
+        Annotated {
+            /// The `@` token.
+            pub token: token::AnnotationOperator<'s>,
+            /// The name of the annotation, like the name of the annotated argument.
+            pub annotation: token::Ident<'s>,
+            /// The argument of the annotation.
+            pub argument: Option<Tree<'s>>,
+            /// The line breaks between the annotation and the annotated definition.
+            pub newlines: Vec<token::Newline<'s>>,
+            /// The annotated definition, placed on the first non-empty line following the
+            /// annotation. It is missing if no line follows the annotation.
+            pub expression: Option<Tree<'s>>,
+        },
     }
 }};}
 
@@ -360,6 +429,196 @@ impl<'s> span::Builder<'s> for TypeConstructorDef<'s> {
 }
 
 
+// === Numbers ===
+
+/// The fractional part of a numeric literal, like `.14` in `3.14`.
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+pub struct FractionalDigits<'s> {
+    /// The `.` token.
+    pub dot:    token::DecimalPoint<'s>,
+    /// The digits following the decimal point.
+    pub digits: token::Digits<'s>,
+}
+
+impl<'s> span::Builder<'s> for FractionalDigits<'s> {
+    fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
+        span.add(&mut self.dot).add(&mut self.digits)
+    }
+}
+
+/// The exponent of a numeric literal, like `e-2` in `3.14e-2`.
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+pub struct NumberExponent<'s> {
+    /// The exponent marker, including the sign if present, like `e` or `e-`.
+    pub marker: token::Exponent<'s>,
+    /// The decimal digits of the exponent.
+    pub digits: token::Digits<'s>,
+}
+
+impl<'s> span::Builder<'s> for NumberExponent<'s> {
+    fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
+        span.add(&mut self.marker).add(&mut self.digits)
+    }
+}
+
+/// Build a numeric literal from its parts. If any of the digits is not valid in the base of the
+/// literal, the result is an [`Invalid`] node.
+pub fn number<'s>(
+    base: Option<token::NumberBase<'s>>,
+    integer: token::Digits<'s>,
+    fractional_digits: Option<FractionalDigits<'s>>,
+    exponent: Option<NumberExponent<'s>>,
+) -> Tree<'s> {
+    let valid = integer.is_valid()
+        && fractional_digits.as_ref().map_or(true, |fraction| fraction.digits.is_valid())
+        && exponent.as_ref().map_or(true, |exponent| exponent.digits.is_valid());
+    let tree = Tree::number(base, integer, fractional_digits, exponent);
+    if valid {
+        tree
+    } else {
+        tree.with_error("Invalid digit in numeric literal.")
+    }
+}
+
+
+// === Lambdas ===
+
+/// An argument of a lambda expression, like `x`, `(Pair a b)`, or `y=1`.
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+pub struct ArgumentDefinition<'s> {
+    /// The pattern the argument is bound to.
+    pub pattern:       Tree<'s>,
+    /// The default value of the argument, if any.
+    pub default_value: Option<ArgumentDefault<'s>>,
+}
+
+impl<'s> span::Builder<'s> for ArgumentDefinition<'s> {
+    fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
+        span.add(&mut self.pattern).add(&mut self.default_value)
+    }
+}
+
+/// The default value of an argument, like `=1` in `\x y=1 -> x + y`.
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+pub struct ArgumentDefault<'s> {
+    /// The `=` token.
+    pub equals:     token::Operator<'s>,
+    /// The expression initializing the argument when no value is provided.
+    pub expression: Tree<'s>,
+}
+
+impl<'s> span::Builder<'s> for ArgumentDefault<'s> {
+    fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
+        span.add(&mut self.equals).add(&mut self.expression)
+    }
+}
+
+
+// === Case Expressions ===
+
+/// A line within the body of a `case` expression.
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+pub struct CaseLine<'s> {
+    /// The token beginning the line. It is empty in the one-line form of the expression.
+    pub newline: token::Newline<'s>,
+    /// The arm defined on this line, unless this is an empty line.
+    pub arm:     Option<CaseArm<'s>>,
+}
+
+impl<'s> span::Builder<'s> for CaseLine<'s> {
+    fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
+        span.add(&mut self.newline).add(&mut self.arm)
+    }
+}
+
+impl<'s> From<token::Newline<'s>> for CaseLine<'s> {
+    fn from(newline: token::Newline<'s>) -> Self {
+        Self { newline, arm: None }
+    }
+}
+
+/// An arm of a `case` expression, like `Just x -> x + 1`.
+#[derive(Clone, Debug, Eq, PartialEq, Visitor, Serialize, Reflect, Deserialize)]
+pub struct CaseArm<'s> {
+    /// The pattern the matched value is tested against. It is an error for this to be empty.
+    pub pattern:    Option<Tree<'s>>,
+    /// The `->` token. If it is missing, the line is not a valid arm and its content is stored as
+    /// the (invalid) expression.
+    pub arrow:      Option<token::Operator<'s>>,
+    /// The expression evaluated if the pattern matches; it may be a `BodyBlock`. It is an error
+    /// for this to be empty.
+    pub expression: Option<Tree<'s>>,
+}
+
+impl<'s> span::Builder<'s> for CaseArm<'s> {
+    fn add_to_span(&mut self, span: Span<'s>) -> Span<'s> {
+        span.add(&mut self.pattern).add(&mut self.arrow).add(&mut self.expression)
+    }
+}
+
+
+// === Annotations ===
+
+/// Build an annotation from the `@` token, the name of the annotation, and the expression following
+/// the name on the same line. Annotations with names starting with an uppercase letter are built-in
+/// annotations, which apply to the following expression; the expression following the name of any
+/// other annotation is its argument.
+pub fn annotation<'s>(
+    token: token::AnnotationOperator<'s>,
+    annotation: token::Ident<'s>,
+    expression: Option<Tree<'s>>,
+) -> Tree<'s> {
+    if annotation.code.starts_with(char::is_uppercase) {
+        Tree::annotated_builtin(token, annotation, default(), expression)
+    } else {
+        Tree::annotated(token, annotation, expression, default(), None)
+    }
+}
+
+/// If the tree is an annotation occupying a line of its own, attach it to the expression returned
+/// by `following`, which also returns the line breaks preceding the expression. The function is
+/// called only if the tree is such an annotation.
+pub fn attach_annotation<'s>(
+    tree: &mut Tree<'s>,
+    following: impl FnOnce() -> Option<(Vec<token::Newline<'s>>, Tree<'s>)>,
+) {
+    let mut result = match &mut *tree.variant {
+        Variant::AnnotatedBuiltin(annotated) if annotated.expression.is_none() => {
+            let (newlines, expression) = match following() {
+                Some(following) => following,
+                None => return,
+            };
+            let token = mem::take(&mut annotated.token);
+            let annotation = mem::take(&mut annotated.annotation);
+            Tree::annotated_builtin(token, annotation, newlines, Some(expression))
+        }
+        Variant::Annotated(annotated) if annotated.expression.is_none() => {
+            let (newlines, expression) = match following() {
+                Some(following) => following,
+                None => return,
+            };
+            let token = mem::take(&mut annotated.token);
+            let annotation = mem::take(&mut annotated.annotation);
+            let argument = annotated.argument.take();
+            Tree::annotated(token, annotation, argument, newlines, Some(expression))
+        }
+        _ => return,
+    };
+    result.span.left_offset = mem::take(&mut tree.span.left_offset);
+    *tree = result;
+}
+
+/// Return whether the tree is an annotation occupying a line of its own, which has not been
+/// attached to any expression.
+pub fn is_unattached_annotation(tree: &Tree) -> bool {
+    match &*tree.variant {
+        Variant::AnnotatedBuiltin(annotated) => annotated.expression.is_none(),
+        Variant::Annotated(annotated) => annotated.expression.is_none(),
+        _ => false,
+    }
+}
+
+
 // === Text literals ===
 
 /// A component of a text literal, within the quotation marks.
@@ -376,6 +635,26 @@ pub enum TextElement<'s> {
         /// The \ character.
         backslash: token::TextEscape<'s>,
     },
+    /// An interpolated expression, like `` `name` `` in `'Hello `name`!'`.
+    Splice {
+        /// The opening backtick.
+        open:       token::Symbol<'s>,
+        /// The interpolated expression. It is an error for this to be empty.
+        expression: Option<Tree<'s>>,
+        /// The closing backtick. It is missing if the line ended before the expression was closed.
+        close:      Option<token::Symbol<'s>>,
+    },
+}
+
+impl<'s> TextElement<'s> {
+    /// Return a mutable reference to the left offset of the first token of this element.
+    pub fn left_offset_mut(&mut self) -> &mut Offset<'s> {
+        match self {
+            TextElement::Section { text } => &mut text.left_offset,
+            TextElement::Escape { backslash } => &mut backslash.left_offset,
+            TextElement::Splice { open, .. } => &mut open.left_offset,
+        }
+    }
 }
 
 impl<'s> span::Builder<'s> for TextElement<'s> {
@@ -383,6 +662,8 @@ impl<'s> span::Builder<'s> for TextElement<'s> {
         match self {
             TextElement::Section { text } => text.add_to_span(span),
             TextElement::Escape { backslash } => backslash.add_to_span(span),
+            TextElement::Splice { open, expression, close } =>
+                span.add(open).add(expression).add(close),
         }
     }
 }
@@ -476,11 +757,8 @@ pub fn apply<'s>(mut func: Tree<'s>, mut arg: Tree<'s>) -> Tree<'s> {
         }
         Variant::TextLiteral(lhs) if let Variant::TextLiteral(rhs) = &mut *arg.variant
                 && lhs.close_quote.is_none() && rhs.open_quote.is_none() => {
-            match rhs.elements.first_mut() {
-                Some(TextElement::Section { text }) => text.left_offset = arg.span.left_offset,
-                Some(TextElement::Escape { backslash }) =>
-                    backslash.left_offset = arg.span.left_offset,
-                None => (),
+            if let Some(first) = rhs.elements.first_mut() {
+                *first.left_offset_mut() = arg.span.left_offset;
             }
             lhs.elements.append(&mut rhs.elements);
             lhs.close_quote = rhs.close_quote.take();
@@ -489,9 +767,6 @@ pub fn apply<'s>(mut func: Tree<'s>, mut arg: Tree<'s>) -> Tree<'s> {
             }
             return func;
         }
-        Variant::TextLiteral(lhs) if let Variant::TextLiteral(rhs) = &mut *arg.variant => {
-            panic!("lhs: {:?}, rhs: {:?}", lhs, rhs)
-        }
         _ => (),
     }
     match &mut *arg.variant {
@@ -511,7 +786,7 @@ pub fn apply<'s>(mut func: Tree<'s>, mut arg: Tree<'s>) -> Tree<'s> {
 ///
 /// For most operands this will simply construct an `OprApp`; however, a non-operator block (i.e. an
 /// `ArgumentBlock`) is reinterpreted as a `BodyBlock` when it appears in the RHS of an operator
-/// expression.
+/// expression, and an application of the `->` operator to two operands is a `Lambda`.
 pub fn apply_operator<'s>(
     lhs: Option<Tree<'s>>,
     opr: Vec<token::Operator<'s>>,
@@ -543,6 +818,11 @@ pub fn apply_operator<'s>(
             }
         }
     }
+    if let Ok(opr_) = &opr && opr_.code == "->" && lhs.is_some() && rhs.is_some() {
+        let pattern = lhs.unwrap();
+        let arguments = vec![ArgumentDefinition { pattern, default_value: None }];
+        return Tree::lambda(None, arguments, opr.ok(), rhs);
+    }
     Tree::opr_app(lhs, opr, rhs)
 }
 
@@ -770,6 +1050,7 @@ impl<'s, 'a> SpanVisitableMut<'s, 'a> for Tree<'s> {
         if visitor.visit_mut(span::RefMut {
             left_offset: &mut self.span.left_offset,
             code_length: self.span.code_length,
+            code:        None,
         }) {
             self.variant.visit_span_mut(visitor)
         }
@@ -786,7 +1067,9 @@ impl<'a, 't, 's, T> SpanVisitable<'s, 'a> for Token<'s, T> {
 impl<'a, 't, 's, T> SpanVisitableMut<'s, 'a> for Token<'s, T> {
     fn visit_span_mut<V: SpanVisitorMut<'s>>(&'a mut self, visitor: &mut V) {
         let code_length = self.code.len();
-        visitor.visit_mut(span::RefMut { left_offset: &mut self.left_offset, code_length });
+        let left_offset = &mut self.left_offset;
+        let code = Some(&mut self.code);
+        visitor.visit_mut(span::RefMut { left_offset, code_length, code });
     }
 }
 
@@ -840,15 +1123,16 @@ impl<'s> span::Builder<'s> for Cow<'static, str> {
 // === CodePrinterVisitor ===
 // ==========================
 
-/// A visitor collecting code representation of AST nodes.
-#[derive(Debug, Default)]
+/// A visitor appending code representation of AST nodes to a buffer. The code is reconstructed
+/// from the tokens and the left offsets of tokens and trees, in the order they are visited.
+#[derive(Debug)]
 #[allow(missing_docs)]
-struct CodePrinterVisitor {
-    pub code: String,
+struct CodePrinterVisitor<'c> {
+    pub code: &'c mut String,
 }
 
-impl Visitor for CodePrinterVisitor {}
-impl<'s, 'a> ItemVisitor<'s, 'a> for CodePrinterVisitor {
+impl<'c> Visitor for CodePrinterVisitor<'c> {}
+impl<'s, 'a, 'c> ItemVisitor<'s, 'a> for CodePrinterVisitor<'c> {
     fn visit_item(&mut self, item: item::Ref<'s, 'a>) -> bool {
         match item {
             item::Ref::Tree(tree) => self.code.push_str(&tree.span.left_offset.code),
@@ -862,11 +1146,18 @@ impl<'s, 'a> ItemVisitor<'s, 'a> for CodePrinterVisitor {
 }
 
 impl<'s> Tree<'s> {
-    /// Code generator of this AST.
+    /// Code generator of this AST. For a tree produced by the parser, the result is exactly the
+    /// parsed source code.
     pub fn code(&self) -> String {
-        let mut visitor = CodePrinterVisitor::default();
+        let mut code = String::new();
+        self.print_code(&mut code);
+        code
+    }
+
+    /// Append the code of this AST to the provided buffer. See [`Self::code`] to learn more.
+    pub fn print_code(&self, code: &mut String) {
+        let mut visitor = CodePrinterVisitor { code };
         self.visit_item(&mut visitor);
-        visitor.code
     }
 }
 
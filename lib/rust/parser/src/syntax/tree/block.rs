@@ -54,10 +54,32 @@ pub fn body_from_lines<'s>(expressions: impl IntoIterator<Item = Line<'s>>) -> T
     use crate::expression_to_statement;
     let expressions = expressions.into_iter();
     let statements = expressions.map(|line| line.map_expression(expression_to_statement));
-    let statements = statements.collect();
+    let statements = attach_annotations(statements.collect());
     Tree::body_block(statements)
 }
 
+/// Attach every annotation occupying a line of its own to the statement on the first non-empty line
+/// following it. The lines between the annotation and the statement become part of the annotation.
+pub fn attach_annotations(lines: Vec<Line<'_>>) -> Vec<Line<'_>> {
+    // The lines are processed from the last one, so that the statement following an annotation
+    // already has the annotations preceding it attached. The output is in reverse order.
+    let mut reversed: Vec<Line> = Vec::with_capacity(lines.len());
+    for mut line in lines.into_iter().rev() {
+        if let Some(expression) = &mut line.expression {
+            attach_annotation(expression, || {
+                let target = reversed.iter().rposition(|line| line.expression.is_some())?;
+                let following = reversed.drain(target..).rev();
+                let (newlines, expressions): (Vec<_>, Vec<_>) =
+                    following.map(|line| (line.newline, line.expression)).unzip();
+                Some((newlines, expressions.into_iter().flatten().next()?))
+            });
+        }
+        reversed.push(line);
+    }
+    reversed.reverse();
+    reversed
+}
+
 
 
 // ======================
@@ -0,0 +1,69 @@
+//! Parse random sequences of tokens, checking that the parser never panics and that the resulting
+//! tree always reproduces the input code.
+
+// === Non-Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+use std::panic;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The number of random inputs to parse.
+const CASES: usize = 2_000;
+
+/// The maximum number of tokens in a random input.
+const MAX_TOKENS: usize = 24;
+
+/// Tokens the random inputs are built of. Keywords of built-in macros are included to exercise
+/// incomplete and malformed macro invocations.
+const TOKENS: &[&str] = &[
+    "x", "Foo", "_", "1", "0x1F", "0b12", "3.14", "+", "-", "*", "=", "==", "->", ".", ",", ":",
+    "~", "@", "(", ")", "[", "]", "{", "}", "\\", "'", "\"", "#", "type", "case", "of", "if",
+    "then", "else", "import", "from", "export", "as", "hiding", "polyglot", "all",
+];
+
+/// Separators placed between the tokens of the random inputs.
+const SEPARATORS: &[&str] = &["", " ", " ", " ", "\n", "\n    ", "\n        "];
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn random_token_sequences() {
+    let parser = enso_parser::Parser::new();
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    for _ in 0..CASES {
+        let code = random_code(&mut rng);
+        let ast = panic::catch_unwind(panic::AssertUnwindSafe(|| parser.run(&code)))
+            .unwrap_or_else(|_| panic!("Parser panicked on input: {code:?}"));
+        assert_eq!(ast.code(), code, "{:?}", &ast);
+    }
+}
+
+fn random_code(rng: &mut impl Rng) -> String {
+    let tokens = rng.gen_range(1..=MAX_TOKENS);
+    let mut code = String::new();
+    for _ in 0..tokens {
+        code.push_str(SEPARATORS.choose(rng).unwrap());
+        code.push_str(TOKENS.choose(rng).unwrap());
+    }
+    code
+}
@@ -102,7 +102,7 @@ fn type_constructors() {
     #[rustfmt::skip]
     let expected = block![
         (TypeDef (Ident type) (Ident Geo) #()
-         #(((Circle #() #((Ident radius) (Number 4))))
+         #(((Circle #() #((Ident radius) (Number () 4 () ()))))
            ((Rectangle #((Ident width) (Ident height)) #()))
            ((Point #() #())))
          #())
@@ -116,8 +116,9 @@ fn type_methods() {
     #[rustfmt::skip]
         let expected = block![
         (TypeDef (Ident type) (Ident Geo) #() #()
-         #((Function number #() "=" (BodyBlock #((Number 23))))
-           (Function area #((Ident self)) "=" (OprApp (Number 1) (Ok "+") (Number 1)))))
+         #((Function number #() "=" (BodyBlock #((Number () 23 () ()))))
+           (Function area #((Ident self)) "="
+            (OprApp (Number () 1 () ()) (Ok "+") (Number () 1 () ())))))
     ];
     test(&code.join("\n"), expected);
 }
@@ -139,12 +140,13 @@ fn type_def_full() {
     #[rustfmt::skip]
     let expected = block![
         (TypeDef (Ident type) (Ident Geo) #()
-         #(((Circle #() #((TypeAnnotated (Ident radius) ":" (Ident float)) (Number 4))))
+         #(((Circle #() #((TypeAnnotated (Ident radius) ":" (Ident float)) (Number () 4 () ()))))
            ((Rectangle #((Ident width) (Ident height)) #()))
            ((Point #() #()))
            (()))
-         #((Function number #() "=" (BodyBlock #((Number 23))))
-           (Function area #((Ident self)) "=" (OprApp (Number 1) (Ok "+") (Number 1)))))
+         #((Function number #() "=" (BodyBlock #((Number () 23 () ()))))
+           (Function area #((Ident self)) "="
+            (OprApp (Number () 1 () ()) (Ok "+") (Number () 1 () ())))))
     ];
     test(&code.join("\n"), expected);
 }
@@ -171,7 +173,7 @@ fn type_def_nested() {
 
 #[test]
 fn assignment_simple() {
-    test("foo = 23", block![(Assignment (Ident foo) "=" (Number 23))]);
+    test("foo = 23", block![(Assignment (Ident foo) "=" (Number () 23 () ()))]);
 }
 
 
@@ -179,9 +181,12 @@ fn assignment_simple() {
 
 #[test]
 fn function_inline_simple_args() {
-    test("foo a = 23", block![(Function foo #((Ident a)) "=" (Number 23))]);
-    test("foo a b = 23", block![(Function foo #((Ident a) (Ident b)) "=" (Number 23))]);
-    test("foo a b c = 23", block![(Function foo #((Ident a) (Ident b) (Ident c)) "=" (Number 23))]);
+    test("foo a = 23", block![(Function foo #((Ident a)) "=" (Number () 23 () ()))]);
+    test("foo a b = 23", block![(Function foo #((Ident a) (Ident b)) "=" (Number () 23 () ()))]);
+    test(
+        "foo a b c = 23",
+        block![(Function foo #((Ident a) (Ident b) (Ident c)) "=" (Number () 23 () ()))],
+    );
 }
 
 #[test]
@@ -202,15 +207,15 @@ fn function_block_simple_args() {
 #[test]
 fn code_block_body() {
     let code = ["main =", "    4"];
-    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #((Number 4))))]);
+    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #((Number () 4 () ()))))]);
     let code = ["main =", "      ", "    4"];
-    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number 4))))]);
+    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number () 4 () ()))))]);
     let code = ["main =", "    ", "    4"];
-    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number 4))))]);
+    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number () 4 () ()))))]);
     let code = ["main =", "  ", "    4"];
-    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number 4))))]);
+    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number () 4 () ()))))]);
     let code = ["main =", "", "    4"];
-    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number 4))))]);
+    test(&code.join("\n"), block![(Function main #() "=" (BodyBlock #(() (Number () 4 () ()))))]);
 
     #[rustfmt::skip]
     let code = [
@@ -221,8 +226,8 @@ fn code_block_body() {
     #[rustfmt::skip]
     let expect = block![
         (Function main #() "=" (BodyBlock #(
-         (OprSectionBoundary (OprApp () (Ok "+") (Number 4)))
-         (App (Ident print) (Number 23)))))
+         (OprSectionBoundary (OprApp () (Ok "+") (Number () 4 () ())))
+         (App (Ident print) (Number () 23 () ())))))
     ];
     test(&code.join("\n"), expect);
 }
@@ -263,7 +268,7 @@ fn code_block_argument_list() {
     let expect = block![
         (Assignment (Ident value) "="
          (ArgumentBlockApplication (Ident foo) #(
-          (OprSectionBoundary (OprApp () (Ok "+") (Number 1)))
+          (OprSectionBoundary (OprApp () (Ok "+") (Number () 1 () ())))
           (Ident bar))))
     ];
     test(&code.join("\n"), expect);
@@ -318,12 +323,12 @@ fn code_block_with_following_statement() {
 fn multiple_operator_error() {
     let code = ["4 + + 1"];
     let expected = block![
-        (OprApp (Number 4) (Err (#("+" "+"))) (Number 1))
+        (OprApp (Number () 4 () ()) (Err (#("+" "+"))) (Number () 1 () ()))
     ];
     test(&code.join("\n"), expected);
     let code = ["4 + + + 1"];
     let expected = block![
-        (OprApp (Number 4) (Err (#("+" "+" "+"))) (Number 1))
+        (OprApp (Number () 4 () ()) (Err (#("+" "+" "+"))) (Number () 1 () ()))
     ];
     test(&code.join("\n"), expected);
 }
@@ -332,11 +337,65 @@ fn multiple_operator_error() {
 fn precedence() {
     let code = ["1 * 2 + 3"];
     let expected = block![
-        (OprApp (OprApp (Number 1) (Ok "*") (Number 2)) (Ok "+") (Number 3))
+        (OprApp (OprApp (Number () 1 () ()) (Ok "*") (Number () 2 () ()))
+                (Ok "+") (Number () 3 () ()))
     ];
     test(&code.join("\n"), expected);
 }
 
+#[test]
+fn operator_precedence() {
+    // Groups of operators of equal precedence, in order of increasing precedence.
+    let groups: &[&[&str]] = &[
+        &[","],
+        &["|", "&"],
+        &[">>", "<<"],
+        &["|>", "<|"],
+        &["!"],
+        &["||"],
+        &["^"],
+        &["&&"],
+        &["==", "!=", "<", ">", "<=", ">="],
+        &["+", "-", "++"],
+        &["*", "/", "%"],
+        &["$", "?"],
+        &["."],
+    ];
+    let right_associative = ["<|", "^"];
+    let ranked = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(rank, group)| group.iter().map(move |opr| (rank, *opr)));
+    let operators = ranked.collect::<Vec<_>>();
+    for &(rank1, opr1) in &operators {
+        for &(rank2, opr2) in &operators {
+            let code = format!("a {opr1} b {opr2} c");
+            let left_nested =
+                rank1 > rank2 || (rank1 == rank2 && !right_associative.contains(&opr2));
+            let tree = if left_nested {
+                format!(
+                    "(OprApp (OprApp (Ident a) (Ok {opr1:?}) (Ident b)) (Ok {opr2:?}) (Ident c))"
+                )
+            } else {
+                format!(
+                    "(OprApp (Ident a) (Ok {opr1:?}) (OprApp (Ident b) (Ok {opr2:?}) (Ident c)))"
+                )
+            };
+            test(&code, lexpr::from_str(&format!("(BodyBlock #({tree}))")).unwrap());
+        }
+    }
+    #[rustfmt::skip]
+    let cases = [
+        ("a = b , c", block![
+            (Assignment (Ident a) "=" (OprApp (Ident b) (Ok ",") (Ident c)))]),
+        ("x -> y -> x", block![
+            (Lambda () #(((Ident x) ())) "->" (Lambda () #(((Ident y) ())) "->" (Ident x)))]),
+        ("a - b - c", block![
+            (OprApp (OprApp (Ident a) (Ok "-") (Ident b)) (Ok "-") (Ident c))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
 
 // === Unary Operators ===
 
@@ -344,7 +403,7 @@ fn precedence() {
 fn unevaluated_argument() {
     let code = ["main ~foo = 4"];
     let expected = block![
-        (Function main #((UnaryOprApp "~" (Ident foo))) "=" (Number 4))
+        (Function main #((UnaryOprApp "~" (Ident foo))) "=" (Number () 4 () ()))
     ];
     test(&code.join("\n"), expected);
 }
@@ -353,7 +412,7 @@ fn unevaluated_argument() {
 fn unary_operator_missing_operand() {
     let code = ["main ~ = 4"];
     let expected = block![
-        (Function main #((UnaryOprApp "~" ())) "=" (Number 4))
+        (Function main #((UnaryOprApp "~" ())) "=" (Number () 4 () ()))
     ];
     test(&code.join("\n"), expected);
 }
@@ -371,7 +430,8 @@ fn unary_operator_at_end_of_expression() {
 fn plus_negative() {
     let code = ["x = 4+-1"];
     let expected = block![
-        (Assignment (Ident x) "=" (OprApp (Number 4) (Ok "+") (UnaryOprApp "-" (Number 1))))
+        (Assignment (Ident x) "="
+                    (OprApp (Number () 4 () ()) (Ok "+") (UnaryOprApp "-" (Number () 1 () ()))))
     ];
     test(&code.join("\n"), expected);
 }
@@ -379,8 +439,8 @@ fn plus_negative() {
 #[test]
 fn minus_binary() {
     let cases = [
-        ("x - 5", block![(OprApp (Ident x) (Ok "-") (Number 5))]),
-        ("x-5", block![(OprApp (Ident x) (Ok "-") (Number 5))]),
+        ("x - 5", block![(OprApp (Ident x) (Ok "-") (Number () 5 () ()))]),
+        ("x-5", block![(OprApp (Ident x) (Ok "-") (Number () 5 () ()))]),
         ("x.-y", block![(OprApp (Ident x) (Ok ".") (UnaryOprApp "-" (Ident y)))]),
         ("x.~y", block![(OprApp (Ident x) (Ok ".") (UnaryOprApp "~" (Ident y)))]),
     ];
@@ -395,7 +455,7 @@ fn minus_section() {
         ("(- x)", block![(Group "(" (OprSectionBoundary (OprApp () (Ok "-") (Ident x))) ")")]),
         ("- (x * 2)", block![
             (OprSectionBoundary (OprApp () (Ok "-")
-             (Group "(" (OprApp (Ident x) (Ok "*") (Number 2)) ")")))]),
+             (Group "(" (OprApp (Ident x) (Ok "*") (Number () 2 () ())) ")")))]),
     ];
     cases.into_iter().for_each(|(code, expected)| test(code, expected));
 }
@@ -404,15 +464,17 @@ fn minus_section() {
 fn minus_unary() {
     #[rustfmt::skip]
     let cases = [
-        ("f -5", block![(App (Ident f) (UnaryOprApp "-" (Number 5)))]),
-        ("-5", block![(UnaryOprApp "-" (Number 5))]),
-        ("(-5)", block![(Group "(" (UnaryOprApp "-" (Number 5)) ")")]),
+        ("f -5", block![(App (Ident f) (UnaryOprApp "-" (Number () 5 () ())))]),
+        ("-5", block![(UnaryOprApp "-" (Number () 5 () ()))]),
+        ("(-5)", block![(Group "(" (UnaryOprApp "-" (Number () 5 () ())) ")")]),
         ("-(x * 2)", block![
-            (UnaryOprApp "-" (Group "(" (OprApp (Ident x) (Ok "*") (Number 2)) ")"))]),
-        ("x=-1", block![(Assignment (Ident x) "=" (UnaryOprApp "-" (Number 1)))]),
-        ("-1+2", block![(OprApp (UnaryOprApp "-" (Number 1)) (Ok "+") (Number 2))]),
-        ("-1*2", block![(OprApp (UnaryOprApp "-" (Number 1)) (Ok "*") (Number 2))]),
-        ("-1.x", block![(OprApp (UnaryOprApp "-" (Number 1)) (Ok ".") (Ident x))]),
+            (UnaryOprApp "-" (Group "(" (OprApp (Ident x) (Ok "*") (Number () 2 () ())) ")"))]),
+        ("x=-1", block![(Assignment (Ident x) "=" (UnaryOprApp "-" (Number () 1 () ())))]),
+        ("-1+2", block![
+            (OprApp (UnaryOprApp "-" (Number () 1 () ())) (Ok "+") (Number () 2 () ()))]),
+        ("-1*2", block![
+            (OprApp (UnaryOprApp "-" (Number () 1 () ())) (Ok "*") (Number () 2 () ()))]),
+        ("-1.x", block![(OprApp (UnaryOprApp "-" (Number () 1 () ())) (Ok ".") (Ident x))]),
     ];
     cases.into_iter().for_each(|(code, expected)| test(code, expected));
 }
@@ -503,6 +565,27 @@ fn export() {
 }
 
 
+// === Macro Errors ===
+
+#[test]
+fn unmatched_macros() {
+    #[rustfmt::skip]
+    let cases = [
+        ("from Foo", block![
+            (Invalid "Macro was not matched with any known macro definition."
+             (App (Ident from) (Ident Foo)))]),
+        ("if a", block![
+            (Invalid "Macro was not matched with any known macro definition."
+             (App (Ident if) (Ident a)))]),
+        ("x = if a", block![
+            (Assignment (Ident x) "="
+             (Invalid "Macro was not matched with any known macro definition."
+              (App (Ident if) (Ident a))))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+
 // === Metadata ===
 
 
@@ -516,7 +599,7 @@ fn metadata_raw() {
     let code = code.join("\n");
     let (_meta, code) = enso_parser::metadata::parse(&code).unwrap();
     let expected = block![
-        (Number 4)
+        (Number () 4 () ())
         ()
     ];
     test(code, expected);
@@ -527,7 +610,86 @@ fn metadata_parsing() {
     let code = metadata::ORDERS_WITH_METADATA;
     let (meta, code) = enso_parser::metadata::parse(code).unwrap();
     let _ast = enso_parser::Parser::new().run(code);
-    let _meta: enso_parser::metadata::Metadata = meta.unwrap();
+    let meta: enso_parser::metadata::Metadata = meta.unwrap();
+    let id = "fa6ee8df-3d75-4822-a4d6-dfa5168b5f0b".parse().unwrap();
+    let node = &meta.ide.node[&id];
+    assert_eq!(node.position.unwrap().vector, [503.0, -6.0]);
+    let visualization = node.visualization.as_ref().unwrap();
+    assert_eq!(visualization.name, "Table");
+    assert_eq!(visualization.project, enso_parser::metadata::VisualizationProject::Builtin);
+    assert_eq!(meta.get_uuid(5, 8), Some("6c809d6e-d1dc-443a-b93b-704288988725".parse().unwrap()));
+}
+
+#[test]
+fn metadata_writing() {
+    let (meta, code) = enso_parser::metadata::parse(metadata::ORDERS_WITH_METADATA).unwrap();
+    let mut meta = meta.unwrap();
+    let id = "13f32da2-c06b-4a10-8941-20b11446eb81".parse().unwrap();
+    meta.ide.node.get_mut(&id).unwrap().position.as_mut().unwrap().vector = [1.0, 2.0];
+    meta.set_uuid(0, 4, id);
+    let written = meta.write(code);
+    let (reparsed, reparsed_code) = enso_parser::metadata::parse(&written).unwrap();
+    assert_eq!(reparsed_code, code);
+    assert_eq!(reparsed.unwrap(), meta);
+}
+
+#[test]
+fn metadata_rewriting() {
+    // Metadata written by the IDE is normalized on the first write, and then stable.
+    let (meta, code) = enso_parser::metadata::parse(metadata::ORDERS_WITH_METADATA).unwrap();
+    let written = meta.unwrap().write(code);
+    assert!(!written.contains(r#""selected":false"#));
+    assert!(!written.contains(r#""intended_method":null"#));
+    let (reparsed, reparsed_code) = enso_parser::metadata::parse(&written).unwrap();
+    assert_eq!(reparsed_code, code);
+    assert_eq!(reparsed.unwrap().write(reparsed_code), written);
+}
+
+#[test]
+fn metadata_writing_without_final_newline() {
+    let meta = enso_parser::metadata::Metadata::default();
+    let written = meta.write("main = 4");
+    assert_eq!(written, "main = 4\n#### METADATA ####\n[]\n{\"ide\":{}}");
+    let (reparsed, code) = enso_parser::metadata::parse(&written).unwrap();
+    assert_eq!(code, "main = 4\n");
+    assert_eq!(reparsed.unwrap(), meta);
+}
+
+#[test]
+fn metadata_lenient_decoding() {
+    let good = "fa6ee8df-3d75-4822-a4d6-dfa5168b5f0b";
+    let sections = [
+        format!(r#""{good}":{{"position":{{"vector":[1.0,2.0]}},"selected":"yes"}}"#),
+        r#""13f32da2-c06b-4a10-8941-20b11446eb81":[]"#.to_string(),
+        r#""not-an-id":{}"#.to_string(),
+    ];
+    let input = format!(
+        "main = 4\n#### METADATA ####\n[]\n{{\"ide\":{{\"node\":{{{}}},\"import\":5}}}}",
+        sections.join(",")
+    );
+    let meta = enso_parser::metadata::parse(&input).unwrap().0.unwrap();
+    assert_eq!(meta.ide.node.len(), 1);
+    let node = &meta.ide.node[&good.parse().unwrap()];
+    assert_eq!(node.position.unwrap().vector, [1.0, 2.0]);
+    assert!(!node.selected);
+    assert!(meta.ide.import.is_empty());
+}
+
+#[test]
+fn metadata_errors() {
+    let parse = |metadata: &str| {
+        let input = format!("main = 4\n#### METADATA ####\n{metadata}");
+        enso_parser::metadata::parse(&input).unwrap().0
+    };
+    assert!(parse("").is_err());
+    assert!(parse("[[{\"index\":{\"value\":0}}]]").is_err());
+    assert!(parse("[]\n{}\ntrailing").is_err());
+    let empty = enso_parser::metadata::Metadata::default();
+    assert_eq!(parse("[]").unwrap(), empty);
+    assert_eq!(parse("[]\n{}\n").unwrap(), empty);
+    // Malformed IDE sections are replaced with their defaults.
+    assert_eq!(parse("[]\n{\"ide\":{\"node\":[]}}").unwrap(), empty);
+    assert_eq!(parse("[]\n{\"ide\":[]}").unwrap(), empty);
 }
 
 
@@ -547,11 +709,246 @@ fn type_annotations() {
     #[rustfmt::skip]
     let cases = [
         ("val = 123 : Int", block![
-            (Assignment (Ident val) "=" (TypeAnnotated (Number 123) ":" (Ident Int)))]),
+            (Assignment (Ident val) "=" (TypeAnnotated (Number () 123 () ()) ":" (Ident Int)))]),
         ("val = foo (123 : Int)", block![
             (Assignment (Ident val) "="
              (App (Ident foo)
-              (Group "(" (TypeAnnotated (Number 123) ":" (Ident Int)) ")")))]),
+              (Group "(" (TypeAnnotated (Number () 123 () ()) ":" (Ident Int)) ")")))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+
+// === Lambdas ===
+
+#[test]
+fn lambdas() {
+    #[rustfmt::skip]
+    let cases = [
+        ("x -> x + 1", block![
+            (Lambda () #(((Ident x) ())) "->" (OprApp (Ident x) (Ok "+") (Number () 1 () ())))]),
+        ("Cons h _ -> h", block![
+            (Lambda () #(((App (App (Ident Cons) (Ident h)) (Wildcard _)) ())) "->" (Ident h))]),
+        ("f = x -> x", block![
+            (Assignment (Ident f) "=" (Lambda () #(((Ident x) ())) "->" (Ident x)))]),
+        ("map (x -> x)", block![
+            (App (Ident map)
+             (Group "(" (Lambda () #(((Ident x) ())) "->" (Ident x)) ")"))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+#[test]
+fn backslash_lambdas() {
+    #[rustfmt::skip]
+    let cases = [
+        ("\\x -> x", block![(Lambda "\\" #(((Ident x) ())) "->" (Ident x))]),
+        ("\\x y=1 -> x", block![
+            (Lambda "\\" #(((Ident x) ()) ((Ident y) ("=" (Number () 1 () ())))) "->" (Ident x))]),
+        ("\\x -> \\y -> x", block![
+            (Lambda "\\" #(((Ident x) ())) "->"
+             (Lambda "\\" #(((Ident y) ())) "->" (Ident x)))]),
+        ("map (\\x -> x)", block![
+            (App (Ident map)
+             (Group "(" (Lambda "\\" #(((Ident x) ())) "->" (Ident x)) ")"))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+#[test]
+fn backslash_lambda_block_body() {
+    #[rustfmt::skip]
+    let code = [
+        "\\x ->",
+        "    y = x",
+        "    y",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (Lambda "\\" #(((Ident x) ())) "->"
+         (BodyBlock #((Assignment (Ident y) "=" (Ident x)) (Ident y))))
+    ];
+    test(&code.join("\n"), expected);
+}
+
+
+// === Case Expressions ===
+
+#[test]
+fn case_expression() {
+    #[rustfmt::skip]
+    let code = [
+        "case a of",
+        "    Some -> x",
+        "    Int -> x",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (Case (Ident case) (Ident a) (Ident of)
+         #(((Ident Some) "->" (Ident x)) ((Ident Int) "->" (Ident x))))
+    ];
+    test(&code.join("\n"), expected);
+}
+
+#[test]
+fn case_block_bodies() {
+    #[rustfmt::skip]
+    let code = [
+        "case self of",
+        "    Cons head _ -> head",
+        "",
+        "    Nil ->",
+        "        x = 1",
+        "        x",
+        "    _ -> 0",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (Case (Ident case) (Ident self) (Ident of)
+         #(((App (App (Ident Cons) (Ident head)) (Wildcard _)) "->" (Ident head))
+           ()
+           ((Ident Nil) "->"
+            (BodyBlock #((Assignment (Ident x) "=" (Number () 1 () ())) (Ident x))))
+           ((Wildcard _) "->" (Number () 0 () ()))))
+    ];
+    test(&code.join("\n"), expected);
+}
+
+#[test]
+fn case_one_line() {
+    #[rustfmt::skip]
+    let cases = [
+        ("case x of _ -> 0", block![
+            (Case (Ident case) (Ident x) (Ident of) #(((Wildcard _) "->" (Number () 0 () ()))))]),
+        ("y = case x of Some z -> z", block![
+            (Assignment (Ident y) "="
+             (Case (Ident case) (Ident x) (Ident of)
+              #(((App (Ident Some) (Ident z)) "->" (Ident z)))))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+#[test]
+fn case_invalid_arms() {
+    #[rustfmt::skip]
+    let code = [
+        "case x of",
+        "    ->",
+        "    Nil ->",
+        "    -> 0",
+        "    Nil",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (Case (Ident case) (Ident x) (Ident of)
+         #((() () (Invalid "Expected a pattern and an expression in case arm."
+                   (OprSectionBoundary (OprApp () (Ok "->") ()))))
+           (() () (Invalid "Expected an expression after `->` in case arm."
+                   (OprSectionBoundary (OprApp (Ident Nil) (Ok "->") ()))))
+           (() () (Invalid "Expected a pattern before `->` in case arm."
+                   (OprSectionBoundary (OprApp () (Ok "->") (Number () 0 () ())))))
+           (() () (Invalid "Expected `->` in case arm." (Ident Nil)))))
+    ];
+    test(&code.join("\n"), expected);
+}
+
+#[test]
+fn case_nested() {
+    #[rustfmt::skip]
+    let code = [
+        "case a of",
+        "    Pair x y -> case x of",
+        "        True -> y",
+        "        _ -> 0",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (Case (Ident case) (Ident a) (Ident of)
+         #(((App (App (Ident Pair) (Ident x)) (Ident y)) "->"
+            (Case (Ident case) (Ident x) (Ident of)
+             #(((Ident True) "->" (Ident y)) ((Wildcard _) "->" (Number () 0 () ())))))))
+    ];
+    test(&code.join("\n"), expected);
+}
+
+
+// === Annotations ===
+
+#[test]
+fn inline_annotations() {
+    #[rustfmt::skip]
+    let cases = [
+        ("@Tail_Call go t", block![
+            (AnnotatedBuiltin "@" Tail_Call #() (App (Ident go) (Ident t)))]),
+        ("go t = @Tail_Call go t", block![
+            (Function go #((Ident t)) "="
+             (AnnotatedBuiltin "@" Tail_Call #() (App (Ident go) (Ident t))))]),
+        ("plus = @Builtin_Method \"Integer.+\"", block![
+            (Assignment (Ident plus) "="
+             (AnnotatedBuiltin "@" Builtin_Method #()
+              (TextLiteral "\"" #((Section "Integer.+")) "\"" 0)))]),
+        ("@Builtin_Type", block![(AnnotatedBuiltin "@" Builtin_Type #() ())]),
+        ("a @ b", block![(OprApp (Ident a) (Ok "@") (Ident b))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+#[test]
+fn annotated_definitions() {
+    #[rustfmt::skip]
+    let cases = [
+        ("@Builtin_Type\ntype Foo", block![
+            (AnnotatedBuiltin "@" Builtin_Type #(())
+             (TypeDef (Ident type) (Ident Foo) #() #() #()))]),
+        ("@column Widget.Select\nfoo column = column", block![
+            (Annotated "@" column (OprApp (Ident Widget) (Ok ".") (Ident Select)) #(())
+             (Function foo #((Ident column)) "=" (Ident column)))]),
+        ("@x 1\n\n@y 2\nfoo = 3", block![
+            (Annotated "@" x (Number () 1 () ()) #(() ())
+             (Annotated "@" y (Number () 2 () ()) #(())
+              (Assignment (Ident foo) "=" (Number () 3 () ()))))]),
+        ("type Foo\n    @x 1\n    foo = 2", block![
+            (TypeDef (Ident type) (Ident Foo) #() #()
+             #((Annotated "@" x (Number () 1 () ()) #(())
+                (Assignment (Ident foo) "=" (Number () 2 () ())))))]),
+        ("@x 1\n", block![(Annotated "@" x (Number () 1 () ()) #() ()) ()]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+
+// === Numbers ===
+
+#[test]
+fn numbers() {
+    #[rustfmt::skip]
+    let cases = [
+        ("100", block![(Number () 100 () ())]),
+        ("1_000_000", block![(Number () "1_000_000" () ())]),
+        ("0x1F", block![(Number "0x" "1F" () ())]),
+        ("16_ff", block![(Number "16_" "ff" () ())]),
+        ("0b101", block![(Number "0b" 101 () ())]),
+        ("3.14", block![(Number () 3 ("." 14) ())]),
+        ("3.14e-2", block![(Number () 3 ("." 14) ("e-" 2))]),
+        ("1e10", block![(Number () 1 () ("e" 10))]),
+        ("-1.5", block![(UnaryOprApp "-" (Number () 1 ("." 5) ()))]),
+        ("x = 2.5 * y", block![
+            (Assignment (Ident x) "="
+             (OprApp (Number () 2 ("." 5) ()) (Ok "*") (Ident y)))]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+#[test]
+fn numbers_with_invalid_digits() {
+    #[rustfmt::skip]
+    let cases = [
+        ("0b12", block![
+            (Invalid "Invalid digit in numeric literal." (Number "0b" 12 () ()))]),
+        ("0o78", block![
+            (Invalid "Invalid digit in numeric literal." (Number "0o" 78 () ()))]),
+        ("12ab", block![
+            (Invalid "Invalid digit in numeric literal." (Number () "12ab" () ()))]),
     ];
     cases.into_iter().for_each(|(code, expected)| test(code, expected));
 }
@@ -581,6 +978,56 @@ fn inline_text_literals() {
     cases.into_iter().for_each(|(code, expected)| test(code, expected));
 }
 
+#[test]
+fn interpolated_literals() {
+    #[rustfmt::skip]
+    let cases = [
+        ("'Simple case.'", block![(TextLiteral "'" #((Section "Simple case.")) "'" 0)]),
+        ("'With a `splice`.'", block![
+            (TextLiteral "'"
+             #((Section "With a ") (Splice "`" (Ident splice) "`") (Section "."))
+             "'" 0)]),
+        ("'` SpliceWithLeadingWhitespace`'", block![
+            (TextLiteral "'" #((Splice "`" (Ident SpliceWithLeadingWhitespace) "`")) "'" 0)]),
+        ("'Sum: `a + b`!'", block![
+            (TextLiteral "'"
+             #((Section "Sum: ") (Splice "`" (OprApp (Ident a) (Ok "+") (Ident b)) "`")
+               (Section "!"))
+             "'" 0)]),
+        (r#"'Escaped \` backtick'"#, block![
+            (TextLiteral "'"
+             #((Section "Escaped ") (Escape "\\") (Section "` backtick"))
+             "'" 0)]),
+        ("x = 'a `b` c'", block![
+            (Assignment (Ident x) "="
+             (TextLiteral "'" #((Section "a ") (Splice "`" (Ident b) "`") (Section " c")) "'" 0))]),
+        ("'Unclosed `splice", block![
+            (TextLiteral "'" #((Section "Unclosed ") (Splice "`" (Ident splice) ())) () 0)]),
+        (r#""Not `interpolated`""#, block![
+            (TextLiteral "\"" #((Section "Not `interpolated`")) "\"" 0)]),
+    ];
+    cases.into_iter().for_each(|(code, expected)| test(code, expected));
+}
+
+#[test]
+fn interpolated_multiline_literals() {
+    #[rustfmt::skip]
+    let code = [
+        "'''",
+        "    Hello `name`!",
+        "x",
+    ];
+    #[rustfmt::skip]
+    let expected = block![
+        (TextLiteral
+         "'''"
+         #((Section "\n") (Section "Hello ") (Splice "`" (Ident name) "`") (Section "!"))
+         () 4)
+        (Ident x)
+    ];
+    test(&code.join("\n"), expected);
+}
+
 #[test]
 fn multiline_text_literals() {
     test("'''", block![(TextLiteral "'''" #() () 0)]);
@@ -603,7 +1050,7 @@ fn multiline_text_literals() {
            (Section "\n") (Section "also part of the string")
            (Section "\n") (Section ""))
         () 4)
-        (Number 3)
+        (Number () 3 () ())
     ];
     test(CODE, expected);
 }
@@ -628,7 +1075,7 @@ use std::collections::HashSet;
 /// postprocessing:
 /// - For concision, field names are stripped (as if all structs were tuple structs).
 /// - Most token types are represented as their contents, rather than as a token struct. For
-///   example, a `token::Number` may be represented like: `sexp![10]`, and a `token::Ident` may look
+///   example, a `token::Digits` may be represented like: `sexp![10]`, and a `token::Ident` may look
 ///   like `sexp![foo]`.
 fn test(code: &str, expect: Value) {
     let ast = enso_parser::Parser::new().run(code);
@@ -660,16 +1107,22 @@ where T: serde::Serialize + Reflect {
     let ident_token = rust_to_meta[&token::variant::Ident::reflect().id];
     let operator_token = rust_to_meta[&token::variant::Operator::reflect().id];
     let symbol_token = rust_to_meta[&token::variant::Symbol::reflect().id];
-    let number_token = rust_to_meta[&token::variant::Number::reflect().id];
+    let wildcard_token = rust_to_meta[&token::variant::Wildcard::reflect().id];
+    let number_base_token = rust_to_meta[&token::variant::NumberBase::reflect().id];
+    let digits_token = rust_to_meta[&token::variant::Digits::reflect().id];
+    let decimal_point_token = rust_to_meta[&token::variant::DecimalPoint::reflect().id];
+    let exponent_token = rust_to_meta[&token::variant::Exponent::reflect().id];
     let newline_token = rust_to_meta[&token::variant::Newline::reflect().id];
     let text_start_token = rust_to_meta[&token::variant::TextStart::reflect().id];
     let text_end_token = rust_to_meta[&token::variant::TextEnd::reflect().id];
     let text_escape_token = rust_to_meta[&token::variant::TextEscape::reflect().id];
     let text_section_token = rust_to_meta[&token::variant::TextSection::reflect().id];
+    let annotation_token = rust_to_meta[&token::variant::AnnotationOperator::reflect().id];
     // TODO: Implement `#[reflect(flag = "enso::concrete")]`, which just attaches user data to the
     //  type info; then filter by flag here instead of hard-coding these simplifications.
     let line = rust_to_meta[&tree::block::Line::reflect().id];
     let operator_line = rust_to_meta[&tree::block::OperatorLine::reflect().id];
+    let case_line = rust_to_meta[&tree::CaseLine::reflect().id];
     let token_to_str = move |token: Value| {
         let range = token_code_range(&token, base);
         code[range].to_owned().into_boxed_str()
@@ -679,6 +1132,8 @@ where T: serde::Serialize + Reflect {
     let token_to_str_ = token_to_str.clone();
     to_s_expr.mapper(operator_token, move |token| Value::string(token_to_str_(token)));
     let token_to_str_ = token_to_str.clone();
+    to_s_expr.mapper(wildcard_token, move |token| Value::symbol(token_to_str_(token)));
+    let token_to_str_ = token_to_str.clone();
     to_s_expr.mapper(symbol_token, move |token| Value::string(token_to_str_(token)));
     let token_to_str_ = token_to_str.clone();
     to_s_expr.mapper(text_start_token, move |token| Value::string(token_to_str_(token)));
@@ -688,9 +1143,21 @@ where T: serde::Serialize + Reflect {
     to_s_expr.mapper(text_escape_token, move |token| Value::string(token_to_str_(token)));
     let token_to_str_ = token_to_str.clone();
     to_s_expr.mapper(text_section_token, move |token| Value::string(token_to_str_(token)));
+    let token_to_str_ = token_to_str.clone();
+    to_s_expr.mapper(annotation_token, move |token| Value::string(token_to_str_(token)));
+    let token_to_str_ = token_to_str.clone();
+    to_s_expr.mapper(number_base_token, move |token| Value::string(token_to_str_(token)));
+    let token_to_str_ = token_to_str.clone();
+    to_s_expr.mapper(decimal_point_token, move |token| Value::string(token_to_str_(token)));
+    let token_to_str_ = token_to_str.clone();
+    to_s_expr.mapper(exponent_token, move |token| Value::string(token_to_str_(token)));
     let token_to_str_ = token_to_str;
-    to_s_expr.mapper(number_token, move |token| {
-        Value::Number(token_to_str_(token).parse::<u64>().unwrap().into())
+    to_s_expr.mapper(digits_token, move |token| {
+        let digits = token_to_str_(token);
+        match digits.parse::<u64>() {
+            Ok(number) => Value::Number(number.into()),
+            Err(_) => Value::string(digits),
+        }
     });
     let into_car = |cons| match cons {
         Value::Cons(cons) => cons.into_pair().0,
@@ -698,6 +1165,7 @@ where T: serde::Serialize + Reflect {
     };
     to_s_expr.mapper(line, into_car);
     to_s_expr.mapper(operator_line, into_car);
+    to_s_expr.mapper(case_line, into_car);
     to_s_expr.skip(newline_token);
     tuplify(to_s_expr.value(ast_ty, &value))
 }
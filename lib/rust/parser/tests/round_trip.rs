@@ -0,0 +1,59 @@
+//! Parse the Enso source files of the distribution, checking that the code printed from the
+//! resulting trees is identical to the parsed code.
+
+// === Non-Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![deny(unconditional_recursion)]
+#![warn(unsafe_code)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+#![warn(unused_qualifications)]
+
+use std::fs;
+use std::path::Path;
+use std::path::PathBuf;
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[test]
+fn distribution_files() {
+    let distribution = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../../distribution");
+    let mut files = vec![];
+    collect_enso_files(&distribution, &mut files);
+    assert!(!files.is_empty(), "No Enso files found in {}.", distribution.display());
+    let parser = enso_parser::Parser::new();
+    let mismatched = files
+        .iter()
+        .filter(|path| {
+            let code = fs::read_to_string(path).unwrap();
+            let code = match enso_parser::metadata::parse(&code) {
+                Some((_, code)) => code,
+                None => &code,
+            };
+            parser.run(code).code() != code
+        })
+        .collect::<Vec<_>>();
+    assert!(mismatched.is_empty(), "Printed code differs from the source of: {mismatched:#?}");
+}
+
+/// Collect the paths of all `.enso` files in the given directory and its subdirectories.
+fn collect_enso_files(dir: &Path, out: &mut Vec<PathBuf>) {
+    let mut entries =
+        fs::read_dir(dir).unwrap().map(|entry| entry.unwrap().path()).collect::<Vec<_>>();
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            collect_enso_files(&path, out);
+        } else if path.extension().map_or(false, |extension| extension == "enso") {
+            out.push(path);
+        }
+    }
+}
@@ -0,0 +1,50 @@
+//! Tool that generates Chrome Trace Event files from profiling data, for viewing in
+//! `chrome://tracing`, [Perfetto](https://ui.perfetto.dev) or the Chrome DevTools.
+//!
+//! # Usage
+//!
+//! The tool reads a
+//! [JSON-formatted event log](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format)
+//! from stdin, and writes a trace to stdout. The input may contain profiles of multiple processes;
+//! each of them becomes a process of the trace.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin chrome_trace < profile.json > trace.json
+//! ```
+
+// === Features ===
+#![feature(test)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler::format::AnyMetadata;
+use enso_profiler_data as data;
+use enso_profiler_data::export::chrome_trace;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    use std::io::Read;
+    let mut log = String::new();
+    std::io::stdin().read_to_string(&mut log).unwrap();
+    let mut trace = chrome_trace::Trace::default();
+    for profile in data::parse_multiprocess_profile::<AnyMetadata>(&log) {
+        trace.add_profile(&profile.unwrap());
+    }
+    serde_json::to_writer(std::io::stdout(), &trace).unwrap();
+}
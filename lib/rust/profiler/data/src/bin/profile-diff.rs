@@ -0,0 +1,132 @@
+//! Tool that compares the time spent in each stack of profilers in two captures, e.g. made before
+//! and after a change.
+//!
+//! # Usage
+//!
+//! The tool reads two sets of
+//! [JSON-formatted event logs](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format):
+//! the baseline and the current capture, each given as a comma-separated list of files. The
+//! durations are averaged over the profiles of each capture. The report, written to stdout, ranks
+//! the stacks by the change of their self-duration, and lists the stacks present in only one of
+//! the captures. The optional last argument limits the number of ranked stacks (20 by default).
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin profile-diff before1.json,before2.json after1.json,after2.json
+//! ```
+
+// === Features ===
+#![feature(test)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler::format::AnyMetadata;
+use enso_profiler_data as data;
+use enso_profiler_data::diff;
+use std::collections::HashSet;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The number of the ranked stacks shown by default.
+const DEFAULT_RANKED: usize = 20;
+
+/// Separates the labels of the stack in the report.
+const PATH_SEPARATOR: &str = " > ";
+
+
+
+// ============
+// === Main ===
+// ============
+
+fn main() {
+    let mut args = std::env::args();
+    let argv0 = args.next().unwrap();
+    let usage = &format!("Usage: {} baseline1.json,... current1.json,... [count]", argv0);
+    let baseline = read_capture(&args.next().expect(usage));
+    let current = read_capture(&args.next().expect(usage));
+    let ranked = args.next().map_or(DEFAULT_RANKED, |count| count.parse().expect(usage));
+    println!(
+        "Durations in ms, averaged over {} baseline and {} current profile(s).",
+        baseline.profile_count(),
+        current.profile_count()
+    );
+    let diffs = diff::diff(baseline, current);
+
+    let top_level = diffs.iter().filter(|diff| diff.path.len() == 1);
+    let total = |timings: Option<diff::Timings>| timings.map_or(0.0, |t| t.total_duration);
+    let baseline_total: f64 = top_level.clone().map(|diff| total(diff.baseline)).sum();
+    let current_total: f64 = top_level.map(|diff| total(diff.current)).sum();
+    let total_delta = current_total - baseline_total;
+    println!("Total: {:.1} -> {:.1} ({:+.1})", baseline_total, current_total, total_delta);
+
+    let mut by_self_delta: Vec<_> = diffs.iter().collect();
+    by_self_delta.sort_by(|a, b| {
+        b.self_delta().partial_cmp(&a.self_delta()).unwrap_or(std::cmp::Ordering::Equal)
+    });
+    let regressions = by_self_delta.iter().filter(|diff| diff.self_delta() > 0.0);
+    print_ranked("Regressions", regressions.take(ranked));
+    let improvements = by_self_delta.iter().rev().filter(|diff| diff.self_delta() < 0.0);
+    print_ranked("Improvements", improvements.take(ranked));
+
+    print_subtrees("New stacks", &diffs, diff::Status::New, |diff| diff.current);
+    print_subtrees("Missing stacks", &diffs, diff::Status::Missing, |diff| diff.baseline);
+}
+
+/// Read the profiles of all files in the comma-separated list.
+fn read_capture(paths: &str) -> diff::Capture {
+    let mut capture = diff::Capture::default();
+    for path in paths.split(',') {
+        let log = std::fs::read_to_string(path).unwrap();
+        for profile in data::parse_multiprocess_profile::<AnyMetadata>(&log) {
+            capture.add_profile(&profile.unwrap());
+        }
+    }
+    capture
+}
+
+/// Print the stacks with their self-duration and total duration changes.
+fn print_ranked<'a>(title: &str, diffs: impl Iterator<Item = &'a &'a diff::FrameDiff>) {
+    println!();
+    println!("{} (self, total, stack):", title);
+    for diff in diffs {
+        let path = diff.path.join(PATH_SEPARATOR);
+        println!("{:>+8.1} {:>+8.1} {}", diff.self_delta(), diff.total_delta(), path);
+    }
+}
+
+/// Print the stacks with the given status, whose parents have a different status, with their
+/// total duration.
+fn print_subtrees(
+    title: &str,
+    diffs: &[diff::FrameDiff],
+    status: diff::Status,
+    timings: impl Fn(&diff::FrameDiff) -> Option<diff::Timings>,
+) {
+    println!();
+    println!("{} (total, stack):", title);
+    let mut reported = HashSet::new();
+    for diff in diffs.iter().filter(|diff| diff.status() == status) {
+        let parent = &diff.path[..diff.path.len() - 1];
+        let is_subtree_root = !reported.contains(parent);
+        reported.insert(diff.path.as_slice());
+        if is_subtree_root {
+            let total = timings(diff).map_or(0.0, |t| t.total_duration);
+            println!("{:>8.1} {}", total, diff.path.join(PATH_SEPARATOR));
+        }
+    }
+}
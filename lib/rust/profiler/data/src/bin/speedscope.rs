@@ -0,0 +1,53 @@
+//! Tool that generates [speedscope](https://www.speedscope.app) files from profiling data.
+//!
+//! # Usage
+//!
+//! The tool reads a
+//! [JSON-formatted event log](https://github.com/enso-org/design/blob/main/epics/profiling/implementation.md#file-format)
+//! from stdin, and writes a speedscope file to stdout. The file contains the timeline of each
+//! profile of the input, and the time spent in each stack of profilers aggregated over all of them.
+//!
+//! For example:
+//!
+//! ```console
+//! ~/git/enso/data $ cargo run --bin speedscope < profile.json > profile.speedscope.json
+//! ```
+
+// === Features ===
+#![feature(test)]
+// === Standard Linter Configuration ===
+#![deny(non_ascii_idents)]
+#![warn(unsafe_code)]
+// === Non-Standard Linter Configuration ===
+#![deny(unconditional_recursion)]
+#![warn(missing_copy_implementations)]
+#![warn(missing_debug_implementations)]
+#![warn(missing_docs)]
+#![warn(trivial_casts)]
+#![warn(trivial_numeric_casts)]
+#![warn(unused_import_braces)]
+
+use enso_profiler::format::AnyMetadata;
+use enso_profiler_data as data;
+use enso_profiler_data::export::speedscope;
+
+
+
+// ============
+// === main ===
+// ============
+
+fn main() {
+    use std::io::Read;
+    let mut log = String::new();
+    std::io::stdin().read_to_string(&mut log).unwrap();
+    let mut file = speedscope::File::new("Enso profile");
+    let mut aggregator = data::aggregate::Aggregator::default();
+    for profile in data::parse_multiprocess_profile::<AnyMetadata>(&log) {
+        let profile = profile.unwrap();
+        file.add_profile(&profile);
+        aggregator.add_profile(&profile);
+    }
+    file.add_aggregate("Aggregate", aggregator);
+    serde_json::to_writer(std::io::stdout(), &file).unwrap();
+}
@@ -0,0 +1,280 @@
+//! Supports comparing the time spent in each stack of profilers in two sets of profiles, e.g.
+//! captured before and after a change.
+//!
+//! The frames of the [`aggregate::Frame`] trees are aligned by their label paths. Line numbers are
+//! not part of the labels compared, as unrelated changes often move the profiled code.
+
+use crate::aggregate;
+
+use std::collections::BTreeMap;
+
+
+
+// ===============
+// === Capture ===
+// ===============
+
+/// Aggregated data of one or more profiles of the same scenario.
+#[derive(Debug, Default)]
+pub struct Capture {
+    aggregator: aggregate::Aggregator,
+    profiles:   usize,
+}
+
+impl Capture {
+    /// Add data from a profile to the capture.
+    pub fn add_profile<Metadata>(&mut self, profile: &crate::Profile<Metadata>) {
+        self.aggregator.add_profile(profile);
+        self.profiles += 1;
+    }
+
+    /// Return the number of profiles added.
+    pub fn profile_count(&self) -> usize {
+        self.profiles
+    }
+
+    /// Build the tree of the timings of each label path, averaged over the profiles.
+    fn into_tree(self) -> Node {
+        let Self { aggregator, profiles } = self;
+        let mut root = Node::default();
+        root.add_children(&aggregator.into());
+        root.scale(1.0 / profiles.max(1) as f64);
+        root
+    }
+}
+
+
+
+// ===============
+// === Timings ===
+// ===============
+
+/// The time spent in the intervals of a stack of profilers, on average per profile.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Timings {
+    /// Duration of the intervals, exclusive of time in child intervals, in milliseconds.
+    pub self_duration:  f64,
+    /// Duration of the intervals, in milliseconds.
+    pub total_duration: f64,
+    /// Number of the intervals.
+    pub intervals:      f64,
+}
+
+
+
+// ============
+// === Node ===
+// ============
+
+/// The timings of a label path, and of the paths extending it.
+#[derive(Debug, Default)]
+struct Node {
+    timings:  Timings,
+    children: BTreeMap<String, Node>,
+}
+
+impl Node {
+    /// Add the timings of the frame's children, merging the children whose labels differ only in
+    /// line numbers.
+    fn add_children(&mut self, frame: &aggregate::Frame) {
+        for (label, child) in &frame.children {
+            let node = self.children.entry(path_segment(label)).or_default();
+            node.timings.self_duration += child.self_duration();
+            node.timings.total_duration += child.total_duration();
+            node.timings.intervals += child.interval_count() as f64;
+            node.add_children(child);
+        }
+    }
+
+    fn scale(&mut self, factor: f64) {
+        self.timings.self_duration *= factor;
+        self.timings.total_duration *= factor;
+        self.timings.intervals *= factor;
+        for child in self.children.values_mut() {
+            child.scale(factor);
+        }
+    }
+}
+
+/// The label without the line number.
+fn path_segment(label: &str) -> String {
+    match label.parse::<crate::Label>() {
+        Ok(crate::Label { name, pos: Some(pos) }) => format!("{} ({})", name, pos.file),
+        Ok(crate::Label { name, pos: None }) => name,
+        Err(_) => label.to_owned(),
+    }
+}
+
+
+
+// =================
+// === FrameDiff ===
+// =================
+
+/// The timings of a label path in both captures.
+#[derive(Clone, Debug)]
+pub struct FrameDiff {
+    /// The labels of the profilers in the stack, outermost first.
+    pub path:     Vec<String>,
+    /// The timings in the baseline capture, if the path occurs there.
+    pub baseline: Option<Timings>,
+    /// The timings in the current capture, if the path occurs there.
+    pub current:  Option<Timings>,
+}
+
+impl FrameDiff {
+    /// Whether the path occurs in both captures, or only in one of them.
+    pub fn status(&self) -> Status {
+        match (self.baseline, self.current) {
+            (Some(_), None) => Status::Missing,
+            (None, Some(_)) => Status::New,
+            _ => Status::Common,
+        }
+    }
+
+    /// Change of the self-duration, in milliseconds. A missing frame has zero duration.
+    pub fn self_delta(&self) -> f64 {
+        let duration = |timings: Option<Timings>| timings.map_or(0.0, |t| t.self_duration);
+        duration(self.current) - duration(self.baseline)
+    }
+
+    /// Change of the total duration, in milliseconds. A missing frame has zero duration.
+    pub fn total_delta(&self) -> f64 {
+        let duration = |timings: Option<Timings>| timings.map_or(0.0, |t| t.total_duration);
+        duration(self.current) - duration(self.baseline)
+    }
+}
+
+
+// === Status ===
+
+/// Identifies the captures a label path occurs in.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// The path occurs in both captures.
+    Common,
+    /// The path occurs only in the current capture.
+    New,
+    /// The path occurs only in the baseline capture.
+    Missing,
+}
+
+
+
+// ============
+// === diff ===
+// ============
+
+/// Align the label paths of both captures, returning the timings of each path occurring in any of
+/// them, in depth-first order.
+pub fn diff(baseline: Capture, current: Capture) -> Vec<FrameDiff> {
+    let baseline = baseline.into_tree();
+    let current = current.into_tree();
+    let mut diffs = Vec::new();
+    let mut path = Vec::new();
+    diff_children(Some(&baseline), Some(&current), &mut path, &mut diffs);
+    diffs
+}
+
+fn diff_children(
+    baseline: Option<&Node>,
+    current: Option<&Node>,
+    path: &mut Vec<String>,
+    diffs: &mut Vec<FrameDiff>,
+) {
+    let baseline_children = baseline.into_iter().flat_map(|node| node.children.keys());
+    let current_children = current.into_iter().flat_map(|node| node.children.keys());
+    let mut labels: Vec<_> = baseline_children.chain(current_children).collect();
+    labels.sort_unstable();
+    labels.dedup();
+    for label in labels {
+        let baseline = baseline.and_then(|node| node.children.get(label));
+        let current = current.and_then(|node| node.children.get(label));
+        path.push(label.clone());
+        diffs.push(FrameDiff {
+            path:     path.clone(),
+            baseline: baseline.map(|node| node.timings),
+            current:  current.map(|node| node.timings),
+        });
+        diff_children(baseline, current, path, diffs);
+        path.pop();
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use enso_profiler::format;
+
+    /// Build a profile of top-level measurements, each containing nested measurements, given as
+    /// `(label, start, end, children)` in milliseconds.
+    fn profile(measurements: &[(&'static str, f64, f64, &[(&'static str, f64, f64)])]) -> Capture {
+        let time = format::Timestamp::from_ms;
+        let mut log = format::Builder::new();
+        for &(label, start, end, children) in measurements {
+            let parent = log.create(Some(time(start)), format::Parent::root(), label);
+            log.start(time(start), parent);
+            for &(label, start, end) in children {
+                let child = log.create(Some(time(start)), parent.into(), label);
+                log.start(time(start), child);
+                log.end(time(end), child);
+            }
+            log.end(time(end), parent);
+        }
+        let profile: crate::Profile<crate::OpaqueMetadata> = log.build_string().parse().unwrap();
+        let mut capture = Capture::default();
+        capture.add_profile(&profile);
+        capture
+    }
+
+    #[test]
+    fn diff_aligned_frames() {
+        let baseline = profile(&[
+            ("parent (a.rs:1)", 0.0, 4.0, &[("child (a.rs:5)", 1.0, 2.0)]),
+            ("removed (a.rs:9)", 5.0, 6.0, &[]),
+        ]);
+        let current = profile(&[
+            // The line numbers changed.
+            ("parent (a.rs:2)", 0.0, 6.0, &[("child (a.rs:6)", 1.0, 4.0)]),
+            ("added (b.rs:1)", 7.0, 9.0, &[]),
+        ]);
+        let diffs = diff(baseline, current);
+        let summary: Vec<_> = diffs
+            .iter()
+            .map(|diff| (diff.path.join("/"), diff.status(), diff.self_delta(), diff.total_delta()))
+            .collect();
+        let expected = vec![
+            ("added (b.rs)".to_owned(), Status::New, 2.0, 2.0),
+            ("parent (a.rs)".to_owned(), Status::Common, 0.0, 2.0),
+            ("parent (a.rs)/child (a.rs)".to_owned(), Status::Common, 2.0, 2.0),
+            ("removed (a.rs)".to_owned(), Status::Missing, -1.0, -1.0),
+        ];
+        assert_eq!(summary, expected);
+    }
+
+    #[test]
+    fn average_over_profiles() {
+        let mut capture = Capture::default();
+        for duration in [1.0, 3.0] {
+            let time = format::Timestamp::from_ms;
+            let mut log = format::Builder::new();
+            let id = log.create(Some(time(0.0)), format::Parent::root(), "work (a.rs:1)");
+            log.start(time(0.0), id);
+            log.end(time(duration), id);
+            let profile: crate::Profile<crate::OpaqueMetadata> =
+                log.build_string().parse().unwrap();
+            capture.add_profile(&profile);
+        }
+        let diffs = diff(Capture::default(), capture);
+        let timings = Timings { self_duration: 2.0, total_duration: 2.0, intervals: 1.0 };
+        assert_eq!(diffs.len(), 1);
+        assert_eq!(diffs[0].current, Some(timings));
+    }
+}
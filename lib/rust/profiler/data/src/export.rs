@@ -0,0 +1,36 @@
+//! Exporters of profiles to the formats of third-party profile viewers.
+
+
+// ==============
+// === Export ===
+// ==============
+
+pub mod chrome_trace;
+pub mod speedscope;
+
+
+
+// =================
+// === Test data ===
+// =================
+
+/// A small profile with nested and async measurements and metadata, shared by the golden-file
+/// tests of the exporters.
+#[cfg(test)]
+pub(crate) fn example_profile() -> crate::Profile<crate::OpaqueMetadata> {
+    use enso_profiler::format;
+    let time = format::Timestamp::from_ms;
+    let mut log = format::Builder::new();
+    log.process("Ide");
+    let parent = log.create(Some(time(1.0)), format::Parent::root(), "parent (app/a.rs:1)");
+    log.start(time(1.0), parent);
+    let child = log.create(Some(time(2.0)), parent.into(), "child (app/a.rs:5)");
+    log.start(time(2.0), child);
+    log.metadata(time(2.5), "Note", "awaiting");
+    log.pause(time(3.0), child);
+    log.end(time(4.0), parent);
+    log.start(time(5.0), child);
+    log.end(time(6.0), child);
+    log.metadata(time(7.0), "Note", "done");
+    log.build_string().parse().unwrap()
+}
@@ -0,0 +1,342 @@
+//! Export to the Chrome Trace Event format, supported by `chrome://tracing`, Perfetto and the
+//! Chrome DevTools.
+//!
+//! Each profile becomes a process with a single thread, whose track shows the closed intervals as
+//! nested complete events. Measurements active in more than one interval, like the profilers of
+//! async functions, are shown as async events spanning from their creation to their end. Metadata
+//! is written as instant events, with the serialized metadata as the event args.
+//!
+//! The format is described in:
+//! https://docs.google.com/document/d/1CvAClvFfyA5R-PhYUmn5OOQtYMH4h6I0nSsKchNAySU/preview
+
+use crate as data;
+
+use serde::Serialize;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The id of the thread presenting the intervals of a profile.
+const MAIN_THREAD: u32 = 1;
+
+const INTERVAL_CATEGORY: &str = "interval";
+const ASYNC_CATEGORY: &str = "async";
+const METADATA_CATEGORY: &str = "metadata";
+const TRACE_METADATA_CATEGORY: &str = "__metadata";
+
+/// The name of instant events for metadata that is not an enum variant.
+const UNNAMED_METADATA: &str = "Metadata";
+
+
+
+// =============
+// === Trace ===
+// =============
+
+/// A trace file, containing the events of one or more processes.
+#[derive(Clone, Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Trace {
+    /// The events, in no particular order.
+    pub trace_events:      Vec<Event>,
+    /// The unit used by the viewer to display the times.
+    pub display_time_unit: DisplayTimeUnit,
+    /// The time, in milliseconds since the Unix Epoch, corresponding to the zero timestamp.
+    #[serde(skip)]
+    origin:                Option<f64>,
+}
+
+impl Trace {
+    /// Add the events of the profile, as a new process.
+    ///
+    /// The times of the events are aligned using the profiles' [`data::Headers::time_offset`]; the
+    /// time origin of the first added profile is the zero timestamp of the trace.
+    pub fn add_profile<M: Serialize>(&mut self, profile: &data::Profile<M>) -> Process {
+        let time_offset = profile.headers.time_offset.map_or(0.0, |offset| offset.into_ms());
+        let origin = *self.origin.get_or_insert(time_offset);
+        let id = self.process_count() + 1;
+        let process = Process { id, time_offset: time_offset - origin };
+        let name = match &profile.headers.process {
+            Some(name) => name.clone(),
+            None => format!("Process {}", id),
+        };
+        self.push(process.metadata("process_name", &name));
+        self.push(process.metadata("thread_name", "Main"));
+        for &child in &profile.root_interval().children {
+            self.add_interval(profile, process, child);
+        }
+        for metadata in profile.metadata() {
+            self.push(process.metadata_event(metadata));
+        }
+        for id in profile.measurement_ids() {
+            let measurement = &profile[id];
+            let last_interval = measurement.intervals.last().map(|&interval| &profile[interval]);
+            let end = last_interval.and_then(|interval| interval.interval.end);
+            let is_async = measurement.finished && measurement.intervals.len() > 1;
+            if let Some(end) = end.filter(|_| is_async) {
+                let name = measurement.label.to_string();
+                let id = format!("{}.{}", process.id, id.0);
+                let start = measurement.created;
+                let category = ASYNC_CATEGORY;
+                let begin = process.async_event(Phase::AsyncBegin, &name, category, &id, start);
+                let end = process.async_event(Phase::AsyncEnd, &name, category, &id, end);
+                self.push(begin);
+                self.push(end);
+            }
+        }
+        process
+    }
+
+    /// Add the interval as a complete event, if it is closed; recurse into children.
+    fn add_interval<M>(
+        &mut self,
+        profile: &data::Profile<M>,
+        process: Process,
+        id: data::IntervalId,
+    ) {
+        let active = &profile[id];
+        if let Some(duration) = active.interval.duration_ms() {
+            let name = profile[active.measurement].label.to_string();
+            let timestamp = process.timestamp_us(active.interval.start);
+            let event = Event {
+                name,
+                category: INTERVAL_CATEGORY.to_owned(),
+                phase: Phase::Complete,
+                timestamp,
+                duration: Some(duration * 1000.0),
+                process_id: process.id,
+                thread_id: MAIN_THREAD,
+                id: None,
+                scope: None,
+                args: None,
+            };
+            self.push(event);
+        }
+        for &child in &active.children {
+            self.add_interval(profile, process, child);
+        }
+    }
+
+    /// Add an event.
+    pub fn push(&mut self, event: Event) {
+        self.trace_events.push(event);
+    }
+
+    fn process_count(&self) -> u32 {
+        let processes = self.trace_events.iter().map(|event| event.process_id);
+        processes.max().unwrap_or_default()
+    }
+}
+
+
+// === DisplayTimeUnit ===
+
+/// The unit used by the viewer to display the times.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum DisplayTimeUnit {
+    #[serde(rename = "ms")]
+    Milliseconds,
+    #[serde(rename = "ns")]
+    Nanoseconds,
+}
+
+impl Default for DisplayTimeUnit {
+    fn default() -> Self {
+        DisplayTimeUnit::Milliseconds
+    }
+}
+
+
+
+// ===============
+// === Process ===
+// ===============
+
+/// A process of the trace, corresponding to one profile.
+#[derive(Clone, Copy, Debug)]
+pub struct Process {
+    /// The process id used in the events.
+    pub id:      u32,
+    /// The offset of the profile's time origin from the trace's zero timestamp, in milliseconds.
+    time_offset: f64,
+}
+
+impl Process {
+    /// Convert the profile's timestamp to the trace time, in microseconds.
+    pub fn timestamp_us(self, time: data::Timestamp) -> f64 {
+        (time.into_ms() + self.time_offset) * 1000.0
+    }
+
+    /// An event naming the process or its thread.
+    fn metadata(self, kind: &str, name: &str) -> Event {
+        Event {
+            name:       kind.to_owned(),
+            category:   TRACE_METADATA_CATEGORY.to_owned(),
+            phase:      Phase::Metadata,
+            timestamp:  0.0,
+            duration:   None,
+            process_id: self.id,
+            thread_id:  MAIN_THREAD,
+            id:         None,
+            scope:      None,
+            args:       Some(serde_json::json!({ "name": name })),
+        }
+    }
+
+    /// An instant event of the metadata. The metadata enum variants are serialized as single-entry
+    /// objects, and the entry key is used as the event name.
+    fn metadata_event<M: Serialize>(self, metadata: &data::Timestamped<M>) -> Event {
+        let data = serde_json::to_value(&metadata.data).unwrap_or_else(|error| {
+            serde_json::Value::String(format!("Cannot serialize metadata: {}", error))
+        });
+        let (name, args) = match data {
+            serde_json::Value::Object(object) if object.len() == 1 => {
+                let name = object.keys().next().cloned().unwrap_or_default();
+                (name, serde_json::Value::Object(object))
+            }
+            serde_json::Value::Object(object) =>
+                (UNNAMED_METADATA.to_owned(), serde_json::Value::Object(object)),
+            data => (UNNAMED_METADATA.to_owned(), serde_json::json!({ "data": data })),
+        };
+        Event {
+            name,
+            category: METADATA_CATEGORY.to_owned(),
+            phase: Phase::Instant,
+            timestamp: self.timestamp_us(metadata.time),
+            duration: None,
+            process_id: self.id,
+            thread_id: MAIN_THREAD,
+            id: None,
+            scope: Some(Scope::Thread),
+            args: Some(args),
+        }
+    }
+
+    /// The beginning or the end of an async event. The events with the same category and id are
+    /// shown as a single span.
+    pub fn async_event(
+        self,
+        phase: Phase,
+        name: &str,
+        category: &str,
+        id: &str,
+        time: data::Timestamp,
+    ) -> Event {
+        Event {
+            name: name.to_owned(),
+            category: category.to_owned(),
+            phase,
+            timestamp: self.timestamp_us(time),
+            duration: None,
+            process_id: self.id,
+            thread_id: MAIN_THREAD,
+            id: Some(id.to_owned()),
+            scope: None,
+            args: None,
+        }
+    }
+}
+
+
+
+// =============
+// === Event ===
+// =============
+
+/// A trace event.
+#[derive(Clone, Debug, Serialize)]
+pub struct Event {
+    /// The name displayed in the viewer.
+    pub name:       String,
+    /// The comma-separated categories, used for filtering and for matching the async events.
+    #[serde(rename = "cat")]
+    pub category:   String,
+    /// The event type.
+    #[serde(rename = "ph")]
+    pub phase:      Phase,
+    /// The time of the event, in microseconds.
+    #[serde(rename = "ts")]
+    pub timestamp:  f64,
+    /// The duration of a complete event, in microseconds.
+    #[serde(rename = "dur", skip_serializing_if = "Option::is_none")]
+    pub duration:   Option<f64>,
+    #[allow(missing_docs)]
+    #[serde(rename = "pid")]
+    pub process_id: u32,
+    #[allow(missing_docs)]
+    #[serde(rename = "tid")]
+    pub thread_id:  u32,
+    /// Identifies the span of an async event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id:         Option<String>,
+    /// The scope of an instant event.
+    #[serde(rename = "s", skip_serializing_if = "Option::is_none")]
+    pub scope:      Option<Scope>,
+    /// Arbitrary data displayed with the event.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub args:       Option<serde_json::Value>,
+}
+
+
+// === Phase ===
+
+/// The type of an [`Event`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Phase {
+    /// An event with a start time and a duration.
+    #[serde(rename = "X")]
+    Complete,
+    /// An event without a duration.
+    #[serde(rename = "i")]
+    Instant,
+    /// The beginning of an async event.
+    #[serde(rename = "b")]
+    AsyncBegin,
+    /// The end of an async event.
+    #[serde(rename = "e")]
+    AsyncEnd,
+    /// Information about a process or a thread.
+    #[serde(rename = "M")]
+    Metadata,
+}
+
+
+// === Scope ===
+
+/// The track an instant event is drawn on.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+pub enum Scope {
+    #[serde(rename = "g")]
+    Global,
+    #[serde(rename = "p")]
+    Process,
+    #[serde(rename = "t")]
+    Thread,
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOLDEN: &str = include_str!("golden/chrome_trace.json");
+
+    #[test]
+    fn export_example_profile() {
+        let profile = crate::export::example_profile();
+        let mut trace = Trace::default();
+        trace.add_profile(&profile);
+        let expected: serde_json::Value = serde_json::from_str(GOLDEN).unwrap();
+        assert_eq!(serde_json::to_value(&trace).unwrap(), expected);
+    }
+}
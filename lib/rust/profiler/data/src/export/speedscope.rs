@@ -0,0 +1,285 @@
+//! Export to the [speedscope](https://www.speedscope.app) file format.
+//!
+//! A profile is exported as an evented profile, showing the closed intervals on a timeline. The
+//! time spent in each stack of profilers, aggregated by an [`aggregate::Aggregator`], is exported
+//! as a sampled profile, with a single sample of each stack weighted by its self-duration; it is
+//! best viewed in the "Left Heavy" and "Sandwich" views.
+//!
+//! The format is described in: https://www.speedscope.app/file-format-schema.json
+
+use crate as data;
+use crate::aggregate;
+
+use serde::Serialize;
+use std::collections::HashMap;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+const SCHEMA: &str = "https://www.speedscope.app/file-format-schema.json";
+const EXPORTER: &str = "enso-profiler-data";
+
+
+
+// ============
+// === File ===
+// ============
+
+/// A speedscope file, containing any number of profiles sharing the frames.
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct File {
+    #[serde(rename = "$schema")]
+    schema:               &'static str,
+    shared:               Shared,
+    profiles:             Vec<Profile>,
+    name:                 String,
+    active_profile_index: usize,
+    exporter:             &'static str,
+    /// Indices of the frames, by the profiler labels.
+    #[serde(skip)]
+    frame_indices:        HashMap<String, usize>,
+}
+
+/// The data shared by the profiles of a [`File`].
+#[derive(Clone, Debug, Default, Serialize)]
+struct Shared {
+    frames: Vec<Frame>,
+}
+
+impl File {
+    /// Create a file without profiles.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            schema:               SCHEMA,
+            shared:               Default::default(),
+            profiles:             Default::default(),
+            name:                 name.into(),
+            active_profile_index: 0,
+            exporter:             EXPORTER,
+            frame_indices:        Default::default(),
+        }
+    }
+
+    /// Add the timeline of the profile's intervals as an evented profile.
+    pub fn add_profile<M>(&mut self, profile: &data::Profile<M>) {
+        let mut events = Vec::new();
+        for &child in &profile.root_interval().children {
+            self.add_interval(profile, child, None, &mut events);
+        }
+        let start_value = events.first().map_or(0.0, |event| event.at);
+        let end_value = events.last().map_or(0.0, |event| event.at);
+        let name = profile.headers.process.clone().unwrap_or_else(|| "Profile".into());
+        let unit = Unit::Milliseconds;
+        self.profiles.push(Profile::Evented { name, unit, start_value, end_value, events });
+    }
+
+    /// Add the open and close events of the interval, if it is closed; recurse into children.
+    /// The times are clamped to the parent interval, as the events must be properly nested.
+    fn add_interval<M>(
+        &mut self,
+        profile: &data::Profile<M>,
+        id: data::IntervalId,
+        parent: Option<(f64, f64)>,
+        events: &mut Vec<Event>,
+    ) {
+        let active = &profile[id];
+        let start = active.interval.start.into_ms();
+        let end = match active.interval.end {
+            Some(end) => end.into_ms(),
+            // Open intervals are skipped, together with their children.
+            None => return,
+        };
+        let (start, end) = match parent {
+            Some((parent_start, parent_end)) =>
+                (start.max(parent_start).min(parent_end), end.max(parent_start).min(parent_end)),
+            None => (start, end),
+        };
+        let measurement = &profile[active.measurement];
+        let frame = self.frame(&measurement.label);
+        events.push(Event { kind: EventKind::Open, frame, at: start });
+        let mut children = active.children.clone();
+        children.sort_by_key(|&child| profile[child].interval.start);
+        for child in children {
+            self.add_interval(profile, child, Some((start, end)), events);
+        }
+        events.push(Event { kind: EventKind::Close, frame, at: end });
+    }
+
+    /// Add the aggregated time of each stack of profilers as a sampled profile.
+    pub fn add_aggregate(&mut self, name: impl Into<String>, aggregator: aggregate::Aggregator) {
+        let root = aggregate::Frame::from(aggregator);
+        let mut samples = Vec::new();
+        let mut weights = Vec::new();
+        let mut stack = Vec::new();
+        self.add_aggregate_frames(&root, &mut stack, &mut samples, &mut weights);
+        let name = name.into();
+        let unit = Unit::Milliseconds;
+        let start_value = 0.0;
+        let end_value = weights.iter().sum();
+        self.profiles.push(Profile::Sampled {
+            name,
+            unit,
+            start_value,
+            end_value,
+            samples,
+            weights,
+        });
+    }
+
+    /// Add a sample for each child of the frame, and recurse into it. The children are visited in
+    /// the order of their labels, to make the output deterministic.
+    fn add_aggregate_frames(
+        &mut self,
+        frame: &aggregate::Frame,
+        stack: &mut Vec<usize>,
+        samples: &mut Vec<Vec<usize>>,
+        weights: &mut Vec<f64>,
+    ) {
+        let mut children = frame.children.iter().collect::<Vec<_>>();
+        children.sort_by_key(|(label, _)| *label);
+        for (label, child) in children {
+            let index = match self.frame_indices.get(&**label) {
+                Some(index) => *index,
+                None => self.add_frame(label.to_string(), Frame::from_name(label.to_string())),
+            };
+            stack.push(index);
+            let weight = child.self_duration();
+            if weight > 0.0 {
+                samples.push(stack.clone());
+                weights.push(weight);
+            }
+            self.add_aggregate_frames(child, stack, samples, weights);
+            stack.pop();
+        }
+    }
+
+    /// The index of the frame of the profiler label, added if needed.
+    fn frame(&mut self, label: &data::Label) -> usize {
+        let key = label.to_string();
+        match self.frame_indices.get(&key) {
+            Some(index) => *index,
+            None => self.add_frame(key, Frame::from_label(label)),
+        }
+    }
+
+    fn add_frame(&mut self, key: String, frame: Frame) -> usize {
+        let index = self.shared.frames.len();
+        self.shared.frames.push(frame);
+        self.frame_indices.insert(key, index);
+        index
+    }
+}
+
+
+
+// =============
+// === Frame ===
+// =============
+
+/// A profiler, as shown in the viewer.
+#[derive(Clone, Debug, Serialize)]
+struct Frame {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line: Option<u32>,
+}
+
+impl Frame {
+    fn from_label(label: &data::Label) -> Self {
+        let name = label.name.clone();
+        let file = label.pos.as_ref().map(|pos| pos.file.clone());
+        let line = label.pos.as_ref().map(|pos| pos.line);
+        Self { name, file, line }
+    }
+
+    fn from_name(name: String) -> Self {
+        Self { name, file: None, line: None }
+    }
+}
+
+
+
+// ===============
+// === Profile ===
+// ===============
+
+/// A profile of a [`File`].
+#[derive(Clone, Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum Profile {
+    #[serde(rename_all = "camelCase")]
+    Evented {
+        name:        String,
+        unit:        Unit,
+        start_value: f64,
+        end_value:   f64,
+        events:      Vec<Event>,
+    },
+    #[serde(rename_all = "camelCase")]
+    Sampled {
+        name:        String,
+        unit:        Unit,
+        start_value: f64,
+        end_value:   f64,
+        samples:     Vec<Vec<usize>>,
+        weights:     Vec<f64>,
+    },
+}
+
+/// The unit of the values in a profile.
+#[derive(Clone, Copy, Debug, Serialize)]
+#[serde(rename_all = "lowercase")]
+enum Unit {
+    Milliseconds,
+}
+
+
+// === Event ===
+
+/// An event of an evented profile.
+#[derive(Clone, Copy, Debug, Serialize)]
+struct Event {
+    #[serde(rename = "type")]
+    kind:  EventKind,
+    frame: usize,
+    at:    f64,
+}
+
+#[derive(Clone, Copy, Debug, Serialize)]
+enum EventKind {
+    #[serde(rename = "O")]
+    Open,
+    #[serde(rename = "C")]
+    Close,
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GOLDEN: &str = include_str!("golden/speedscope.json");
+
+    #[test]
+    fn export_example_profile() {
+        let profile = crate::export::example_profile();
+        let mut file = File::new("example");
+        file.add_profile(&profile);
+        let mut aggregator = aggregate::Aggregator::default();
+        aggregator.add_profile(&profile);
+        file.add_aggregate("Aggregate", aggregator);
+        let expected: serde_json::Value = serde_json::from_str(GOLDEN).unwrap();
+        assert_eq!(serde_json::to_value(&file).unwrap(), expected);
+    }
+}
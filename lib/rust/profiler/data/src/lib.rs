@@ -96,6 +96,8 @@ use std::rc::Rc;
 // ==============
 
 pub mod aggregate;
+pub mod diff;
+pub mod export;
 pub mod parse;
 
 
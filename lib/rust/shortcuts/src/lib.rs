@@ -76,10 +76,26 @@ lazy_static! {
     static ref SIDE_KEYS_SET: HashSet<&'static str> = SIDE_KEYS.iter().copied().collect();
 }
 
+/// Check whether the key is a side key, like `ctrl` or `ctrl-left`.
+fn is_side_key(key: &str) -> bool {
+    let name = key.strip_suffix("-left").or_else(|| key.strip_suffix("-right")).unwrap_or(key);
+    SIDE_KEYS_SET.contains(name)
+}
+
+/// Split the expression to the strokes of a key sequence. The strokes are separated by commas,
+/// like in "ctrl k, ctrl s".
+fn sequence_strokes(expr: &str) -> Vec<&str> {
+    expr.split(',').map(|t| t.trim()).filter(|t| !t.is_empty()).collect()
+}
+
 /// The maximum time difference between presses/clicks where they are treated as single
 /// `DoublePress`/`DoubleClick` event.
 pub const DOUBLE_EVENT_TIME_MS: f32 = 300.0;
 
+/// The maximum time difference between the strokes of a key sequence, like "ctrl k, ctrl s". If
+/// the next stroke is not pressed in this time, the sequence is cancelled.
+pub const SEQUENCE_TIMEOUT_MS: f32 = 1000.0;
+
 
 
 // ==================
@@ -90,7 +106,8 @@ pub const DOUBLE_EVENT_TIME_MS: f32 = 300.0;
 /// As a clarification, the event `DoublePress` is emitted on second press of a button/key happening
 /// in short time interval from the first one. `DoubleClick`, on the other hand, happens on release,
 /// not on press.
-#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
 #[allow(missing_docs)]
 pub enum ActionType {
     Press,
@@ -111,7 +128,8 @@ pub use ActionType::*;
 #[allow(missing_docs)]
 pub trait Registry<T>: Default {
     /// Add a new action mapping. `The expr` needs to be a list of keys separated by space, like
-    /// "ctrl shift a".
+    /// "ctrl shift a", or a sequence of such lists separated by commas, like "ctrl k, ctrl s". See
+    /// the docs of [`Sequences`] to learn how the sequences are resolved.
     fn add(&self, action_type: ActionType, expr: impl AsRef<str>, action: impl Into<T>);
 
     /// Get a list of items registered for the action that just happened. It might include items
@@ -129,6 +147,92 @@ pub trait Registry<T>: Default {
 
 
 
+// =================
+// === Sequences ===
+// =================
+
+/// A key sequence, like "ctrl k, ctrl s". The representation of the strokes depends on the
+/// registry implementation.
+#[derive(Clone, Debug)]
+struct Sequence<Stroke, T> {
+    strokes:     Vec<Stroke>,
+    action_type: ActionType,
+    action:      T,
+}
+
+/// Key sequences registered in a shortcut registry, and the state of their resolution.
+///
+/// All strokes of a sequence but the last one are matched on key press. After a stroke is matched,
+/// the sequence is pending until the next stroke is pressed, until a stroke not continuing it is
+/// pressed, or until [`SEQUENCE_TIMEOUT_MS`] passes. Pressing only the side keys, like `ctrl`, does
+/// not cancel the pending sequences, as they are a part of the next stroke. The action is emitted
+/// when the last stroke is pressed or, for the `Release` and `DoubleClick` action types, on the
+/// following release. When a pending sequence is continued, the actions registered for the single
+/// stroke are not emitted.
+#[derive(Debug, Derivative)]
+#[derivative(Default(bound = ""))]
+pub struct Sequences<Stroke, T> {
+    sequences: Vec<Sequence<Stroke, T>>,
+    /// The indexes of pending sequences, with the number of their matched strokes.
+    pending:   Vec<(usize, usize)>,
+    /// The time the last stroke was matched.
+    last_time: f32,
+    /// The indexes of sequences which will emit their actions on the next release.
+    completed: Vec<usize>,
+}
+
+impl<Stroke, T: Clone> Sequences<Stroke, T> {
+    fn add(&mut self, strokes: Vec<Stroke>, action_type: ActionType, action: T) {
+        self.sequences.push(Sequence { strokes, action_type, action });
+    }
+
+    /// Process the press event. The `matches` function checks whether the stroke is pressed.
+    /// Returns the emitted actions, and whether a pending sequence was continued.
+    fn on_press(
+        &mut self,
+        only_side_keys: bool,
+        matches: impl Fn(&Stroke) -> bool,
+    ) -> (Vec<T>, bool) {
+        let time = web::time_from_start() as f32;
+        if time - self.last_time > SEQUENCE_TIMEOUT_MS {
+            self.pending.clear();
+        }
+        self.completed.clear();
+        let mut out = vec![];
+        let mut pending = vec![];
+        let mut continued = false;
+        let started = (0..self.sequences.len()).map(|ix| (ix, 0));
+        for (ix, matched) in mem::take(&mut self.pending).into_iter().chain(started) {
+            let sequence = &self.sequences[ix];
+            if matches(&sequence.strokes[matched]) {
+                continued |= matched > 0;
+                self.last_time = time;
+                if matched + 1 < sequence.strokes.len() {
+                    pending.push((ix, matched + 1));
+                } else if matches!(sequence.action_type, Release | DoubleClick) {
+                    self.completed.push(ix);
+                } else {
+                    out.push(sequence.action.clone());
+                }
+            } else if only_side_keys && matched > 0 {
+                pending.push((ix, matched));
+            }
+        }
+        self.pending = pending.into_iter().sorted().dedup().collect();
+        (out, continued)
+    }
+
+    /// Process the release event. Returns the emitted actions, and whether a pending sequence was
+    /// completed.
+    fn on_release(&mut self) -> (Vec<T>, bool) {
+        let completed = mem::take(&mut self.completed);
+        let out = completed.iter().map(|ix| self.sequences[*ix].action.clone()).collect_vec();
+        (out, !completed.is_empty())
+    }
+}
+
+
+
 // =============================
 // === AutomataRegistryModel ===
 // =============================
@@ -153,6 +257,7 @@ pub struct AutomataRegistryModel<T> {
     action_map:    HashMap<ActionType, HashMap<nfa::State, T>>,
     press_times:   HashMap<dfa::State, f32>,
     release_times: HashMap<dfa::State, f32>,
+    sequences:     Sequences<nfa::State, T>,
 }
 
 
@@ -185,6 +290,7 @@ impl<T> AutomataRegistryModel<T> {
         let action_map = default();
         let press_times = default();
         let release_times = default();
+        let sequences = default();
         Self {
             dirty,
             nfa,
@@ -197,6 +303,7 @@ impl<T> AutomataRegistryModel<T> {
             action_map,
             press_times,
             release_times,
+            sequences,
         }
     }
 }
@@ -205,6 +312,18 @@ impl<T: Clone> AutomataRegistryModel<T> {
     fn add(&mut self, action_type: ActionType, expr: impl AsRef<str>, action: impl Into<T>) {
         self.dirty = true;
         let expr = expr.as_ref();
+        let strokes = sequence_strokes(expr);
+        if strokes.len() > 1 {
+            let strokes = strokes
+                .into_iter()
+                .map(|stroke| {
+                    let keys = stroke.split(' ').map(|t| t.trim()).collect_vec();
+                    self.add_key_permutations(self.nfa.start, keys)
+                })
+                .collect();
+            self.sequences.add(strokes, action_type, action.into());
+            return;
+        }
         let end_state = if let Some(key) = expr.strip_prefix('-') {
             let key = format!("-{}", key.trim().to_lowercase());
             let sym = Symbol::new_named(hash(&key), key);
@@ -359,9 +478,15 @@ impl<T: Clone> AutomataRegistryModel<T> {
         if is_double {
             actions.extend(nfa_states.iter().filter_map(|t| self.get_action(action2, *t)));
         }
-        if press {
+        let (sequence_actions, continued) = if press {
             self.pressed.insert(input);
             self.press_times.insert(focus_state, new_time);
+            let only_side_keys = self.pressed.iter().all(|key| is_side_key(key));
+            let dfa = &self.dfa;
+            let current = self.current;
+            self.sequences.on_press(only_side_keys, |stroke| {
+                !current.is_invalid() && dfa.sources[current.id()].contains(stroke)
+            })
         } else {
             self.pressed.remove(&input);
             self.release_times.insert(focus_state, new_time);
@@ -369,8 +494,13 @@ impl<T: Clone> AutomataRegistryModel<T> {
                 self.current = Dfa::START_STATE;
             }
             self.reset_to_known_state();
+            self.sequences.on_release()
+        };
+        if continued {
+            sequence_actions
+        } else {
+            actions.extended(sequence_actions)
         }
-        actions
     }
 
     fn reset_to_known_state(&mut self) {
@@ -494,6 +624,7 @@ pub struct HashSetRegistryModel<T> {
     release_times: HashMap<String, f32>,
     side_keys:     HashMap<String, Vec<String>>,
     key_aliases:   HashMap<String, String>,
+    sequences:     Sequences<Vec<String>, T>,
 }
 
 impl<T> HashSetRegistryModel<T> {
@@ -506,8 +637,18 @@ impl<T> HashSetRegistryModel<T> {
         let release_times = default();
         let side_keys = default();
         let key_aliases = key_aliases();
-        Self { current_expr, actions, pressed, press_times, release_times, side_keys, key_aliases }
-            .init()
+        let sequences = default();
+        Self {
+            current_expr,
+            actions,
+            pressed,
+            press_times,
+            release_times,
+            side_keys,
+            key_aliases,
+            sequences,
+        }
+        .init()
     }
 
     fn init(mut self) -> Self {
@@ -528,6 +669,12 @@ impl<T: HashSetRegistryItem> HashSetRegistryModel<T> {
     pub fn add(&mut self, action_type: ActionType, input: impl AsRef<str>, action: impl Into<T>) {
         let input = input.as_ref();
         let action = action.into();
+        let strokes = sequence_strokes(input);
+        if strokes.len() > 1 {
+            let strokes = strokes.into_iter().map(|stroke| self.possible_exprs(stroke)).collect();
+            self.sequences.add(strokes, action_type, action);
+            return;
+        }
         let exprs = self.possible_exprs(input);
         let map = self.actions.entry(action_type).or_default();
         for expr in exprs {
@@ -548,7 +695,21 @@ impl<T: HashSetRegistryItem> HashSetRegistryModel<T> {
                 self.pressed.remove(&input);
             }
             self.current_expr = self.current_expr();
-            out.extended(self.process_event(Press)).extended(self.process_event(PressAndRepeat))
+            let out = out
+                .extended(self.process_event(Press))
+                .extended(self.process_event(PressAndRepeat));
+            let (sequence_out, continued) = if press {
+                let only_side_keys = self.pressed.iter().all(|key| is_side_key(key));
+                let current_expr = &self.current_expr;
+                self.sequences.on_press(only_side_keys, |stroke| stroke.contains(current_expr))
+            } else {
+                self.sequences.on_release()
+            };
+            if continued {
+                sequence_out
+            } else {
+                out.extended(sequence_out)
+            }
         } else {
             if press {
                 self.process_event(PressAndRepeat)
@@ -719,6 +880,19 @@ impl<T: HashSetRegistryItem> Registry<T> for HashSetRegistry<T> {
 mod tests {
     use super::*;
 
+    use std::sync::Mutex;
+    use std::sync::MutexGuard;
+
+    lazy_static! {
+        static ref TIME_LOCK: Mutex<()> = default();
+    }
+
+    /// Lock the simulated time. The tests simulating the passage of time share the global time
+    /// offset, so they can not be run in parallel.
+    fn lock_time() -> MutexGuard<'static, ()> {
+        TIME_LOCK.lock().unwrap_or_else(|error| error.into_inner())
+    }
+
     // === Press ===
 
     #[test]
@@ -770,6 +944,7 @@ mod tests {
     // #[test] fn automata_registry_double_press() { double_press::<AutomataRegistry<i32>>(); }
     #[test]
     fn hash_set_registry_double_press() {
+        let _time = lock_time();
         double_press::<HashSetRegistry<i32>>();
     }
     fn double_press<T: Registry<i32>>() -> T {
@@ -940,6 +1115,67 @@ mod tests {
         assert_eq!(registry.on_press("a"), vec![0]);
         registry
     }
+
+
+    // === Key Sequences ===
+
+    #[test]
+    fn automata_registry_key_sequence() {
+        let _time = lock_time();
+        key_sequence::<AutomataRegistry<i32>>();
+    }
+    #[test]
+    fn hash_set_registry_key_sequence() {
+        let _time = lock_time();
+        key_sequence::<HashSetRegistry<i32>>();
+    }
+    fn key_sequence<T: Registry<i32>>() -> T {
+        let nothing = Vec::<i32>::new();
+        let registry: T = default();
+        registry.add(Press, "ctrl k, ctrl s", 0);
+        registry.add(Press, "ctrl s", 1);
+        registry.add(Release, "ctrl k, a, b", 2);
+        // The sequence.
+        assert_eq!(registry.on_press("ctrl-left"), nothing);
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_press("s"), vec![0]);
+        assert_eq!(registry.on_release("s"), nothing);
+        // The single stroke, as the sequence was completed.
+        assert_eq!(registry.on_press("s"), vec![1]);
+        assert_eq!(registry.on_release("s"), nothing);
+        // The side keys can be released and pressed again between the strokes.
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_release("ctrl-left"), nothing);
+        assert_eq!(registry.on_press("ctrl-right"), nothing);
+        assert_eq!(registry.on_press("s"), vec![0]);
+        assert_eq!(registry.on_release("s"), nothing);
+        // A stroke not continuing the sequence cancels it.
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_press("x"), nothing);
+        assert_eq!(registry.on_release("x"), nothing);
+        assert_eq!(registry.on_release("ctrl-right"), nothing);
+        assert_eq!(registry.on_press("ctrl-left"), nothing);
+        assert_eq!(registry.on_press("s"), vec![1]);
+        assert_eq!(registry.on_release("s"), nothing);
+        // The sequence times out.
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        web::simulate_sleep((SEQUENCE_TIMEOUT_MS + 100.0).into());
+        assert_eq!(registry.on_press("s"), vec![1]);
+        assert_eq!(registry.on_release("s"), nothing);
+        // The action of a sequence with the `Release` action type is emitted on release.
+        assert_eq!(registry.on_press("k"), nothing);
+        assert_eq!(registry.on_release("k"), nothing);
+        assert_eq!(registry.on_release("ctrl-left"), nothing);
+        assert_eq!(registry.on_press("a"), nothing);
+        assert_eq!(registry.on_release("a"), nothing);
+        assert_eq!(registry.on_press("b"), nothing);
+        assert_eq!(registry.on_release("b"), vec![2]);
+        registry
+    }
 }
 
 
@@ -0,0 +1,302 @@
+//! Computing the differences between two texts as a minimal list of changes.
+//!
+//! The texts are compared line by line using the Myers algorithm (see "An O(ND) Difference
+//! Algorithm and Its Variations", E. W. Myers, 1986). Every group of adjacent lines which differ is
+//! then narrowed to the characters between the common prefix and suffix of the group, so that
+//! editing a few characters in a line does not replace the whole line.
+
+use crate::prelude::*;
+use crate::unit::*;
+
+use crate::range::Range;
+use crate::text::Change;
+use crate::text::Text;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The largest edit distance computed exactly. The memory used by the Myers algorithm grows with
+/// the square of the distance, so sequences differing more are replaced as a whole instead.
+pub const MAX_EDIT_DISTANCE: usize = 1000;
+
+
+
+// ============
+// === Hunk ===
+// ============
+
+/// A group of adjacent elements of the source sequence replaced with a group of adjacent elements
+/// of the target sequence. Any of the groups may be empty.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Hunk {
+    /// The indices of the replaced elements of the source sequence.
+    pub source: std::ops::Range<usize>,
+    /// The indices of the inserted elements of the target sequence.
+    pub target: std::ops::Range<usize>,
+}
+
+/// Compute the shortest edit script transforming the `source` sequence into the `target` one. The
+/// script is returned as a list of hunks sorted by their position; the elements between the hunks
+/// are equal in both sequences.
+///
+/// If the edit distance exceeds [`MAX_EDIT_DISTANCE`], the script is not minimal: everything
+/// between the common prefix and suffix of the sequences is returned as a single hunk.
+pub fn hunks<T: PartialEq>(source: &[T], target: &[T]) -> Vec<Hunk> {
+    let prefix = source.iter().zip(target).take_while(|(a, b)| a == b).count();
+    let source_rest = &source[prefix..];
+    let target_rest = &target[prefix..];
+    let suffix = source_rest.iter().rev().zip(target_rest.iter().rev()).take_while(|(a, b)| a == b);
+    let suffix = suffix.count();
+    let source_diff = &source_rest[..source_rest.len() - suffix];
+    let target_diff = &target_rest[..target_rest.len() - suffix];
+    let shift = |hunk: Hunk| Hunk {
+        source: hunk.source.start + prefix..hunk.source.end + prefix,
+        target: hunk.target.start + prefix..hunk.target.end + prefix,
+    };
+    myers(source_diff, target_diff).into_iter().map(shift).collect()
+}
+
+/// The Myers algorithm. The furthest reaching paths of every iteration are remembered, and the
+/// edit script is recovered by walking them backwards from the end of both sequences.
+///
+/// Before the iteration `d`, only the paths on the diagonals `-d..=d` can be reached, so only
+/// these are remembered; the trace takes `O(D^2)` memory for the edit distance `D`.
+fn myers<T: PartialEq>(source: &[T], target: &[T]) -> Vec<Hunk> {
+    let source_len = source.len() as isize;
+    let target_len = target.len() as isize;
+    let max = source_len + target_len;
+    let index = |diagonal: isize| (diagonal + max) as usize;
+    let mut furthest = vec![0_isize; 2 * max as usize + 2];
+    let mut trace = Vec::new();
+    'search: for depth in 0..=max {
+        if depth > MAX_EDIT_DISTANCE as isize {
+            return vec![Hunk { source: 0..source.len(), target: 0..target.len() }];
+        }
+        trace.push(furthest[index(-depth)..=index(depth)].to_vec());
+        for diagonal in (-depth..=depth).step_by(2) {
+            let down = diagonal == -depth
+                || diagonal != depth
+                    && furthest[index(diagonal - 1)] < furthest[index(diagonal + 1)];
+            let mut x = if down {
+                furthest[index(diagonal + 1)]
+            } else {
+                furthest[index(diagonal - 1)] + 1
+            };
+            let mut y = x - diagonal;
+            while x < source_len && y < target_len && source[x as usize] == target[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            furthest[index(diagonal)] = x;
+            if x >= source_len && y >= target_len {
+                break 'search;
+            }
+        }
+    }
+
+    // Walk the trace backwards, collecting the non-diagonal moves as growing hunks.
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let (mut x, mut y) = (source_len, target_len);
+    for (depth, furthest) in trace.iter().enumerate().skip(1).rev() {
+        let depth = depth as isize;
+        let furthest = |diagonal: isize| furthest[(diagonal + depth) as usize];
+        let diagonal = x - y;
+        let down = diagonal == -depth
+            || diagonal != depth && furthest(diagonal - 1) < furthest(diagonal + 1);
+        let previous_diagonal = if down { diagonal + 1 } else { diagonal - 1 };
+        let previous_x = furthest(previous_diagonal);
+        let previous_y = previous_x - previous_diagonal;
+        let snake = if down { x - previous_x } else { y - previous_y };
+        let (move_x, move_y) = ((x - snake) as usize, (y - snake) as usize);
+        let (previous_x, previous_y) = (previous_x as usize, previous_y as usize);
+        match hunks.last_mut() {
+            Some(hunk) if snake == 0 => {
+                hunk.source.start = previous_x;
+                hunk.target.start = previous_y;
+            }
+            _ => hunks.push(Hunk { source: previous_x..move_x, target: previous_y..move_y }),
+        }
+        x = previous_x as isize;
+        y = previous_y as isize;
+    }
+    hunks.reverse();
+    hunks
+}
+
+
+
+// ============
+// === Diff ===
+// ============
+
+/// Compute the changes transforming the `source` text into the `target` one.
+///
+/// The changes are sorted by their position and do not overlap; their ranges refer to the byte
+/// offsets of the `source` text. When applied in reverse order, every change keeps the positions
+/// of the preceding ones valid.
+pub fn diff(source: &str, target: &str) -> Vec<Change<Bytes, String>> {
+    let source_lines = source.split_inclusive('\n').collect_vec();
+    let target_lines = target.split_inclusive('\n').collect_vec();
+    let offsets = |lines: &[&str]| {
+        let mut offsets = Vec::with_capacity(lines.len() + 1);
+        offsets.push(0);
+        offsets.extend(lines.iter().scan(0, |offset, line| {
+            *offset += line.len();
+            Some(*offset)
+        }));
+        offsets
+    };
+    let source_offsets = offsets(&source_lines);
+    let target_offsets = offsets(&target_lines);
+    let changes = hunks(&source_lines, &target_lines).into_iter().map(|hunk| {
+        let source_range = source_offsets[hunk.source.start]..source_offsets[hunk.source.end];
+        let target_range = target_offsets[hunk.target.start]..target_offsets[hunk.target.end];
+        narrowed_change(&source[source_range.clone()], &target[target_range.clone()], |offset| {
+            source_range.start + offset
+        })
+    });
+    changes.collect()
+}
+
+/// Create a change replacing the `source` with the `target`, skipping their common prefix and
+/// suffix. The `offset` function maps the byte offsets in `source` to the offsets in the whole
+/// text.
+fn narrowed_change(
+    source: &str,
+    target: &str,
+    offset: impl Fn(usize) -> usize,
+) -> Change<Bytes, String> {
+    let common_length = |a: &mut dyn Iterator<Item = char>, b: &mut dyn Iterator<Item = char>| {
+        a.zip(b).take_while(|(a, b)| a == b).map(|(a, _)| a.len_utf8()).sum::<usize>()
+    };
+    let prefix = common_length(&mut source.chars(), &mut target.chars());
+    let max_suffix = source.len().min(target.len()) - prefix;
+    let suffix = common_length(&mut source.chars().rev(), &mut target.chars().rev());
+    let suffix = if suffix > max_suffix {
+        // The suffix overlaps the prefix; shrink it to the nearest character boundary.
+        let shorter = if source.len() < target.len() { source } else { target };
+        (0..=max_suffix).rev().find(|s| shorter.is_char_boundary(shorter.len() - s)).unwrap_or(0)
+    } else {
+        suffix
+    };
+    let range = Range::new(Bytes::from(offset(prefix)), Bytes::from(offset(source.len() - suffix)));
+    let text = target[prefix..target.len() - suffix].to_owned();
+    Change { range, text }
+}
+
+impl Text {
+    /// Compute the changes transforming this text into the `target` one. See [`diff`] to learn
+    /// more.
+    pub fn diff(&self, target: &Text) -> Vec<Change<Bytes, String>> {
+        diff(&self.to_string(), &target.to_string())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Check that applying the changes in reverse order to the source gives the target, and return
+    /// the changes.
+    fn check_diff(source: &str, target: &str) -> Vec<Change<Bytes, String>> {
+        let changes = diff(source, target);
+        let mut result = source.to_owned();
+        for change in changes.iter().rev() {
+            change.apply(&mut result).unwrap();
+        }
+        assert_eq!(result, target, "Applying the diff of {source:?} and {target:?}.");
+        let ranges = changes.iter().map(|change| change.range);
+        assert!(ranges.clone().zip(ranges.skip(1)).all(|(a, b)| a.end <= b.start));
+        changes
+    }
+
+    /// The total size of the changes: the number of replaced and inserted bytes.
+    fn size(changes: &[Change<Bytes, String>]) -> usize {
+        let replaced =
+            changes.iter().map(|change| (change.range.end - change.range.start).as_usize());
+        let inserted = changes.iter().map(|change| change.text.len());
+        replaced.sum::<usize>() + inserted.sum::<usize>()
+    }
+
+    #[test]
+    fn sequence_hunks() {
+        let source = ['a', 'b', 'c', 'a', 'b', 'b', 'a'];
+        let target = ['c', 'b', 'a', 'b', 'a', 'c'];
+        let script = hunks(&source, &target);
+        let distance: usize = script.iter().map(|h| h.source.len() + h.target.len()).sum();
+        assert_eq!(distance, 5);
+        assert_eq!(hunks(&source, &source), vec![]);
+        assert_eq!(hunks(&[] as &[char], &['a']), vec![Hunk { source: 0..0, target: 0..1 }]);
+        assert_eq!(hunks(&['a'], &[]), vec![Hunk { source: 0..1, target: 0..0 }]);
+    }
+
+    #[test]
+    fn distant_sequences() {
+        // Every other element differs, so each of them is a hunk.
+        let count = MAX_EDIT_DISTANCE;
+        let source = (0..count).map(|i| if i % 2 == 0 { i } else { count + i }).collect_vec();
+        let target = (0..count).map(|i| if i % 2 == 0 { i } else { 2 * count + i }).collect_vec();
+        let script = hunks(&source, &target);
+        let distance: usize = script.iter().map(|h| h.source.len() + h.target.len()).sum();
+        assert_eq!(distance, MAX_EDIT_DISTANCE);
+        assert_eq!(script.len(), count / 2);
+
+        // One more difference exceeds the limit; the whole range between the common prefix and
+        // suffix is replaced.
+        let mut target = target;
+        target[0] = 3 * count;
+        let script = hunks(&source, &target);
+        assert_eq!(script, vec![Hunk { source: 0..count, target: 0..count }]);
+    }
+
+    #[test]
+    fn simple_changes() {
+        assert!(check_diff("", "").is_empty());
+        assert!(check_diff("same\ntext\n", "same\ntext\n").is_empty());
+        let changes = check_diff("", "new\ntext");
+        assert_eq!(changes, vec![Change::inserted(Bytes::from(0), "new\ntext".to_owned())]);
+        check_diff("old\ntext\n", "");
+        check_diff("a\nb\n", "a\nb");
+        check_diff("a\nb", "a\nb\n");
+        let changes = check_diff("333<->🌊12345\n", "333x🔥12345\n");
+        let range = Range::new(Bytes::from(3), Bytes::from(10));
+        assert_eq!(changes, vec![Change { range, text: "x🔥".to_owned() }]);
+    }
+
+    #[test]
+    fn multiple_hunks() {
+        let source = "main =\n    x = 1\n    y = 2\n    z = 3\n    w = 4\n    x + y + z + w\n";
+        let target = "main =\n    x = 10\n    y = 2\n    z = 3\n    x + y + z + w\n    done\n";
+        let changes = check_diff(source, target);
+        let texts = changes.iter().map(|change| change.text.as_str()).collect_vec();
+        assert_eq!(texts, vec!["0", "", "    done\n"]);
+
+        let source = "🌊\nα\nβ\nγ\nδ\n";
+        let target = "🌊\nα\nγ\nβ\nδ\nε\n";
+        check_diff(source, target);
+    }
+
+    #[test]
+    fn edit_sizes() {
+        let lines = (0..100).map(|i| format!("    line_{i} = {i}\n")).collect_vec();
+        let source = lines.concat();
+        let target = source.replace("line_3 ", "line_three ").replace("line_97 =", "line_97 :=");
+        let changes = check_diff(&source, &target);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(size(&changes), "three".len() + "3".len() + ":".len());
+
+        let common = Text::from(source.as_str()).common_prefix_and_suffix(&target.as_str().into());
+        let common = (common.prefix + common.suffix).as_usize();
+        let prefix_postfix_size = source.len() + target.len() - 2 * common;
+        assert!(size(&changes) * 100 < prefix_postfix_size);
+    }
+}
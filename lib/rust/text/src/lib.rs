@@ -93,6 +93,7 @@
 // === Export ===
 // ==============
 
+pub mod diff;
 pub mod range;
 pub mod rope;
 pub mod spans;
@@ -0,0 +1,210 @@
+//! Comparison of the operation statistics of two benchmark runs.
+
+use enso_prelude::*;
+
+use crate::Stats;
+
+use std::fmt;
+use time::Duration;
+
+
+
+// ==================
+// === Comparison ===
+// ==================
+
+/// The difference of a single operation between the baseline and the current run.
+#[derive(Debug)]
+pub struct Comparison {
+    /// The average duration of the operation in the baseline run.
+    pub baseline:  Duration,
+    /// The average duration of the operation in the current run.
+    pub current:   Duration,
+    /// The probability of observing such a difference if both runs had the same distribution of
+    /// durations.
+    pub p_value:   f64,
+    /// Whether the operation is slower by more than the threshold, and the difference is
+    /// statistically significant.
+    pub regressed: bool,
+    /// The operation log line.
+    pub line:      String,
+}
+
+impl Comparison {
+    /// The change of the average duration.
+    pub fn delta(&self) -> Duration {
+        self.current - self.baseline
+    }
+
+    /// The change of the average duration, relative to the baseline, in percents.
+    pub fn delta_percent(&self) -> f64 {
+        let baseline = self.baseline.as_seconds_f64();
+        let delta = self.delta().as_seconds_f64();
+        if delta == 0.0 {
+            0.0
+        } else if baseline == 0.0 {
+            f64::INFINITY * delta.signum()
+        } else {
+            delta / baseline * 100.0
+        }
+    }
+}
+
+impl Display for Comparison {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let baseline_millis = self.baseline.whole_milliseconds();
+        let current_millis = self.current.whole_milliseconds();
+        let delta_millis = self.delta().whole_milliseconds();
+        let delta_percent = self.delta_percent();
+        let p_value = self.p_value;
+        let marker = if self.regressed { "[REGRESSION] " } else { "" };
+        let truncated_line = self.line.chars().take(80).collect::<String>();
+
+        write!(
+            f,
+            "{baseline_millis}ms -> {current_millis}ms ({delta_millis:+}ms, {delta_percent:+.1}%, \
+            p={p_value:.3}) {marker}{truncated_line}"
+        )
+    }
+}
+
+/// Compare the statistics of the same operations in the baseline and the current run.
+///
+/// An operation regresses when its average duration grows by more than `threshold_percent`, and
+/// the Mann-Whitney U test finds the difference significant at the `significance` level.
+pub fn compare(
+    baseline: &[Stats],
+    current: &[Stats],
+    threshold_percent: f64,
+    significance: f64,
+) -> Vec<Comparison> {
+    baseline
+        .iter()
+        .zip(current)
+        .map(|(baseline, current)| {
+            let p_value = mann_whitney_p_value(&baseline.samples, &current.samples);
+            let mut comparison = Comparison {
+                baseline: baseline.avg,
+                current: current.avg,
+                p_value,
+                regressed: false,
+                line: current.line.clone(),
+            };
+            comparison.regressed =
+                comparison.delta_percent() > threshold_percent && p_value < significance;
+            comparison
+        })
+        .collect()
+}
+
+
+
+// ===========================
+// === Mann-Whitney U Test ===
+// ===========================
+
+/// The two-sided p-value of the Mann-Whitney U test, using the normal approximation with the tie
+/// and the continuity corrections. Unlike the t-test, it does not assume the durations to be
+/// normally distributed, which is rarely the case for latencies.
+fn mann_whitney_p_value(first: &[Duration], second: &[Duration]) -> f64 {
+    let first_len = first.len() as f64;
+    let second_len = second.len() as f64;
+    let len = first_len + second_len;
+    if first.is_empty() || second.is_empty() {
+        return 1.0;
+    }
+
+    let first_samples = first.iter().map(|duration| (*duration, true));
+    let second_samples = second.iter().map(|duration| (*duration, false));
+    let mut samples = first_samples.chain(second_samples).collect_vec();
+    samples.sort_by_key(|(duration, _)| *duration);
+
+    // Ranks start at 1, and the tied samples get the average of their ranks.
+    let mut first_rank_sum = 0.0;
+    let mut tie_correction = 0.0;
+    let mut start = 0;
+    while start < samples.len() {
+        let duration = samples[start].0;
+        let end = start + samples[start..].iter().take_while(|(d, _)| *d == duration).count();
+        let ties = (end - start) as f64;
+        let rank = start as f64 + (ties + 1.0) / 2.0;
+        let first_ties = samples[start..end].iter().filter(|(_, is_first)| *is_first).count();
+        first_rank_sum += rank * first_ties as f64;
+        tie_correction += ties.powi(3) - ties;
+        start = end;
+    }
+
+    let u = first_rank_sum - first_len * (first_len + 1.0) / 2.0;
+    let mean = first_len * second_len / 2.0;
+    let variance =
+        first_len * second_len / 12.0 * ((len + 1.0) - tie_correction / (len * (len - 1.0)));
+    if variance <= 0.0 {
+        return 1.0;
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / variance.sqrt();
+    erfc(z / std::f64::consts::SQRT_2)
+}
+
+/// The complementary error function of a non-negative argument, approximated with the maximum
+/// error of 1.5e-7 (Abramowitz and Stegun, formula 7.1.26).
+fn erfc(x: f64) -> f64 {
+    const P: f64 = 0.3275911;
+    const A: [f64; 5] = [0.254829592, -0.284496736, 1.421413741, -1.453152027, 1.061405429];
+    let t = 1.0 / (1.0 + P * x);
+    let polynomial = A.iter().rev().fold(0.0, |acc, a| acc * t + a) * t;
+    polynomial * (-x * x).exp()
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(values: &[i64]) -> Vec<Duration> {
+        values.iter().copied().map(Duration::milliseconds).collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        let message = format!("Expected {expected}, got {actual}.");
+        assert!((actual - expected).abs() < tolerance, "{}", message);
+    }
+
+    #[test]
+    fn erfc_values() {
+        assert_close(erfc(0.0), 1.0, 1.5e-7);
+        assert_close(erfc(0.5), 0.4795001222, 1.5e-7);
+        assert_close(erfc(1.0), 0.1572992071, 1.5e-7);
+        assert_close(erfc(2.0), 0.0046777350, 1.5e-7);
+    }
+
+    #[test]
+    fn mann_whitney_values() {
+        // The example of `scipy.stats.mannwhitneyu` with `method="asymptotic"`: U = 17.
+        let males = millis(&[19, 22, 16, 29, 24]);
+        let females = millis(&[20, 11, 17, 12]);
+        assert_close(mann_whitney_p_value(&males, &females), 0.1113468865, 1e-6);
+        assert_close(mann_whitney_p_value(&females, &males), 0.1113468865, 1e-6);
+        // Completely separated samples: U = 0.
+        let first = millis(&[1, 2, 3, 4, 5]);
+        let second = millis(&[6, 7, 8, 9, 10]);
+        assert_close(mann_whitney_p_value(&first, &second), 0.0121857804, 1e-6);
+        assert_eq!(mann_whitney_p_value(&first, &[]), 1.0);
+    }
+
+    #[test]
+    fn mann_whitney_ties() {
+        // U = 1.5, with the tie correction of the variance.
+        let first = millis(&[1, 2, 2, 3, 3, 3]);
+        let second = millis(&[3, 4, 4, 5, 6, 6]);
+        assert_close(mann_whitney_p_value(&first, &second), 0.0087327685, 1e-6);
+        // All samples are tied, so the variance is zero.
+        let same = millis(&[5, 5, 5]);
+        assert_eq!(mann_whitney_p_value(&same, &same), 1.0);
+        assert_close(mann_whitney_p_value(&first, &first), 1.0, 1e-9);
+    }
+}
@@ -0,0 +1,155 @@
+//! The structured model of the log lines written by the language server and the wstest tool.
+
+use lazy_static::lazy_static;
+use regex::Regex;
+use std::str::FromStr;
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+lazy_static! {
+    /// Regex for parsing the log line of the form `[level] [timestamp] [logger] message`.
+    static ref RE_LOGLINE: Regex =
+        Regex::new(r"^\[(\w+)\] \[([\w:.+-]+)\] \[([^\]]*)\] (.*)$").unwrap();
+
+    /// Regex for finding the id of the JSON-RPC message in the log message.
+    static ref RE_REQUEST_ID: Regex = Regex::new(r#""id"\s*:\s*("[^"]*"|-?\d+)"#).unwrap();
+}
+
+
+
+// =============
+// === Level ===
+// =============
+
+/// The log level, as written by the Enso logging service.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warning,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "error" => Ok(Level::Error),
+            "warn" | "warning" => Ok(Level::Warning),
+            "info" => Ok(Level::Info),
+            "debug" => Ok(Level::Debug),
+            "trace" => Ok(Level::Trace),
+            _ => Err(format!("Unknown log level [{s}].")),
+        }
+    }
+}
+
+
+
+// ===============
+// === LogLine ===
+// ===============
+
+/// A single parsed log line.
+#[allow(missing_docs)]
+#[derive(Clone, Debug)]
+pub struct LogLine {
+    pub level:     Level,
+    pub timestamp: OffsetDateTime,
+    pub logger:    String,
+    pub message:   String,
+}
+
+impl LogLine {
+    /// Parse the log line. Returns [`None`] for the lines not following the log format, like the
+    /// continuation lines of multi-line messages, and an error for the lines following the format
+    /// with an unrecognized level or timestamp.
+    pub fn parse(line: &str) -> Result<Option<Self>, String> {
+        let captures = match RE_LOGLINE.captures(line) {
+            Some(captures) => captures,
+            None => return Ok(None),
+        };
+        let level = captures[1].parse()?;
+        let timestamp = OffsetDateTime::parse(&captures[2], &Rfc3339)
+            .map_err(|error| format!("Invalid timestamp [{}]: {error}.", &captures[2]))?;
+        let logger = captures[3].to_owned();
+        let message = captures[4].to_owned();
+        Ok(Some(Self { level, timestamp, logger, message }))
+    }
+
+    /// The logger and the message, as they appear in the log line. The specification lines are
+    /// matched against this text.
+    pub fn text(&self) -> String {
+        format!("[{}] {}", self.logger, self.message)
+    }
+
+    /// The id of the first JSON-RPC message mentioned in the log message, like the request sent
+    /// by wstest or received by the language server.
+    pub fn request_id(&self) -> Option<String> {
+        let captures = RE_REQUEST_ID.captures(&self.message)?;
+        Some(captures[1].to_owned())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(line: &str) -> LogLine {
+        LogLine::parse(line).unwrap().unwrap()
+    }
+
+    #[test]
+    fn parse_wstest_line() {
+        let line = parse(
+            r#"[info] [2022-07-20T11:06:14.251Z] [main] wstest sent bench request [{"id":3}]"#,
+        );
+        assert_eq!(line.level, Level::Info);
+        assert_eq!(line.logger, "main");
+        assert_eq!(line.message, r#"wstest sent bench request [{"id":3}]"#);
+        assert_eq!(line.text(), r#"[main] wstest sent bench request [{"id":3}]"#);
+        assert_eq!(line.request_id(), Some("3".to_owned()));
+    }
+
+    #[test]
+    fn parse_language_server_line() {
+        let line = parse(
+            "[WARNING] [2022-07-20T13:06:14.251+02:00] [org.enso.languageserver.Main] \
+            Received {\"jsonrpc\": \"2.0\", \"id\": \"a-1\"}.",
+        );
+        assert_eq!(line.level, Level::Warning);
+        assert_eq!(line.logger, "org.enso.languageserver.Main");
+        assert_eq!(line.request_id(), Some("\"a-1\"".to_owned()));
+        let utc = parse("[error] [2022-07-20T11:06:14.251Z] [] Failed.");
+        assert_eq!(line.timestamp, utc.timestamp);
+        assert_eq!(utc.level, Level::Error);
+        assert_eq!(utc.logger, "");
+        assert_eq!(utc.request_id(), None);
+        assert_eq!(parse("[debug] [2022-07-20T11:06:14Z] [x] y").level, Level::Debug);
+        assert_eq!(parse("[Trace] [2022-07-20T11:06:14Z] [x] y").level, Level::Trace);
+        assert_eq!(parse("[warn] [2022-07-20T11:06:14Z] [x] y").level, Level::Warning);
+    }
+
+    #[test]
+    fn parse_other_lines() {
+        assert!(LogLine::parse("    at org.enso.Main.main(Main.scala:12)").unwrap().is_none());
+        assert!(LogLine::parse("").unwrap().is_none());
+        assert!(LogLine::parse("[fatal] [2022-07-20T11:06:14Z] [main] Failed.").is_err());
+        assert!(LogLine::parse("[info] [yesterday] [main] Done.").is_err());
+    }
+}
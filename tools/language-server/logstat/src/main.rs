@@ -13,12 +13,17 @@
 #![warn(missing_copy_implementations)]
 #![warn(missing_debug_implementations)]
 
+mod compare;
+mod log;
+
 use enso_prelude::*;
 
+use crate::log::LogLine;
+
 use clap::Parser;
 use clap::ValueHint;
 use lazy_static::lazy_static;
-use regex::Regex;
+use std::collections::HashMap;
 use std::fmt;
 use std::io::Result;
 use std::path::PathBuf;
@@ -60,6 +65,23 @@ struct Args {
     /// Calculate median instead of mean.
     #[clap(long)]
     median: bool,
+
+    /// Logfile of the baseline run to compare with.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    baseline: Option<PathBuf>,
+
+    /// Wstest log file of the baseline run.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    baseline_wstest_log: Option<PathBuf>,
+
+    /// Slowdown of an operation compared to the baseline, in percents, above which it is reported
+    /// as a regression.
+    #[clap(long, value_name = "PERCENT", default_value = "10")]
+    regression_threshold: f64,
+
+    /// Significance level of the test deciding if the slowdown is not caused by noise.
+    #[clap(long, default_value = "0.05")]
+    significance: f64,
 }
 
 
@@ -83,9 +105,10 @@ struct Spec {
 /// Timed operation.
 #[derive(Debug)]
 struct Operation {
-    duration:  Duration,
-    timestamp: OffsetDateTime,
-    line:      String,
+    duration:   Duration,
+    timestamp:  OffsetDateTime,
+    line:       String,
+    request_id: Option<String>,
 }
 
 impl Display for Operation {
@@ -111,6 +134,11 @@ struct Iteration {
 }
 
 impl Iteration {
+    /// The id of the request starting the iteration.
+    pub fn request_id(&self) -> Option<&String> {
+        self.operations.first()?.request_id.as_ref()
+    }
+
     pub fn total_time(&self) -> Duration {
         let mut total: Duration = Duration::ZERO;
         for operation in &self.operations {
@@ -129,10 +157,12 @@ impl Iteration {
 /// Final statistics about benchmarked operation.
 #[derive(Debug)]
 struct Stats {
-    min:  Duration,
-    max:  Duration,
-    avg:  Duration,
-    line: String,
+    min:     Duration,
+    max:     Duration,
+    avg:     Duration,
+    line:    String,
+    /// Durations of the operation in each iteration.
+    samples: Vec<Duration>,
 }
 
 impl Display for Stats {
@@ -155,16 +185,10 @@ impl Display for Stats {
 /// First operation of Wstest sequence of operations.
 static WSTEST_FIRST_OPERATION: usize = 1;
 
-/// Capture group containing the timestamp part of the log line.
-static RE_LOGLINE_TIMESTAMP_CAPTURE_GROUP: usize = 2;
-
-/// Capture group containing the message part of the log line
-static RE_LOGLINE_MESSAGE_CAPTURE_GROUP: usize = 3;
+/// Exit code signalling that some operations regressed compared to the baseline.
+static EXIT_CODE_REGRESSION: i32 = 2;
 
 lazy_static! {
-    /// Regex for parsing the log line.
-    static ref RE_LOGLINE: Regex = Regex::new(r"\[([\w]+)\] \[([\w\d:.-]+)\] (.*)").unwrap();
-
     /// Specification for the log file produced by the wstest tool.
     static ref WSTEST_SPEC: Spec = Spec {
         matches: vec![
@@ -198,39 +222,42 @@ async fn read_logfile(path: &PathBuf, spec: &Spec) -> Result<Vec<Iteration>> {
     let mut current_operations = vec![];
     let mut matches = spec.matches.iter();
     let mut current_match = matches.next().expect("Empty spec!");
+    let mut unrecognized_lines = 0;
+    let mut first_error = None;
 
     while let Some(line) = lines.next_line().await? {
-        if let Some(cap) = RE_LOGLINE.captures(line.as_str()) {
-            let groups = (
-                cap.get(RE_LOGLINE_TIMESTAMP_CAPTURE_GROUP),
-                cap.get(RE_LOGLINE_MESSAGE_CAPTURE_GROUP),
-            );
-            match groups {
-                (Some(timestamp), Some(message)) =>
-                    if message.as_str().contains(current_match) {
-                        let duration = Duration::ZERO;
-                        let timestamp =
-                            OffsetDateTime::parse(timestamp.as_str(), &Rfc3339).unwrap();
-                        let line = message.as_str().to_string();
-
-                        current_operations.push(Operation { duration, timestamp, line });
-
-                        if let Some(m) = matches.next() {
-                            current_match = m
-                        } else {
-                            matches = spec.matches.iter();
-                            current_match = matches.next().expect("Empty spec!");
-                            iterations.push(Iteration { operations: current_operations });
-                            current_operations = vec![];
-                        }
-                    },
-                _ => {
-                    eprintln!("[ERR] Invalid log line [{}]", line);
+        let log_line = LogLine::parse(&line).unwrap_or_else(|error| {
+            unrecognized_lines += 1;
+            first_error.get_or_insert(error);
+            None
+        });
+        if let Some(log_line) = log_line {
+            let text = log_line.text();
+            if text.contains(current_match) {
+                let duration = Duration::ZERO;
+                let timestamp = log_line.timestamp;
+                let request_id = log_line.request_id();
+
+                current_operations.push(Operation { duration, timestamp, line: text, request_id });
+
+                if let Some(m) = matches.next() {
+                    current_match = m
+                } else {
+                    matches = spec.matches.iter();
+                    current_match = matches.next().expect("Empty spec!");
+                    iterations.push(Iteration { operations: current_operations });
+                    current_operations = vec![];
                 }
             }
         }
     }
 
+    if let Some(error) = first_error {
+        let path = path.display();
+        eprintln!(
+            "[WARN] Skipped {unrecognized_lines} unrecognized log line(s) in {path}. {error}"
+        );
+    }
     Ok(iterations)
 }
 
@@ -259,6 +286,38 @@ fn merge_iterations(ws_iterations: &mut [Iteration], log_iterations: Vec<Iterati
     });
 }
 
+/// Match the wstest iterations with the logfile iterations handling the same requests.
+///
+/// The iterations are correlated by the request ids when each wstest iteration sends a request
+/// with a distinct id. Otherwise, the logfile iterations preceding the first wstest iteration are
+/// skipped as the warmup, and the remaining ones are matched in order.
+fn correlate_iterations(
+    ws_iterations: &[Iteration],
+    log_iterations: Vec<Iteration>,
+) -> Vec<Iteration> {
+    let ws_request_ids =
+        ws_iterations.iter().map(Iteration::request_id).collect::<Option<Vec<_>>>();
+    let unique_ids = ws_request_ids.filter(|ids| ids.iter().all_unique());
+    if let Some(ids) = unique_ids {
+        let mut by_request_id = HashMap::new();
+        for iteration in log_iterations {
+            if let Some(id) = iteration.request_id().cloned() {
+                by_request_id.insert(id, iteration);
+            }
+        }
+        ids.into_iter().filter_map(|id| by_request_id.remove(id)).collect()
+    } else {
+        let start_time = &ws_iterations[0].operations[0].timestamp;
+        log_iterations
+            .into_iter()
+            .skip_while(|iteration| {
+                let first_operation = &iteration.operations[0];
+                &first_operation.timestamp < start_time
+            })
+            .collect()
+    }
+}
+
 /// Calculate operation durations for each benchmark iteration.
 fn calculate_durations(iterations: &mut Vec<Iteration>) {
     for iteration in iterations {
@@ -284,6 +343,7 @@ fn analyze_iterations(iterations: &[Iteration], use_median: bool) -> Vec<Stats>
             let current_line = &iterations[0].operations[operation_index].line;
             let durations = iterations.iter().map(|it| it.operations[operation_index].duration);
 
+            let samples = durations.clone().collect_vec();
             let min = durations.clone().min().unwrap_or(Duration::ZERO);
             let max = durations.clone().max().unwrap_or(Duration::ZERO);
             let avg = if use_median {
@@ -293,7 +353,7 @@ fn analyze_iterations(iterations: &[Iteration], use_median: bool) -> Vec<Stats>
             };
             let line = current_line.to_string();
 
-            Stats { min, max, avg, line }
+            Stats { min, max, avg, line, samples }
         })
         .collect_vec();
 
@@ -305,53 +365,41 @@ fn analyze_iterations(iterations: &[Iteration], use_median: bool) -> Vec<Stats>
 
 /// Calculate the average of all iterations.
 fn iterations_average(ops: &[Iteration], stats: &[Stats]) -> Stats {
-    let min_opt = ops.iter().map(|o| o.total_time()).min();
-    let max_opt = ops.iter().map(|o| o.total_time()).max();
+    let samples = ops.iter().map(|o| o.total_time()).collect_vec();
+    let min_opt = samples.iter().min();
+    let max_opt = samples.iter().max();
 
-    let min = min_opt.unwrap_or(Duration::ZERO);
-    let max = max_opt.unwrap_or(Duration::ZERO);
+    let min = min_opt.copied().unwrap_or(Duration::ZERO);
+    let max = max_opt.copied().unwrap_or(Duration::ZERO);
     let avg = stats.iter().map(|s| s.avg).sum();
     let line = String::from("Total");
 
-    Stats { min, max, avg, line }
+    Stats { min, max, avg, line, samples }
 }
 
+/// Read the benchmark iterations from the logfile, merged with the wstest log if provided.
+async fn read_iterations(
+    log: &PathBuf,
+    wstest_log: Option<&PathBuf>,
+    spec: &Spec,
+    skip_iterations: usize,
+) -> Result<Vec<Iteration>> {
+    let log_iterations = read_logfile(log, spec).await?;
 
+    let mut iterations = if let Some(path_buf) = wstest_log {
+        let mut ws_iterations = read_logfile(path_buf, &WSTEST_SPEC).await?;
+        let log_iterations = correlate_iterations(&ws_iterations, log_iterations);
 
-// ============
-// === Main ===
-// ============
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
-    let spec = read_specs(&args.spec).await?;
-
-    let log_iterations = read_logfile(&args.log, &spec).await?;
-
-    let mut iterations = if let Some(path_buf) = args.wstest_log {
-        let mut ws_iterations = read_logfile(&path_buf, &WSTEST_SPEC).await?;
-
-        // skip warmup iterations
-        let start_time = &ws_iterations[0].operations[0].timestamp;
-        let log_iterations_without_warmup = log_iterations
-            .into_iter()
-            .skip_while(|iteration| {
-                let first_operation = &iteration.operations[0];
-                &first_operation.timestamp < start_time
-            })
-            .collect_vec();
-
-        if ws_iterations.len() != log_iterations_without_warmup.len() {
+        if ws_iterations.len() != log_iterations.len() {
             eprintln!(
                 "[ERR] Unequal number of benchmark iterations in log files! [{}] vs. [{}]",
                 ws_iterations.len(),
-                log_iterations_without_warmup.len()
+                log_iterations.len()
             );
             process::exit(1);
         }
 
-        merge_iterations(&mut ws_iterations, log_iterations_without_warmup);
+        merge_iterations(&mut ws_iterations, log_iterations);
 
         ws_iterations
     } else {
@@ -359,9 +407,25 @@ async fn main() -> Result<()> {
     };
 
     // cleanup iterations info before analyzing
-    iterations.drain(..args.skip_iterations);
+    iterations.drain(..skip_iterations);
     calculate_durations(&mut iterations);
 
+    Ok(iterations)
+}
+
+
+
+// ============
+// === Main ===
+// ============
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    let spec = read_specs(&args.spec).await?;
+
+    let iterations =
+        read_iterations(&args.log, args.wstest_log.as_ref(), &spec, args.skip_iterations).await?;
     let stats = analyze_iterations(&iterations, args.median);
 
     println!("avg [min..max] (of {} records)", iterations.len());
@@ -369,5 +433,35 @@ async fn main() -> Result<()> {
         println!("{}", s);
     }
 
+    if let Some(baseline_log) = &args.baseline {
+        let baseline_wstest_log = args.baseline_wstest_log.as_ref();
+        let baseline_iterations =
+            read_iterations(baseline_log, baseline_wstest_log, &spec, args.skip_iterations).await?;
+        let baseline_stats = analyze_iterations(&baseline_iterations, args.median);
+        if baseline_stats.len() != stats.len() {
+            eprintln!(
+                "[ERR] Unequal number of operations in the compared runs! [{}] vs. [{}]",
+                baseline_stats.len(),
+                stats.len()
+            );
+            process::exit(1);
+        }
+
+        let comparisons =
+            compare::compare(&baseline_stats, &stats, args.regression_threshold, args.significance);
+
+        println!();
+        println!("baseline -> current (delta, p-value) (of {} records)", baseline_iterations.len());
+        for comparison in &comparisons {
+            println!("{}", comparison);
+        }
+
+        let regressions = comparisons.iter().filter(|comparison| comparison.regressed).count();
+        if regressions > 0 {
+            eprintln!("[ERR] {regressions} operation(s) regressed compared to the baseline!");
+            process::exit(EXIT_CODE_REGRESSION);
+        }
+    }
+
     Ok(())
 }
@@ -15,9 +15,13 @@
 
 
 mod format;
+mod scenario;
+mod stats;
 
 use enso_prelude::*;
 
+use crate::scenario::Scenario;
+
 use std::path::PathBuf;
 use std::time::Duration;
 
@@ -101,6 +105,23 @@ struct Args {
     /// Time in milliseconds to wait before sending the next request from the `input` file.
     #[clap(long, value_name = "MILLISECONDS", default_value = "0")]
     wait_after_response: u64,
+
+    /// Path to a JSON file with the benchmark scenario, used instead of the `input` and the init
+    /// files. See the `scenario` module for the format description.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    scenario: Option<PathBuf>,
+
+    /// Time in milliseconds after which waiting for a message fails the scenario.
+    #[clap(long, value_name = "MILLISECONDS", default_value = "60000")]
+    scenario_timeout: u64,
+
+    /// File to write the scenario step latencies to, in JSON format.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    report_json: Option<PathBuf>,
+
+    /// File to write the scenario step latencies to, in CSV format.
+    #[clap(long, value_name = "FILE", value_hint = ValueHint::FilePath)]
+    report_csv: Option<PathBuf>,
 }
 
 
@@ -131,6 +152,40 @@ async fn read_lines(path_buf: PathBuf) -> std::io::Result<Vec<String>> {
 
 
 
+// ================
+// === Scenario ===
+// ================
+
+/// Run the benchmark scenario and write the latency reports.
+async fn run_scenario(
+    args: &Args,
+    path: &std::path::Path,
+    runner: &mut scenario::Runner,
+) -> Result<()> {
+    let scenario = Scenario::read(path).await?;
+    let wait_after_init = Duration::from_millis(args.wait_after_init);
+    let wait_after_warmup = Duration::from_millis(args.wait_after_warmup);
+    let report = runner
+        .run(
+            &scenario,
+            args.warmup_iterations,
+            args.benchmark_iterations,
+            wait_after_init,
+            wait_after_warmup,
+        )
+        .await?;
+    print!("{}", report.to_text());
+    if let Some(path) = &args.report_json {
+        tokio::fs::write(path, report.to_json()?).await?;
+    }
+    if let Some(path) = &args.report_csv {
+        tokio::fs::write(path, report.to_csv()).await?;
+    }
+    Ok(())
+}
+
+
+
 // ============
 // === Main ===
 // ============
@@ -140,25 +195,43 @@ async fn main() -> Result<()> {
     let args = Args::parse();
 
     // text socket connection
-    let text_socket_client = ClientBuilder::from_url(args.text_socket).async_connect().await?;
-    let (text_sink, text_stream) = text_socket_client.split();
+    let text_socket_client =
+        ClientBuilder::from_url(args.text_socket.clone()).async_connect().await?;
 
     // binary socket connection
-    let (binary_sink, binary_stream) = if let Some(binary_socket) = args.binary_socket {
-        let binary_client = ClientBuilder::from_url(binary_socket).async_connect().await?;
-        let (sink, stream) = binary_client.split::<Message>();
-        (Some(sink), Some(stream))
-    } else {
-        (None, None)
+    let binary_socket_client = match &args.binary_socket {
+        Some(binary_socket) =>
+            Some(ClientBuilder::from_url(binary_socket.clone()).async_connect().await?),
+        None => None,
     };
 
     // ignored responses
     let mut ignored_text_responses = regex::RegexSet::empty();
-    if let Some(path_buf) = args.ignore_text_socket_responses {
+    if let Some(path_buf) = args.ignore_text_socket_responses.clone() {
         let ignored_lines = read_lines(path_buf).await?;
         ignored_text_responses = regex::RegexSet::new(ignored_lines)?;
     }
 
+    if let Some(path) = &args.scenario {
+        let timeout = Duration::from_millis(args.scenario_timeout);
+        let mut runner = scenario::Runner::new(
+            text_socket_client,
+            binary_socket_client,
+            ignored_text_responses,
+            timeout,
+        );
+        return run_scenario(&args, path, &mut runner).await;
+    }
+
+    let (text_sink, text_stream) = text_socket_client.split();
+    let (binary_sink, binary_stream) = match binary_socket_client {
+        Some(binary_client) => {
+            let (sink, stream) = binary_client.split::<Message>();
+            (Some(sink), Some(stream))
+        }
+        None => (None, None),
+    };
+
     // synchronization channels between requests and responses
     let (text_tx, mut text_rx) = mpsc::channel::<SyncMessage>(1);
     let (binary_tx, mut binary_rx) = mpsc::channel::<SyncMessage>(1);
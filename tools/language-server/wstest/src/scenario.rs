@@ -0,0 +1,551 @@
+//! Scripted benchmark scenarios.
+//!
+//! A scenario is a JSON file with a list of `setup` steps, executed once, and a list of `steps`,
+//! executed in a loop for the warmup and the benchmark iterations. Each step does one of:
+//! - `"request": {...}` sends a JSON-RPC request and waits for the response with the same `id`. The
+//!   `id` is assigned automatically if missing. Error responses fail the scenario.
+//! - `"notify": {...}` sends a JSON-RPC notification without waiting for anything.
+//! - `"binary": "<base64>"` sends a binary message and waits for a binary response.
+//! - `"wait_for": {"method": ..., "params": {...}}` waits for a notification with the given method
+//!   and params containing the given ones. The notifications received since the last message was
+//!   sent are taken into account, so the step may follow the request triggering the notification.
+//! - `"wait_for_binary": {}` waits for a binary message, e.g. a visualisation update.
+//! - `"sleep": <milliseconds>` pauses the scenario.
+//!
+//! The received JSON message can be checked with `"assert": {"<JSON pointer>": <value>}`, and its
+//! fields can be stored in variables with `"capture": {"<variable>": "<JSON pointer>"}`. The
+//! variables are used in the messages as `"${variable}"`, which is replaced with the captured
+//! value, or inside longer strings. The `iteration` variable holds the number of the current
+//! iteration of `steps`.
+//!
+//! The latency of each benchmarked step is measured since the last message was sent until the
+//! awaited message is received.
+
+use enso_prelude::*;
+
+use crate::format;
+use crate::stats::Report;
+
+use futures::stream::LocalBoxStream;
+use futures::Sink;
+use futures::SinkExt;
+use futures::Stream;
+use futures::StreamExt;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::collections::VecDeque;
+use std::pin::Pin;
+use std::time::Duration;
+use std::time::Instant;
+use websocket_lite::Error;
+use websocket_lite::Message;
+use websocket_lite::Opcode;
+use websocket_lite::Result;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+const ITERATION_VARIABLE: &str = "iteration";
+
+
+
+// ================
+// === Scenario ===
+// ================
+
+/// A benchmark scenario.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Scenario {
+    /// The steps executed once, before the warmup.
+    #[serde(default)]
+    pub setup: Vec<Step>,
+    /// The steps executed in each warmup and benchmark iteration.
+    pub steps: Vec<Step>,
+}
+
+/// A single scenario step.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Step {
+    /// The name used in the reports. Defaults to the method of the sent or awaited message.
+    #[serde(default)]
+    pub name:       Option<String>,
+    /// What the step does.
+    #[serde(flatten)]
+    pub action:     Action,
+    /// Variables set to the values under the JSON pointers in the received message.
+    #[serde(default)]
+    pub capture:    BTreeMap<String, String>,
+    /// The expected values under the JSON pointers in the received message.
+    #[serde(default)]
+    pub assert:     BTreeMap<String, Value>,
+    /// Overrides the time after which waiting for a message fails the scenario.
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+/// The action performed by a [`Step`].
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Action {
+    /// Send a JSON-RPC request and wait for its response.
+    Request(Value),
+    /// Send a JSON-RPC notification.
+    Notify(Value),
+    /// Send a base64-encoded binary message and wait for a binary response.
+    Binary(String),
+    /// Wait for a notification with the given method and params containing the given ones.
+    WaitFor {
+        method: String,
+        #[serde(default)]
+        params: Option<Value>,
+    },
+    /// Wait for a binary message.
+    WaitForBinary {},
+    /// Pause for the given number of milliseconds.
+    Sleep(u64),
+}
+
+impl Step {
+    /// The name used in the reports.
+    pub fn name(&self) -> String {
+        let method = |message: &Value| message["method"].as_str().unwrap_or("<request>").to_owned();
+        self.name.clone().unwrap_or_else(|| match &self.action {
+            Action::Request(message) | Action::Notify(message) => method(message),
+            Action::Binary(_) => "<binary request>".to_owned(),
+            Action::WaitFor { method, .. } => method.clone(),
+            Action::WaitForBinary {} => "<binary response>".to_owned(),
+            Action::Sleep(millis) => format!("<sleep {millis}ms>"),
+        })
+    }
+
+    fn receives_json(&self) -> bool {
+        matches!(self.action, Action::Request(_) | Action::WaitFor { .. })
+    }
+}
+
+impl Scenario {
+    /// Read the scenario from a JSON file.
+    pub async fn read(path: &std::path::Path) -> Result<Self> {
+        let text = tokio::fs::read_to_string(path).await?;
+        let scenario: Self = serde_json::from_str(&text)?;
+        for step in scenario.setup.iter().chain(&scenario.steps) {
+            let checks_message = !step.capture.is_empty() || !step.assert.is_empty();
+            if checks_message && !step.receives_json() {
+                let name = step.name();
+                return Err(format!("Step {name} receives no JSON message to check.").into());
+            }
+        }
+        Ok(scenario)
+    }
+}
+
+
+
+// =============
+// === Phase ===
+// =============
+
+/// The part of the benchmark the step is executed in.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Setup,
+    Warmup,
+    Benchmark,
+}
+
+impl Phase {
+    fn log_request(self, message: &str) -> String {
+        match self {
+            Phase::Setup => format::init_request(message),
+            Phase::Warmup => format::warmup_request(message),
+            Phase::Benchmark => format::bench_request(message),
+        }
+    }
+}
+
+
+
+// ==============
+// === Runner ===
+// ==============
+
+/// A WebSocket connection, like the one created by [`websocket_lite::ClientBuilder`].
+pub trait Connection:
+    Sink<Message, Error = Error> + Stream<Item = Result<Message>> + 'static {
+}
+
+impl<T: Sink<Message, Error = Error> + Stream<Item = Result<Message>> + 'static> Connection for T {}
+
+type Socket =
+    (Pin<Box<dyn Sink<Message, Error = Error>>>, LocalBoxStream<'static, Result<Message>>);
+
+/// A message received from one of the sockets.
+#[derive(Clone, Debug)]
+enum Received {
+    Text(Value),
+    Binary,
+}
+
+/// The scenario execution state.
+#[derive(Derivative)]
+#[derivative(Debug)]
+pub struct Runner {
+    #[derivative(Debug = "ignore")]
+    text_socket:       Socket,
+    #[derivative(Debug = "ignore")]
+    binary_socket:     Option<Socket>,
+    ignored_responses: regex::RegexSet,
+    default_timeout:   Duration,
+    variables:         Variables,
+    /// The messages received since the last message was sent, which have not been awaited yet.
+    received:          VecDeque<Received>,
+    last_sent:         Instant,
+    next_request_id:   u64,
+}
+
+impl Runner {
+    /// Constructor.
+    pub fn new(
+        text_socket: impl Connection,
+        binary_socket: Option<impl Connection>,
+        ignored_responses: regex::RegexSet,
+        default_timeout: Duration,
+    ) -> Self {
+        fn split(socket: impl Connection) -> Socket {
+            let (sink, stream) = socket.split();
+            (Box::pin(sink), stream.boxed_local())
+        }
+        Self {
+            text_socket: split(text_socket),
+            binary_socket: binary_socket.map(split),
+            ignored_responses,
+            default_timeout,
+            variables: default(),
+            received: default(),
+            last_sent: Instant::now(),
+            next_request_id: 0,
+        }
+    }
+
+    /// Execute the setup steps, then the given number of warmup and benchmark iterations. Returns
+    /// the latencies of the benchmark iterations.
+    pub async fn run(
+        &mut self,
+        scenario: &Scenario,
+        warmup_iterations: usize,
+        benchmark_iterations: usize,
+        wait_after_init: Duration,
+        wait_after_warmup: Duration,
+    ) -> Result<Report> {
+        for step in &scenario.setup {
+            self.run_step(step, Phase::Setup).await?;
+        }
+        tokio::time::sleep(wait_after_init).await;
+        for iteration in 0..warmup_iterations {
+            self.variables.0.insert(ITERATION_VARIABLE.into(), iteration.into());
+            for step in &scenario.steps {
+                self.run_step(step, Phase::Warmup).await?;
+            }
+        }
+        tokio::time::sleep(wait_after_warmup).await;
+        let mut report = Report::new(scenario.steps.iter().map(Step::name));
+        for iteration in 0..benchmark_iterations {
+            self.variables.0.insert(ITERATION_VARIABLE.into(), iteration.into());
+            for (index, step) in scenario.steps.iter().enumerate() {
+                if let Some(latency) = self.run_step(step, Phase::Benchmark).await? {
+                    report.record(index, latency);
+                }
+            }
+        }
+        Ok(report)
+    }
+
+    /// Execute the step. Returns the time since the last message was sent until the awaited
+    /// message was received, if the step awaits any.
+    async fn run_step(&mut self, step: &Step, phase: Phase) -> Result<Option<Duration>> {
+        let timeout = step.timeout_ms.map_or(self.default_timeout, Duration::from_millis);
+        let received = match &step.action {
+            Action::Request(message) => {
+                let mut message = self.variables.substitute(message)?;
+                if message.get("id").is_none() {
+                    message["id"] = self.next_request_id.into();
+                    self.next_request_id += 1;
+                }
+                let id = message["id"].clone();
+                self.send_text(&message, phase).await?;
+                let response = self.receive_text(timeout, |msg| msg.get("id") == Some(&id)).await?;
+                if let Some(error) = response.get("error") {
+                    return Err(format!("Step {} failed with error {error}.", step.name()).into());
+                }
+                Some(response)
+            }
+            Action::Notify(message) => {
+                let message = self.variables.substitute(message)?;
+                self.send_text(&message, phase).await?;
+                return Ok(None);
+            }
+            Action::Binary(data) => {
+                let bytes = base64::decode(data)?;
+                self.send_binary(bytes, phase).await?;
+                self.receive_binary(timeout).await?;
+                None
+            }
+            Action::WaitFor { method, params } => {
+                let params =
+                    params.as_ref().map(|params| self.variables.substitute(params)).transpose()?;
+                let matches = |message: &Value| {
+                    let params_match = |params: &Value| contains(&message["params"], params);
+                    message.get("id").is_none()
+                        && message["method"] == method.as_str()
+                        && params.as_ref().map_or(true, params_match)
+                };
+                Some(self.receive_text(timeout, matches).await?)
+            }
+            Action::WaitForBinary {} => {
+                self.receive_binary(timeout).await?;
+                None
+            }
+            Action::Sleep(millis) => {
+                tokio::time::sleep(Duration::from_millis(*millis)).await;
+                return Ok(None);
+            }
+        };
+        let latency = self.last_sent.elapsed();
+        if let Some(message) = received {
+            self.check(step, &message)?;
+        }
+        Ok(Some(latency))
+    }
+
+    /// Check the step's assertions on the received message and capture its variables.
+    fn check(&mut self, step: &Step, message: &Value) -> Result<()> {
+        for (pointer, expected) in &step.assert {
+            let expected = self.variables.substitute(expected)?;
+            let actual = message.pointer(pointer);
+            if actual != Some(&expected) {
+                let name = step.name();
+                let actual = actual.map_or("nothing".to_owned(), Value::to_string);
+                let error = format!("Step {name} expected {expected} at {pointer}, got {actual}.");
+                return Err(error.into());
+            }
+        }
+        for (variable, pointer) in &step.capture {
+            let value = message.pointer(pointer).ok_or_else(|| {
+                format!("Step {} cannot capture {variable}: no value at {pointer}.", step.name())
+            })?;
+            self.variables.0.insert(variable.clone(), value.clone());
+        }
+        Ok(())
+    }
+
+    async fn send_text(&mut self, message: &Value, phase: Phase) -> Result<()> {
+        let text = message.to_string();
+        self.received.clear();
+        self.last_sent = Instant::now();
+        self.text_socket.0.send(Message::text(text.as_str())).await?;
+        println!("{}", phase.log_request(&text));
+        Ok(())
+    }
+
+    async fn send_binary(&mut self, bytes: Vec<u8>, phase: Phase) -> Result<()> {
+        let socket = self.binary_socket.as_mut().ok_or("The scenario needs a binary socket.")?;
+        self.received.clear();
+        self.last_sent = Instant::now();
+        socket.0.send(Message::binary(bytes)).await?;
+        println!("{}", phase.log_request(format::MESSAGE_BINARY));
+        Ok(())
+    }
+
+    /// Wait for a text message accepted by the predicate, unless one was received already.
+    async fn receive_text(
+        &mut self,
+        timeout: Duration,
+        predicate: impl Fn(&Value) -> bool,
+    ) -> Result<Value> {
+        let accepted = |received: &Received| match received {
+            Received::Text(message) => predicate(message),
+            Received::Binary => false,
+        };
+        match self.receive(timeout, accepted).await? {
+            Received::Text(message) => Ok(message),
+            Received::Binary => unreachable!("Binary messages are never accepted."),
+        }
+    }
+
+    /// Wait for a binary message, unless one was received already.
+    async fn receive_binary(&mut self, timeout: Duration) -> Result<()> {
+        self.receive(timeout, |received| matches!(received, Received::Binary)).await?;
+        Ok(())
+    }
+
+    async fn receive(
+        &mut self,
+        timeout: Duration,
+        accepted: impl Fn(&Received) -> bool,
+    ) -> Result<Received> {
+        if let Some(index) = self.received.iter().position(&accepted) {
+            return Ok(self.received.remove(index).expect("The index is in bounds."));
+        }
+        let deadline = tokio::time::Instant::now() + timeout;
+        loop {
+            let next = tokio::time::timeout_at(deadline, self.next_message()).await;
+            let received = next.map_err(|_| "Timed out waiting for a message.")??;
+            if accepted(&received) {
+                return Ok(received);
+            }
+            self.received.push_back(received);
+        }
+    }
+
+    /// Receive the next message from either of the sockets, skipping the ignored ones.
+    async fn next_message(&mut self) -> Result<Received> {
+        loop {
+            let binary_socket = self.binary_socket.as_mut().map(|socket| &mut socket.1);
+            let next_binary = async {
+                match binary_socket {
+                    Some(stream) => stream.next().await,
+                    None => futures::future::pending().await,
+                }
+            };
+            let message = tokio::select! {
+                message = self.text_socket.1.next() => message,
+                message = next_binary => message,
+            };
+            let message = message.ok_or("The connection has been closed.")??;
+            match message.opcode() {
+                Opcode::Text => {
+                    let text = message.as_text().unwrap_or_default();
+                    if self.ignored_responses.is_match(text) {
+                        println!("{}", format::response_ignored(text));
+                    } else {
+                        println!("{}", format::response_text(text));
+                        return Ok(Received::Text(serde_json::from_str(text)?));
+                    }
+                }
+                Opcode::Binary => {
+                    println!("{}", format::response_binary());
+                    return Ok(Received::Binary);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+
+/// Check if the `expected` value is a part of the `actual` one: all fields of the expected objects
+/// are present in the actual ones and contain the expected values.
+fn contains(actual: &Value, expected: &Value) -> bool {
+    match (actual, expected) {
+        (Value::Object(actual), Value::Object(expected)) => expected
+            .iter()
+            .all(|(key, value)| actual.get(key).map_or(false, |actual| contains(actual, value))),
+        (actual, expected) => actual == expected,
+    }
+}
+
+
+
+// =================
+// === Variables ===
+// =================
+
+/// The values of the variables used in the scenario messages, by their names.
+#[derive(Clone, Debug, Default)]
+struct Variables(BTreeMap<String, Value>);
+
+impl Variables {
+    /// Replace the variables used in the strings of the message with their values.
+    fn substitute(&self, message: &Value) -> Result<Value> {
+        Ok(match message {
+            Value::String(text) => {
+                let variable = text.strip_prefix("${").and_then(|text| text.strip_suffix('}'));
+                match variable {
+                    Some(name) if !name.contains('}') => self.variable(name)?.clone(),
+                    _ => Value::String(self.interpolate(text)?),
+                }
+            }
+            Value::Array(items) => {
+                let items = items.iter().map(|item| self.substitute(item));
+                Value::Array(items.collect::<Result<_>>()?)
+            }
+            Value::Object(fields) => {
+                let fields =
+                    fields.iter().map(|(key, value)| Ok((key.clone(), self.substitute(value)?)));
+                Value::Object(fields.collect::<Result<_>>()?)
+            }
+            other => other.clone(),
+        })
+    }
+
+    fn interpolate(&self, text: &str) -> Result<String> {
+        let mut result = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find("${") {
+            let end =
+                rest[start..].find('}').ok_or_else(|| format!("Unclosed variable in {text}."))?;
+            result += &rest[..start];
+            match self.variable(&rest[start + 2..start + end])? {
+                Value::String(value) => result += value,
+                value => result += &value.to_string(),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        result += rest;
+        Ok(result)
+    }
+
+    fn variable(&self, name: &str) -> Result<&Value> {
+        self.0.get(name).ok_or_else(|| format!("Unknown variable {name}.").into())
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use serde_json::json;
+
+    fn variables() -> Variables {
+        let variables = [("id", json!("abc")), ("n", json!(3)), ("obj", json!({"a": [1]}))];
+        Variables(variables.into_iter().map(|(name, value)| (name.to_owned(), value)).collect())
+    }
+
+    #[test]
+    fn substitute_whole_strings() {
+        let variables = variables();
+        let message = json!({"id": "${id}", "params": ["${n}", {"value": "${obj}"}], "x": 1});
+        let expected = json!({"id": "abc", "params": [3, {"value": {"a": [1]}}], "x": 1});
+        assert_eq!(variables.substitute(&message).unwrap(), expected);
+    }
+
+    #[test]
+    fn substitute_inside_strings() {
+        let variables = variables();
+        let substitute = |text: &str| variables.substitute(&json!(text)).unwrap();
+        assert_eq!(substitute("file_${id}.enso"), json!("file_abc.enso"));
+        assert_eq!(substitute("${n}${id}"), json!("3abc"));
+        assert_eq!(substitute("${obj}!"), json!(r#"{"a":[1]}!"#));
+        assert_eq!(substitute("no variables"), json!("no variables"));
+        assert_eq!(substitute("${id}-${id}"), json!("abc-abc"));
+    }
+
+    #[test]
+    fn substitute_errors() {
+        let variables = variables();
+        assert!(variables.substitute(&json!("${unknown}")).is_err());
+        assert!(variables.substitute(&json!(["x ${id"])).is_err());
+        assert!(variables.substitute(&json!({"a": "${unknown} text"})).is_err());
+    }
+}
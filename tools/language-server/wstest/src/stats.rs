@@ -0,0 +1,237 @@
+//! Latency statistics of the benchmarked scenario steps.
+
+use enso_prelude::*;
+
+use serde::Serialize;
+use std::time::Duration;
+
+
+
+// =================
+// === Constants ===
+// =================
+
+/// The upper bound of the first histogram bucket. Each next bucket's bound is twice as large.
+const FIRST_BUCKET_BOUND_MS: f64 = 1.0;
+
+/// The percentiles reported for each step.
+const PERCENTILES: [u8; 4] = [50, 90, 95, 99];
+
+const CSV_HEADER: &str = "step,count,min_ms,mean_ms,p50_ms,p90_ms,p95_ms,p99_ms,max_ms";
+
+
+
+// =================
+// === Latencies ===
+// =================
+
+/// The latencies measured for a single scenario step.
+#[derive(Clone, Debug, Default)]
+pub struct Latencies {
+    samples: Vec<Duration>,
+}
+
+impl Latencies {
+    /// Record the latency of a single step execution.
+    pub fn record(&mut self, latency: Duration) {
+        self.samples.push(latency);
+    }
+
+    /// Compute the statistics of the recorded latencies. Returns [`None`] if nothing has been
+    /// recorded.
+    pub fn summary(&self) -> Option<Summary> {
+        let mut samples = self.samples.clone();
+        samples.sort();
+        let samples = samples.into_iter().map(as_millis).collect_vec();
+        let count = samples.len();
+        let min = *samples.first()?;
+        let max = *samples.last()?;
+        let mean = samples.iter().sum::<f64>() / count as f64;
+        let percentiles = PERCENTILES.map(|percentile| Percentile {
+            percentile,
+            value_ms: nearest_rank(&samples, percentile),
+        });
+        let histogram = histogram(&samples);
+        Some(Summary { count, min_ms: min, mean_ms: mean, max_ms: max, percentiles, histogram })
+    }
+}
+
+fn as_millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+/// The value below which the given percent of the sorted `samples` falls, by the nearest-rank
+/// method.
+fn nearest_rank(samples: &[f64], percentile: u8) -> f64 {
+    let rank = (percentile as f64 / 100.0 * samples.len() as f64).ceil() as usize;
+    samples[rank.max(1) - 1]
+}
+
+/// Count the sorted `samples` in buckets with exponentially growing bounds, up to the first
+/// bucket containing the largest sample.
+fn histogram(samples: &[f64]) -> Vec<Bucket> {
+    let mut buckets = vec![];
+    let mut upper_bound_ms = FIRST_BUCKET_BOUND_MS;
+    let mut remaining = samples;
+    while !remaining.is_empty() {
+        let count = remaining.iter().take_while(|sample| **sample <= upper_bound_ms).count();
+        buckets.push(Bucket { upper_bound_ms, count });
+        remaining = &remaining[count..];
+        upper_bound_ms *= 2.0;
+    }
+    buckets
+}
+
+
+
+// ===============
+// === Summary ===
+// ===============
+
+/// The statistics of the latencies of a single step, in milliseconds.
+#[allow(missing_docs)]
+#[derive(Clone, Debug, Serialize)]
+pub struct Summary {
+    pub count:       usize,
+    pub min_ms:      f64,
+    pub mean_ms:     f64,
+    pub max_ms:      f64,
+    pub percentiles: [Percentile; PERCENTILES.len()],
+    pub histogram:   Vec<Bucket>,
+}
+
+/// The latency below which the given percent of samples falls.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Percentile {
+    pub percentile: u8,
+    pub value_ms:   f64,
+}
+
+/// The number of samples in the histogram bucket, between the previous bucket's bound
+/// (exclusive) and `upper_bound_ms` (inclusive).
+#[allow(missing_docs)]
+#[derive(Clone, Copy, Debug, Serialize)]
+pub struct Bucket {
+    pub upper_bound_ms: f64,
+    pub count:          usize,
+}
+
+
+
+// ==============
+// === Report ===
+// ==============
+
+/// The latencies of all benchmarked steps, in the order of the scenario.
+#[derive(Clone, Debug, Default)]
+pub struct Report {
+    steps: Vec<(String, Latencies)>,
+}
+
+#[derive(Serialize)]
+struct StepReport<'a> {
+    step:    &'a str,
+    #[serde(flatten)]
+    summary: Summary,
+}
+
+impl Report {
+    /// Create a report of steps with the given names.
+    pub fn new(step_names: impl IntoIterator<Item = String>) -> Self {
+        let steps = step_names.into_iter().map(|name| (name, default())).collect();
+        Self { steps }
+    }
+
+    /// Record the latency of the step with the given index.
+    pub fn record(&mut self, step: usize, latency: Duration) {
+        if let Some((_, latencies)) = self.steps.get_mut(step) {
+            latencies.record(latency);
+        }
+    }
+
+    fn summaries(&self) -> impl Iterator<Item = StepReport<'_>> {
+        self.steps.iter().filter_map(|(step, latencies)| {
+            latencies.summary().map(|summary| StepReport { step, summary })
+        })
+    }
+
+    /// The report in JSON format: a list of step summaries.
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.summaries().collect_vec())
+    }
+
+    /// The report in CSV format, with a row for each step. The histograms are omitted.
+    pub fn to_csv(&self) -> String {
+        let mut csv = format!("{}\n", CSV_HEADER);
+        for StepReport { step, summary } in self.summaries() {
+            let Summary { count, min_ms, mean_ms, max_ms, percentiles, .. } = summary;
+            let percentiles = percentiles.iter().map(|p| format!("{:.3}", p.value_ms)).join(",");
+            let step = step.replace('"', "\"\"");
+            csv +=
+                &format!("\"{step}\",{count},{min_ms:.3},{mean_ms:.3},{percentiles},{max_ms:.3}\n");
+        }
+        csv
+    }
+
+    /// The human-readable summary, with a line for each step.
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for StepReport { step, summary } in self.summaries() {
+            let Summary { count, min_ms, mean_ms, max_ms, percentiles, .. } = summary;
+            let percentiles = percentiles
+                .iter()
+                .map(|p| format!("p{} {:.1}ms", p.percentile, p.value_ms))
+                .join(", ");
+            text += &format!(
+                "{step}: {mean_ms:.1}ms [{min_ms:.1}..{max_ms:.1}] {percentiles} \
+                (of {count} records)\n"
+            );
+        }
+        text
+    }
+}
+
+
+
+// =============
+// === Tests ===
+// =============
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nearest_rank_percentiles() {
+        let samples = [15.0, 20.0, 35.0, 40.0, 50.0];
+        assert_eq!(nearest_rank(&samples, 5), 15.0);
+        assert_eq!(nearest_rank(&samples, 30), 20.0);
+        assert_eq!(nearest_rank(&samples, 40), 20.0);
+        assert_eq!(nearest_rank(&samples, 50), 35.0);
+        assert_eq!(nearest_rank(&samples, 99), 50.0);
+        assert_eq!(nearest_rank(&samples, 100), 50.0);
+        assert_eq!(nearest_rank(&[7.0], 0), 7.0);
+    }
+
+    #[test]
+    fn histogram_buckets() {
+        let buckets = histogram(&[0.5, 1.0, 1.5, 3.0, 9.0]);
+        let buckets = buckets.iter().map(|b| (b.upper_bound_ms, b.count)).collect_vec();
+        assert_eq!(buckets, vec![(1.0, 2), (2.0, 1), (4.0, 1), (8.0, 0), (16.0, 1)]);
+        assert!(histogram(&[]).is_empty());
+    }
+
+    #[test]
+    fn csv_report() {
+        let mut report = Report::new(["a \"quoted\" step".to_owned(), "empty".to_owned()]);
+        for millis in [3, 1, 4, 2] {
+            report.record(0, Duration::from_millis(millis));
+        }
+        report.record(2, Duration::from_millis(1));
+        let expected = format!(
+            "{CSV_HEADER}\n\"a \"\"quoted\"\" step\",4,1.000,2.500,2.000,4.000,4.000,4.000,4.000\n"
+        );
+        assert_eq!(report.to_csv(), expected);
+    }
+}